@@ -9,9 +9,12 @@
 
 use sqlx::postgres::{PgPoolOptions, PgPool};
 use sqlx::{Row, Error};
+use std::collections::HashMap;
 use std::time::Duration;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use futures::Stream;
+use tokio_stream::wrappers::ReceiverStream;
 
 // -----------------------------------------------------------------
 // 1. هياكل البيانات (Data Structures)
@@ -29,6 +32,120 @@ pub struct MarketTick {
     pub is_anomalous: bool, // علامة جنائية: هل كانت البيانات مشبوهة؟
 }
 
+/// شمعة OHLCV واحدة محسوبة من `market_ticks` عبر `time_bucket`، بدل تجميع النبضات
+/// الخام يدوياً في كل مستهلك (Backtester، مدرّب الذكاء الاصطناعي، الواجهة).
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Candle {
+    pub bucket_start: DateTime<Utc>,
+    pub symbol: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// صف أمر نشط كما يُخزَّن في جدول 'orders' داخل TimescaleDB، بالحد الأدنى اللازم
+/// لمعرفة هل الأمر لا يزال مفتوحاً أم وصل لحالة نهائية.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: i64,
+    pub symbol: String,
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+    pub expire_at: Option<DateTime<Utc>>,
+}
+
+impl OpenOrder {
+    fn is_terminal(&self) -> bool {
+        matches!(self.status.as_str(), "Filled" | "Canceled" | "Rejected" | "Expired")
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expire_at.map_or(false, |expire_at| now >= expire_at)
+    }
+}
+
+// -----------------------------------------------------------------
+// 1.1 ذاكرة الأوامر المفتوحة التصاعدية (Incremental Open-Order Cache)
+// -----------------------------------------------------------------
+
+/// ذاكرة مُخَبَّأة (Cache) لكل الأوامر المفتوحة حالياً: تُحمَّل مرة واحدة عبر استعلام
+/// كامل ثقيل عند الإقلاع (`bootstrap`)، ثم تُحدَّث لاحقاً بجلب السطور التي تغيّرت فقط
+/// منذ آخر تحديث رأيناه (`refresh`)، بدل إعادة مسح الجدول بالكامل مع كل نبضة (Tick).
+pub struct OpenOrderCache {
+    orders: HashMap<i64, OpenOrder>,
+    /// أحدث `updated_at` رأيناه حتى الآن؛ كل تحديث تصاعدي لاحق يطلب فقط ما هو أحدث منه.
+    last_seen: DateTime<Utc>,
+}
+
+impl OpenOrderCache {
+    /// يحمّل المجموعة الكاملة للأوامر النشطة حالياً عبر استعلام ثقيل واحد، ويُستخدم
+    /// فقط عند الإقلاع (أو إعادة المزامنة الكاملة بعد انقطاع طويل).
+    pub async fn bootstrap(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let rows = sqlx::query_as::<_, OpenOrder>(
+            "SELECT order_id, symbol, status, updated_at, expire_at FROM orders
+             WHERE status NOT IN ('Filled', 'Canceled', 'Rejected', 'Expired')"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let last_seen = rows.iter().map(|o| o.updated_at).max().unwrap_or_else(Utc::now);
+        let orders = rows.into_iter().map(|o| (o.order_id, o)).collect();
+
+        Ok(Self { orders, last_seen })
+    }
+
+    /// يجلب فقط السطور التي تغيّرت منذ آخر تحديث رأيناه (`updated_at > last_seen`) ثم
+    /// يدمجها عبر `apply_delta`. هذا ما يحوّل التحديث من مسح كامل متعدد الثواني إلى
+    /// استعلام محدود يصلح للاستدعاء في كل Block/Tick.
+    pub async fn refresh(&mut self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let delta = sqlx::query_as::<_, OpenOrder>(
+            "SELECT order_id, symbol, status, updated_at, expire_at FROM orders
+             WHERE updated_at > $1
+             ORDER BY updated_at ASC"
+        )
+        .bind(self.last_seen)
+        .fetch_all(pool)
+        .await?;
+
+        self.apply_delta(delta);
+        Ok(())
+    }
+
+    /// يدمج دفعة من الأوامر المتغيّرة (من `refresh` أو من دلتا إلغاء/تنفيذ صريحة تصل من
+    /// مصدر آخر) في الذاكرة: يُدخل/يحدّث كل واحد منها ويرفع `last_seen` لأحدث طابع زمني
+    /// رآه، ثم يُبقي (`retain`) فقط الأوامر التي ما زالت مفتوحة فعلاً - مُسقطاً أي أمر
+    /// أصبح `Filled`/`Canceled`/`Rejected`/`Expired`، أو تجاوز `expire_at` الخاص بـ GTD
+    /// وقته الحالي.
+    pub fn apply_delta(&mut self, delta: Vec<OpenOrder>) {
+        let now = Utc::now();
+
+        for order in delta {
+            if order.updated_at > self.last_seen {
+                self.last_seen = order.updated_at;
+            }
+            self.orders.insert(order.order_id, order);
+        }
+
+        self.orders.retain(|_, order| !order.is_terminal() && !order.is_expired(now));
+    }
+
+    /// عدد الأوامر المفتوحة حالياً في الذاكرة.
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// كل الأوامر المفتوحة حالياً، لإعادة حساب حالة المطابقة/المزاد في كل Block/Tick.
+    pub fn open_orders(&self) -> impl Iterator<Item = &OpenOrder> {
+        self.orders.values()
+    }
+}
+
 // -----------------------------------------------------------------
 // 2. مدير قاعدة البيانات (Database Manager)
 // -----------------------------------------------------------------
@@ -152,6 +269,122 @@ impl TSDBManager {
         Ok(rows)
     }
 
+    /// التجميع إلى شموع OHLCV (Server-Side Candle Aggregation).
+    /// يستخدم `time_bucket` الخاصة بـ TimescaleDB لحساب الفتح/الأعلى/الأدنى/الإغلاق/الحجم
+    /// لكل نافذة زمنية داخل قاعدة البيانات مباشرة، بدل سحب ملايين النبضات الخام عبر
+    /// الشبكة ثم تجميعها يدوياً في كل مستهلك.
+    pub async fn fetch_candles(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Duration,
+    ) -> Result<Vec<Candle>, sqlx::Error> {
+        let bucket_width = format!("{} seconds", interval.as_secs_f64());
+
+        let query = "
+            SELECT
+                time_bucket($1::interval, time) AS bucket_start,
+                symbol,
+                (array_agg(price ORDER BY time ASC))[1] AS open,
+                max(price) AS high,
+                min(price) AS low,
+                (array_agg(price ORDER BY time DESC))[1] AS close,
+                sum(quantity) AS volume
+            FROM market_ticks
+            WHERE symbol = $2 AND time >= $3 AND time <= $4
+            GROUP BY bucket_start, symbol
+            ORDER BY bucket_start ASC
+        ";
+
+        let rows = sqlx::query_as::<_, Candle>(query)
+            .bind(bucket_width)
+            .bind(symbol)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// تسجيل/تحديث تجميع مستمر (Continuous Aggregate) لمدة دلو زمني معيّنة (1s, 1m, 1h...)
+    /// كي تُحسَب الشموع تصاعدياً في الخلفية بدل إعادة حسابها بالكامل مع كل استعلام.
+    /// `view_name` يجب أن يكون فريداً لكل مدة (مثلاً "candles_1m")، ويُستدعى مرة واحدة
+    /// عند الإقلاع/الترحيل - وهو idempotent بفضل `IF NOT EXISTS`.
+    pub async fn ensure_continuous_aggregate(
+        &self,
+        view_name: &str,
+        interval: Duration,
+    ) -> Result<(), sqlx::Error> {
+        let bucket_width = format!("{} seconds", interval.as_secs_f64());
+
+        let create_view = format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {view_name}
+             WITH (timescaledb.continuous) AS
+             SELECT
+                 time_bucket('{bucket_width}', time) AS bucket_start,
+                 symbol,
+                 (array_agg(price ORDER BY time ASC))[1] AS open,
+                 max(price) AS high,
+                 min(price) AS low,
+                 (array_agg(price ORDER BY time DESC))[1] AS close,
+                 sum(quantity) AS volume
+             FROM market_ticks
+             GROUP BY bucket_start, symbol
+             WITH NO DATA"
+        );
+        sqlx::query(&create_view).execute(&self.pool).await?;
+
+        // سياسة تحديث تلقائي: تُبقي آخر ساعة بلا تجميد (start_offset) وتُعيد الحساب كل دقيقة.
+        let refresh_policy = format!(
+            "SELECT add_continuous_aggregate_policy('{view_name}',
+                 start_offset => INTERVAL '1 hour',
+                 end_offset => INTERVAL '{bucket_width}',
+                 schedule_interval => INTERVAL '1 minute',
+                 if_not_exists => true)"
+        );
+        sqlx::query(&refresh_policy).execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    /// إعادة تشغيل السوق (Market Replay).
+    /// يبث النبضات التاريخية بنفس توقيت وصولها الأصلي (الفارق بين كل نبضة وأخرى)
+    /// مُقاساً بعامل `speed` (2.0 يعني ضعف السرعة، 0.5 يعني نصفها)، كي تُختبر
+    /// الاستراتيجيات مقابل بيانات مسجّلة كما وصلت فعلاً بدل تفريغها دفعة واحدة.
+    pub async fn replay(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        speed: f64,
+    ) -> Result<impl Stream<Item = MarketTick>, sqlx::Error> {
+        let ticks = self.fetch_history(symbol, start, end).await?;
+        let (tx, rx) = tokio::sync::mpsc::channel(256);
+
+        tokio::spawn(async move {
+            let mut previous_time: Option<DateTime<Utc>> = None;
+
+            for tick in ticks {
+                if let Some(prev) = previous_time {
+                    let gap = tick.time - prev;
+                    if let Ok(gap) = gap.to_std() {
+                        let paced = gap.div_f64(speed.max(f64::EPSILON));
+                        tokio::time::sleep(paced).await;
+                    }
+                }
+                previous_time = Some(tick.time);
+
+                if tx.send(tick).await.is_err() {
+                    break; // المستهلك أغلق الطرف الآخر، لا داعي للاستمرار
+                }
+            }
+        });
+
+        Ok(ReceiverStream::new(rx))
+    }
+
     /// إغلاق الاتصال بأمان.
     pub async fn close(&self) {
         self.pool.close().await;
@@ -180,4 +413,41 @@ mod tests {
         assert_eq!(tick.symbol, "BTCUSDT");
         assert!(tick.price > 0.0);
     }
+
+    #[test]
+    fn apply_delta_drops_terminal_and_expired_orders() {
+        let mut cache = OpenOrderCache {
+            orders: HashMap::new(),
+            last_seen: DateTime::<Utc>::MIN_UTC,
+        };
+
+        let now = Utc::now();
+        cache.apply_delta(vec![
+            OpenOrder {
+                order_id: 1,
+                symbol: "BTCUSDT".to_string(),
+                status: "New".to_string(),
+                updated_at: now,
+                expire_at: None,
+            },
+            OpenOrder {
+                order_id: 2,
+                symbol: "BTCUSDT".to_string(),
+                status: "Filled".to_string(),
+                updated_at: now,
+                expire_at: None,
+            },
+            OpenOrder {
+                order_id: 3,
+                symbol: "ETHUSDT".to_string(),
+                status: "New".to_string(),
+                updated_at: now,
+                expire_at: Some(now - chrono::Duration::seconds(1)),
+            },
+        ]);
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.open_orders().next().unwrap().order_id, 1);
+        assert_eq!(cache.last_seen, now);
+    }
 }
\ No newline at end of file
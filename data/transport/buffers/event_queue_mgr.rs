@@ -9,8 +9,11 @@
  * ==============================================================================
  */
 
+use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use lru::LruCache;
+use parking_lot::Mutex;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
 
@@ -25,29 +28,76 @@ pub enum IngestionEvent {
     Terminate,
 }
 
+/// مفتاح الإزالة المزدوجة: يميز Tick بـ (الرمز، الوقت) وSnapshot بالرمز وحده،
+/// لأن اللقطات المتتالية لنفس الرمز تكون غالباً نسخة مكررة من نفس العمق.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DedupKey {
+    Tick(String, u64),
+    Snapshot(String),
+}
+
+impl IngestionEvent {
+    /// مفتاح الإزالة المزدوجة لهذا الحدث، أو None إذا كان النوع لا يخضع لها أصلاً
+    fn dedup_key(&self) -> Option<DedupKey> {
+        match self {
+            IngestionEvent::MarketTick { symbol, ts, .. } => Some(DedupKey::Tick(symbol.clone(), *ts)),
+            IngestionEvent::OrderBookSnapshot { symbol, .. } => Some(DedupKey::Snapshot(symbol.clone())),
+            IngestionEvent::SystemSignal { .. } | IngestionEvent::Terminate => None,
+        }
+    }
+}
+
 // هيكل مدير الطابور
 pub struct EventQueueManager {
     sender: mpsc::Sender<IngestionEvent>,
     receiver: mpsc::Receiver<IngestionEvent>, // في الواقع، المستهلك سيسحب هذا
     capacity: usize,
-    
+
     // مقاييس الأداء (Telemetry)
     enqueued_count: Arc<AtomicUsize>,
     dropped_count: Arc<AtomicUsize>,
+    deduped_count: Arc<AtomicUsize>,
+
+    // نافذة الإزالة المزدوجة: حجمها مستقل عن سعة القناة، ومعطّلة افتراضياً
+    dedup_window: Option<Mutex<LruCache<DedupKey, ()>>>,
 }
 
 impl EventQueueManager {
-    /// إنشاء مدير طوابير جديد
+    /// إنشاء مدير طوابير جديد (بدون إزالة مزدوجة)
     /// capacity: الحد الأقصى للعناصر في الذاكرة (لمنع استهلاك الرام بالكامل)
     pub fn new(capacity: usize) -> Self {
+        Self::new_with_dedup(capacity, 0)
+    }
+
+    /// إنشاء مدير طوابير مع تفعيل نافذة إزالة مزدوجة بسعة `dedup_capacity`.
+    /// تمرير 0 يعطّل الإزالة المزدوجة تماماً (نفس سلوك `new`).
+    pub fn new_with_dedup(capacity: usize, dedup_capacity: usize) -> Self {
         let (tx, rx) = mpsc::channel(capacity);
-        
+
         Self {
             sender: tx,
             receiver: rx,
             capacity,
             enqueued_count: Arc::new(AtomicUsize::new(0)),
             dropped_count: Arc::new(AtomicUsize::new(0)),
+            deduped_count: Arc::new(AtomicUsize::new(0)),
+            dedup_window: NonZeroUsize::new(dedup_capacity).map(|cap| Mutex::new(LruCache::new(cap))),
+        }
+    }
+
+    /// يتحقق مما إذا كان الحدث مكرراً ضمن النافذة الأخيرة، ويسجله إن لم يكن كذلك.
+    /// يعيد `true` إذا وجب إسقاط الحدث (مكرر).
+    fn is_duplicate(&self, event: &IngestionEvent) -> bool {
+        let Some(window) = &self.dedup_window else { return false };
+        let Some(key) = event.dedup_key() else { return false };
+
+        let mut cache = window.lock();
+        if cache.contains(&key) {
+            self.deduped_count.fetch_add(1, Ordering::Relaxed);
+            true
+        } else {
+            cache.put(key, ());
+            false
         }
     }
 
@@ -55,6 +105,10 @@ impl EventQueueManager {
     /// هذه الدالة تحاول الإدخال، وإذا كان الطابور ممتلئاً، تنتظر (Backpressure)
     /// بدلاً من رفض البيانات فوراً.
     pub async fn push_safe(&self, event: IngestionEvent) -> Result<(), String> {
+        if self.is_duplicate(&event) {
+            return Ok(());
+        }
+
         // ننتظر 100 ملي ثانية كحد أقصى لإيجاد مكان في الطابور
         let result = timeout(Duration::from_millis(100), self.sender.send(event)).await;
 
@@ -78,6 +132,10 @@ impl EventQueueManager {
     /// دالة الإدخال السريع (Fire & Forget)
     /// تستخدم للبيانات الأقل أهمية (مثل السجلات) حيث السرعة أهم من الضمان.
     pub fn push_fast(&self, event: IngestionEvent) -> Result<(), String> {
+        if self.is_duplicate(&event) {
+            return Ok(());
+        }
+
         match self.sender.try_send(event) {
             Ok(_) => {
                 self.enqueued_count.fetch_add(1, Ordering::Relaxed);
@@ -106,11 +164,12 @@ impl EventQueueManager {
         Some(original_rx)
     }
 
-    /// تقرير الحالة
-    pub fn get_metrics(&self) -> (usize, usize) {
+    /// تقرير الحالة: (المُدخل، المُسقط، المُزال تكراره)
+    pub fn get_metrics(&self) -> (usize, usize, usize) {
         (
             self.enqueued_count.load(Ordering::Relaxed),
-            self.dropped_count.load(Ordering::Relaxed)
+            self.dropped_count.load(Ordering::Relaxed),
+            self.deduped_count.load(Ordering::Relaxed),
         )
     }
 }
@@ -135,4 +194,35 @@ mod tests {
         assert!(res.is_err());
         assert_eq!(res.err().unwrap(), "QUEUE_FULL_TIMEOUT");
     }
+
+    #[test]
+    fn test_dedup_lane_drops_repeated_ticks_and_snapshots() {
+        let mgr = EventQueueManager::new_with_dedup(16, 8);
+
+        let tick = IngestionEvent::MarketTick { symbol: "BTCUSD".into(), price: 50_000.0, volume: 1.0, ts: 100 };
+        let snapshot = IngestionEvent::OrderBookSnapshot { symbol: "BTCUSD".into(), depth: 10 };
+
+        assert!(mgr.push_fast(tick.clone()).is_ok());
+        assert!(mgr.push_fast(tick.clone()).is_ok()); // مكرر بنفس (الرمز، الوقت)
+        assert!(mgr.push_fast(snapshot.clone()).is_ok());
+        assert!(mgr.push_fast(snapshot.clone()).is_ok()); // مكرر بنفس الرمز
+
+        let (enqueued, dropped, deduped) = mgr.get_metrics();
+        assert_eq!(enqueued, 2);
+        assert_eq!(dropped, 0);
+        assert_eq!(deduped, 2);
+    }
+
+    #[test]
+    fn test_dedup_lane_disabled_by_default() {
+        let mgr = EventQueueManager::new(16);
+        let tick = IngestionEvent::MarketTick { symbol: "ETHUSD".into(), price: 3_000.0, volume: 1.0, ts: 1 };
+
+        assert!(mgr.push_fast(tick.clone()).is_ok());
+        assert!(mgr.push_fast(tick).is_ok());
+
+        let (enqueued, _, deduped) = mgr.get_metrics();
+        assert_eq!(enqueued, 2);
+        assert_eq!(deduped, 0);
+    }
 }
\ No newline at end of file
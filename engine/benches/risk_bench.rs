@@ -92,6 +92,8 @@ fn bench_risk_engine(c: &mut Criterion) {
             min_notional: Decimal::from(10),
             max_notional: Decimal::from(1_000_000),
             max_price_deviation: Decimal::from_f64(0.10).unwrap(),
+            band_up: Decimal::from_f64(0.10).unwrap(),
+            band_down: Decimal::from_f64(0.10).unwrap(),
         };
         let checker = PreTradeCheck::new(constraints);
         
@@ -105,8 +107,9 @@ fn bench_risk_engine(c: &mut Criterion) {
 
         b.iter(|| {
             black_box(checker.validate(
-                black_box(&order), 
-                black_box(ref_price)
+                black_box(&order),
+                black_box(ref_price),
+                black_box(None)
             ));
         });
     });
@@ -1,169 +1,547 @@
-// Exchange Connector
-
-/*
- * ALPHA SOVEREIGN - BINANCE WEBSOCKET ADAPTER
- * =================================================================
- * Component Name: engine/src/adapters/binance_ws.rs
- * Core Responsibility: إدارة تدفق بيانات Binance اللحظي وضمان استمرارية الاتصال (Integration Pillar).
- * Design Pattern: Async Event Loop / Auto-Reconnect / Heartbeat
- * Forensic Impact: يسجل لحظة الانقطاع ولحظة العودة بدقة. أي فجوة زمنية هنا تعني "فجوة في البيانات" (Data Gap) في التحقيق.
- * =================================================================
- */
-
-use std::time::Duration;
-use futures::{StreamExt, SinkExt};
-use tokio::net::TcpStream;
-use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use tracing::{info, warn, error, debug};
-use url::Url;
-
-use crate::transport::{EventTx, IngressEvent};
-use crate::error::AlphaResult;
-
-// الثوابت
-const BINANCE_FUTURES_WS: &str = "wss://fstream.binance.com/ws";
-const RECONNECT_DELAY_MS: u64 = 1000;
-const MAX_RECONNECT_DELAY_MS: u64 = 30000;
-
-/// هيكل البيانات القادمة من Binance (Book Ticker)
-#[derive(Debug, Deserialize)]
-struct BinanceBookTicker {
-    s: String, // Symbol
-    b: String, // Best Bid Price
-    B: String, // Best Bid Qty
-    a: String, // Best Ask Price
-    A: String, // Best Ask Qty
-    T: u64,    // Transaction Time
-    E: u64,    // Event Time
-}
-
-pub struct BinanceWsManager {
-    event_bus: EventTx,
-    running: bool,
-}
-
-impl BinanceWsManager {
-    pub fn new(event_bus: EventTx) -> Self {
-        Self {
-            event_bus,
-            running: true,
-        }
-    }
-
-    /// تشغيل الاستماع للبيانات العامة (Market Data)
-    /// يقوم بإنشاء خيط خلفي يدير الاتصال ويعيد المحاولة للأبد.
-    pub async fn start_market_stream(&self, symbols: Vec<String>) {
-        let bus = self.event_bus.clone();
-        
-        // تحويل الرموز لصيغة URL (btcusdt@bookTicker)
-        let streams: Vec<String> = symbols.iter()
-            .map(|s| format!("{}@bookTicker", s.to_lowercase()))
-            .collect();
-        
-        let url_str = format!("{}/{}", BINANCE_FUTURES_WS, streams.join("/"));
-
-        tokio::spawn(async move {
-            let mut retry_delay = RECONNECT_DELAY_MS;
-
-            loop {
-                info!("BINANCE_WS: Connecting to Market Stream...");
-                
-                match connect_async(Url::parse(&url_str).unwrap()).await {
-                    Ok((ws_stream, _)) => {
-                        info!("BINANCE_WS: Connected successfully.");
-                        retry_delay = RECONNECT_DELAY_MS; // إعادة ضبط التأخير عند النجاح
-                        
-                        // بدء معالجة الرسائل
-                        Self::handle_connection(ws_stream, bus.clone()).await;
-                        
-                        warn!("BINANCE_WS: Connection lost via handle return.");
-                    },
-                    Err(e) => {
-                        error!("BINANCE_WS: Connection Failed: {}. Retrying in {}ms", e, retry_delay);
-                    }
-                }
-
-                // استراتيجية الانتظار قبل إعادة المحاولة (Exponential Backoff)
-                sleep(Duration::from_millis(retry_delay)).await;
-                retry_delay = std::cmp::min(retry_delay * 2, MAX_RECONNECT_DELAY_MS);
-            }
-        });
-    }
-
-    /// المعالج الداخلي للاتصال (The Inner Loop)
-    async fn handle_connection(
-        mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
-        event_bus: EventTx
-    ) {
-        // حلقة القراءة
-        while let Some(msg) = ws_stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    // محاولة فك التشفير السريع (Fast Path)
-                    // نستخدم serde_json::from_str داخل كتلة منطقية
-                    
-                    // هنا نفترض أن الرسالة هي BookTicker
-                    // في التطبيق الكامل، يجب التمييز بين أنواع الرسائل
-                    match serde_json::from_str::<BinanceBookTicker>(&text) {
-                        Ok(ticker) => {
-                            // تحويل السعر من String إلى Decimal (مكلف قليلاً لكن ضروري)
-                            if let (Ok(price), Ok(_qty)) = (
-                                rust_decimal::Decimal::from_str_radix(&ticker.b, 10), // Bid Price as ref
-                                rust_decimal::Decimal::from_str_radix(&ticker.B, 10)
-                            ) {
-                                // إرسال الحدث للمحرك
-                                let event = IngressEvent::MarketData {
-                                    symbol: ticker.s,
-                                    price: price, // نستخدم Bid كسعر حالي للتبسيط
-                                    timestamp: ticker.T,
-                                };
-                                
-                                if let Err(e) = event_bus.try_send(event) {
-                                    // إذا امتلأت القناة، فهذا يعني أن المحرك يختنق!
-                                    // لا نوقف WS، بل نسقط الحزمة ونسجل الخطأ
-                                    error!("BINANCE_WS: Engine Backpressure! Dropping tick. {}", e);
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            // قد تكون رسالة ping أو heartbeat من البورصة
-                            if !text.contains("ping") {
-                                debug!("BINANCE_WS: Parse Error or Unknown Msg: {} | Payload: {:.50}...", e, text);
-                            }
-                        }
-                    }
-                },
-                Ok(Message::Ping(payload)) => {
-                    // الرد بـ Pong إلزامي للحفاظ على الاتصال
-                    let _ = ws_stream.send(Message::Pong(payload)).await;
-                },
-                Ok(Message::Close(_)) => {
-                    warn!("BINANCE_WS: Server sent CLOSE frame.");
-                    break;
-                },
-                Err(e) => {
-                    error!("BINANCE_WS: Stream Error: {}", e);
-                    break;
-                },
-                _ => {} // Binary/Pong ignored
-            }
-        }
-    }
-
-    /// (اختياري) تجديد ListenKey للبيانات الخاصة
-    /// يتطلب REST Client (سنفترض وجوده)
-    pub async fn keepalive_user_stream(listen_key: String) {
-        // حلقة لا نهائية ترسل طلب PUT كل 30 دقيقة
-        // لمنع انتهاء صلاحية جلسة المستخدم
-        tokio::spawn(async move {
-            loop {
-                sleep(Duration::from_secs(1800)).await; // 30 دقيقة
-                info!("BINANCE_WS: Renewing ListenKey...");
-                // adapter.rest_client.put_listen_key(&listen_key).await;
-            }
-        });
-    }
-}
\ No newline at end of file
+// Exchange Connector
+
+/*
+ * ALPHA SOVEREIGN - BINANCE WEBSOCKET ADAPTER
+ * =================================================================
+ * Component Name: engine/src/adapters/binance_ws.rs
+ * Core Responsibility: إدارة تدفق بيانات Binance اللحظي وضمان استمرارية الاتصال (Integration Pillar).
+ * Design Pattern: Async Event Loop / Auto-Reconnect / Heartbeat
+ * Forensic Impact: يسجل لحظة الانقطاع ولحظة العودة بدقة. أي فجوة زمنية هنا تعني "فجوة في البيانات" (Data Gap) في التحقيق.
+ * =================================================================
+ */
+
+use std::time::Duration;
+use futures::{StreamExt, SinkExt};
+use tokio::net::TcpStream;
+use tokio::time::sleep;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn, error, debug};
+use url::Url;
+use rust_decimal::Decimal;
+
+use crate::transport::{EventTx, IngressEvent};
+
+// الثوابت
+const BINANCE_FUTURES_WS: &str = "wss://fstream.binance.com/stream?streams=";
+const BINANCE_FUTURES_USER_WS: &str = "wss://fstream.binance.com/ws";
+const BINANCE_FUTURES_REST: &str = "https://fapi.binance.com";
+const RECONNECT_DELAY_MS: u64 = 1000;
+const MAX_RECONNECT_DELAY_MS: u64 = 30000;
+const LISTEN_KEY_KEEPALIVE_SECS: u64 = 1800; // 30 دقيقة - نافذة Binance قبل انتهاء الصلاحية هي 60 دقيقة
+
+/// أنواع تدفقات السوق التي يعرضها Binance والتي يمكن الاشتراك فيها معاً ضمن اتصال واحد
+/// (Combined Stream). كل نوع يُترجم إلى لاحقة قناة مختلفة ويُفكَّك إلى بنية Rust مختلفة.
+#[derive(Debug, Clone)]
+pub enum StreamKind {
+    /// كل صفقة فردية (`@trade`)
+    IndividualTrade,
+    /// الصفقات المجمَّعة (`@aggTrade`) - أقل ضجيجاً من التدفق الفردي
+    AggTrade,
+    /// أفضل عرض وطلب حاليين (`@bookTicker`)
+    BookTicker,
+    /// لقطة عمق جزئية بعدد مستويات ثابت (`@depth{levels}`)
+    PartialBookDepth { levels: u8 },
+    /// شمعة زمنية لفاصل معين (`@kline_{interval}`)
+    Kline { interval: String },
+    /// إحصائية متجددة لآخر 24 ساعة (`@ticker`)
+    Ticker24h,
+}
+
+impl StreamKind {
+    /// لاحقة اسم القناة كما تتوقعها Binance، دون الرمز نفسه
+    fn channel_suffix(&self) -> String {
+        match self {
+            StreamKind::IndividualTrade => "trade".to_string(),
+            StreamKind::AggTrade => "aggTrade".to_string(),
+            StreamKind::BookTicker => "bookTicker".to_string(),
+            StreamKind::PartialBookDepth { levels } => format!("depth{}", levels),
+            StreamKind::Kline { interval } => format!("kline_{}", interval),
+            StreamKind::Ticker24h => "ticker".to_string(),
+        }
+    }
+}
+
+/// هياكل البيانات الخام القادمة من Binance، واحدة لكل نوع قناة (Wire Format)
+#[derive(Debug, Deserialize)]
+struct BinanceBookTicker {
+    s: String, // Symbol
+    b: String, // Best Bid Price
+    #[serde(rename = "B")]
+    bid_qty: String,
+    a: String, // Best Ask Price
+    #[serde(rename = "A")]
+    ask_qty: String,
+    #[serde(rename = "T")]
+    transaction_time: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceTradeLike {
+    s: String, // Symbol
+    p: String, // Price
+    q: String, // Quantity
+    #[serde(rename = "T")]
+    trade_time: u64,
+    m: bool, // Is buyer the market maker?
+}
+
+#[derive(Debug, Deserialize)]
+struct BinancePartialDepth {
+    #[serde(rename = "T")]
+    transaction_time: Option<u64>,
+    #[serde(rename = "E")]
+    event_time: Option<u64>,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceKlineEnvelope {
+    #[serde(rename = "E")]
+    event_time: u64,
+    k: BinanceKlineDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceKlineDetail {
+    i: String, // Interval
+    o: String, // Open
+    h: String, // High
+    l: String, // Low
+    c: String, // Close
+    v: String, // Volume
+    x: bool,   // Is this candle closed?
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceTicker24h {
+    c: String, // Last Price
+    #[serde(rename = "P")]
+    price_change_percent: String,
+    v: String, // Volume
+    #[serde(rename = "E")]
+    event_time: u64,
+}
+
+/// غلاف حدث `ORDER_TRADE_UPDATE` كما يرد على تدفق بيانات المستخدم الخاص - لا يوجد هنا
+/// غلاف `{"stream","data"}` كما في التدفقات العامة؛ الحقل `e` هو من يحدد النوع مباشرة.
+#[derive(Debug, Deserialize)]
+struct BinanceOrderTradeUpdateEnvelope {
+    o: BinanceOrderTradeUpdate,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceOrderTradeUpdate {
+    s: String, // Symbol
+    c: String, // Client Order ID
+    i: u64,    // Exchange Order ID
+    #[serde(rename = "X")]
+    status: String, // Order Status (NEW, PARTIALLY_FILLED, FILLED, ...)
+    z: String, // Cumulative Filled Quantity
+    #[serde(rename = "ap")]
+    avg_price: String,
+    n: String, // Commission Amount
+    #[serde(rename = "N")]
+    commission_asset: Option<String>,
+    #[serde(rename = "T")]
+    transaction_time: u64,
+}
+
+/// غلاف حدث `ACCOUNT_UPDATE` - يحمل قائمة مراكز (`P`) قد تتحدث أكثر من رمز دفعة واحدة
+#[derive(Debug, Deserialize)]
+struct BinanceAccountUpdateEnvelope {
+    #[serde(rename = "E")]
+    event_time: u64,
+    a: BinanceAccountUpdateData,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceAccountUpdateData {
+    #[serde(rename = "P")]
+    positions: Vec<BinancePositionUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinancePositionUpdate {
+    s: String, // Symbol
+    #[serde(rename = "pa")]
+    position_amount: String,
+    #[serde(rename = "ep")]
+    entry_price: String,
+    #[serde(rename = "up")]
+    unrealized_pnl: String,
+}
+
+pub struct BinanceWsManager {
+    event_bus: EventTx,
+    running: bool,
+}
+
+impl BinanceWsManager {
+    pub fn new(event_bus: EventTx) -> Self {
+        Self {
+            event_bus,
+            running: true,
+        }
+    }
+
+    /// تشغيل الاستماع للبيانات العامة (Market Data) عبر مجموعة غير متجانسة من القنوات
+    /// يقوم بإنشاء خيط خلفي يدير الاتصال ويعيد المحاولة للأبد.
+    pub async fn start_market_stream(&self, subscriptions: Vec<(String, StreamKind)>) {
+        let bus = self.event_bus.clone();
+
+        // بناء رابط التدفق المُجمَّع (Combined Stream): symbol@channel/symbol@channel/...
+        let streams: Vec<String> = subscriptions.iter()
+            .map(|(symbol, kind)| format!("{}@{}", symbol.to_lowercase(), kind.channel_suffix()))
+            .collect();
+
+        let url_str = format!("{}{}", BINANCE_FUTURES_WS, streams.join("/"));
+
+        tokio::spawn(async move {
+            let mut retry_delay = RECONNECT_DELAY_MS;
+
+            loop {
+                info!("BINANCE_WS: Connecting to Combined Market Stream ({} channels)...", streams.len());
+
+                match connect_async(Url::parse(&url_str).unwrap()).await {
+                    Ok((ws_stream, _)) => {
+                        info!("BINANCE_WS: Connected successfully.");
+                        retry_delay = RECONNECT_DELAY_MS; // إعادة ضبط التأخير عند النجاح
+
+                        // بدء معالجة الرسائل
+                        Self::handle_connection(ws_stream, bus.clone()).await;
+
+                        warn!("BINANCE_WS: Connection lost via handle return.");
+                    },
+                    Err(e) => {
+                        error!("BINANCE_WS: Connection Failed: {}. Retrying in {}ms", e, retry_delay);
+                    }
+                }
+
+                // استراتيجية الانتظار قبل إعادة المحاولة (Exponential Backoff)
+                sleep(Duration::from_millis(retry_delay)).await;
+                retry_delay = std::cmp::min(retry_delay * 2, MAX_RECONNECT_DELAY_MS);
+            }
+        });
+    }
+
+    /// المعالج الداخلي للاتصال (The Inner Loop)
+    async fn handle_connection(
+        mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        event_bus: EventTx
+    ) {
+        // حلقة القراءة
+        while let Some(msg) = ws_stream.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    Self::dispatch_frame(&text, &event_bus);
+                },
+                Ok(Message::Ping(payload)) => {
+                    // الرد بـ Pong إلزامي للحفاظ على الاتصال
+                    let _ = ws_stream.send(Message::Pong(payload)).await;
+                },
+                Ok(Message::Close(_)) => {
+                    warn!("BINANCE_WS: Server sent CLOSE frame.");
+                    break;
+                },
+                Err(e) => {
+                    error!("BINANCE_WS: Stream Error: {}", e);
+                    break;
+                },
+                _ => {} // Binary/Pong ignored
+            }
+        }
+    }
+
+    /// يحدد نوع القناة من اسم التدفق المُرفق في غلاف التدفق المُجمَّع (`{"stream":..,"data":..}`)
+    /// ثم يفكك الحمولة إلى `IngressEvent` المناسب قبل إرسالها للناقل.
+    fn dispatch_frame(text: &str, event_bus: &EventTx) {
+        let envelope: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                // قد تكون رسالة ping أو heartbeat من البورصة
+                if !text.contains("ping") {
+                    debug!("BINANCE_WS: Parse Error or Unknown Msg: {} | Payload: {:.50}...", e, text);
+                }
+                return;
+            }
+        };
+
+        let (stream, data) = match (envelope.get("stream").and_then(Value::as_str), envelope.get("data")) {
+            (Some(stream), Some(data)) => (stream, data),
+            _ => {
+                debug!("BINANCE_WS: Non-combined-stream frame ignored: {:.50}...", text);
+                return;
+            }
+        };
+
+        // الرمز دائماً هو الجزء قبل '@' في اسم التدفق - مصدر موثوق حتى لو غابت "s" عن الحمولة
+        let symbol = stream.split('@').next().unwrap_or_default().to_uppercase();
+
+        let event = if stream.ends_with("@bookTicker") {
+            Self::parse_book_ticker(data, &symbol)
+        } else if stream.contains("@aggTrade") {
+            Self::parse_trade(data, &symbol)
+        } else if stream.contains("@trade") {
+            Self::parse_trade(data, &symbol)
+        } else if stream.contains("@depth") {
+            Self::parse_depth(data, &symbol)
+        } else if stream.contains("@kline_") {
+            Self::parse_kline(data, &symbol)
+        } else if stream.ends_with("@ticker") {
+            Self::parse_ticker_24h(data, &symbol)
+        } else {
+            debug!("BINANCE_WS: Unrecognized stream channel: {}", stream);
+            None
+        };
+
+        if let Some(event) = event {
+            if let Err(e) = event_bus.try_send(event) {
+                // إذا امتلأت القناة، فهذا يعني أن المحرك يختنق!
+                // لا نوقف WS، بل نسقط الحزمة ونسجل الخطأ
+                error!("BINANCE_WS: Engine Backpressure! Dropping {} frame. {}", stream, e);
+            }
+        }
+    }
+
+    fn parse_book_ticker(data: &Value, symbol: &str) -> Option<IngressEvent> {
+        let ticker: BinanceBookTicker = serde_json::from_value(data.clone()).ok()?;
+        Some(IngressEvent::BookTicker {
+            symbol: symbol.to_string(),
+            bid_price: Decimal::from_str_radix(&ticker.b, 10).ok()?,
+            bid_qty: Decimal::from_str_radix(&ticker.bid_qty, 10).ok()?,
+            ask_price: Decimal::from_str_radix(&ticker.a, 10).ok()?,
+            ask_qty: Decimal::from_str_radix(&ticker.ask_qty, 10).ok()?,
+            timestamp: ticker.transaction_time,
+        })
+    }
+
+    fn parse_trade(data: &Value, symbol: &str) -> Option<IngressEvent> {
+        let trade: BinanceTradeLike = serde_json::from_value(data.clone()).ok()?;
+        Some(IngressEvent::Trade {
+            symbol: symbol.to_string(),
+            price: Decimal::from_str_radix(&trade.p, 10).ok()?,
+            quantity: Decimal::from_str_radix(&trade.q, 10).ok()?,
+            is_buyer_maker: trade.m,
+            timestamp: trade.trade_time,
+        })
+    }
+
+    fn parse_depth(data: &Value, symbol: &str) -> Option<IngressEvent> {
+        let depth: BinancePartialDepth = serde_json::from_value(data.clone()).ok()?;
+        let to_levels = |levels: &[[String; 2]]| -> Option<Vec<(Decimal, Decimal)>> {
+            levels.iter()
+                .map(|[price, qty]| Some((Decimal::from_str_radix(price, 10).ok()?, Decimal::from_str_radix(qty, 10).ok()?)))
+                .collect()
+        };
+
+        Some(IngressEvent::DepthSnapshot {
+            symbol: symbol.to_string(),
+            bids: to_levels(&depth.bids)?,
+            asks: to_levels(&depth.asks)?,
+            timestamp: depth.transaction_time.or(depth.event_time).unwrap_or(0),
+        })
+    }
+
+    fn parse_kline(data: &Value, symbol: &str) -> Option<IngressEvent> {
+        let envelope: BinanceKlineEnvelope = serde_json::from_value(data.clone()).ok()?;
+        // لا نُصدر حدثاً إلا عند إغلاق الشمعة فعلياً - الشموع الجارية تُهمَل هنا
+        if !envelope.k.x {
+            return None;
+        }
+
+        Some(IngressEvent::CandleClose {
+            symbol: symbol.to_string(),
+            interval: envelope.k.i,
+            open: Decimal::from_str_radix(&envelope.k.o, 10).ok()?,
+            high: Decimal::from_str_radix(&envelope.k.h, 10).ok()?,
+            low: Decimal::from_str_radix(&envelope.k.l, 10).ok()?,
+            close: Decimal::from_str_radix(&envelope.k.c, 10).ok()?,
+            volume: Decimal::from_str_radix(&envelope.k.v, 10).ok()?,
+            timestamp: envelope.event_time,
+        })
+    }
+
+    fn parse_ticker_24h(data: &Value, symbol: &str) -> Option<IngressEvent> {
+        let ticker: BinanceTicker24h = serde_json::from_value(data.clone()).ok()?;
+        Some(IngressEvent::Ticker24h {
+            symbol: symbol.to_string(),
+            last_price: Decimal::from_str_radix(&ticker.c, 10).ok()?,
+            price_change_percent: Decimal::from_str_radix(&ticker.price_change_percent, 10).ok()?,
+            volume: Decimal::from_str_radix(&ticker.v, 10).ok()?,
+            timestamp: ticker.event_time,
+        })
+    }
+
+    /// تشغيل تدفق بيانات المستخدم الخاص (User Data Stream) على مقبس منفصل عن السوق العام.
+    /// يجدد `listenKey` دورياً عبر REST بالتوازي مع حلقة الاتصال/إعادة الاتصال الخاصة به.
+    pub async fn start_user_stream(&self, listen_key: String, api_key: String) {
+        let bus = self.event_bus.clone();
+
+        Self::keepalive_user_stream(listen_key.clone(), api_key);
+
+        tokio::spawn(async move {
+            let mut retry_delay = RECONNECT_DELAY_MS;
+            let mut disconnected_at: Option<std::time::Instant> = None;
+
+            loop {
+                let url_str = format!("{}/{}", BINANCE_FUTURES_USER_WS, listen_key);
+                info!("BINANCE_WS: Connecting to User Data Stream...");
+
+                match connect_async(Url::parse(&url_str).unwrap()).await {
+                    Ok((ws_stream, _)) => {
+                        info!("BINANCE_WS: User Data Stream connected successfully.");
+                        retry_delay = RECONNECT_DELAY_MS;
+
+                        // إذا كان هناك انقطاع سابق، فإن طول الفجوة بين الانقطاع وهذه اللحظة
+                        // هو نافذة بيانات مفقودة (Data Gap) يجب تسجيلها للتحقيق الجنائي.
+                        if let Some(since) = disconnected_at.take() {
+                            warn!(
+                                "BINANCE_WS: User Data Stream data gap of {:?} during reconnect.",
+                                since.elapsed()
+                            );
+                        }
+
+                        Self::handle_user_connection(ws_stream, bus.clone()).await;
+
+                        warn!("BINANCE_WS: User Data Stream connection lost.");
+                        disconnected_at = Some(std::time::Instant::now());
+                    },
+                    Err(e) => {
+                        error!("BINANCE_WS: User Data Stream connection failed: {}. Retrying in {}ms", e, retry_delay);
+                        disconnected_at.get_or_insert_with(std::time::Instant::now);
+                    }
+                }
+
+                sleep(Duration::from_millis(retry_delay)).await;
+                retry_delay = std::cmp::min(retry_delay * 2, MAX_RECONNECT_DELAY_MS);
+            }
+        });
+    }
+
+    /// المعالج الداخلي لاتصال بيانات المستخدم الخاص - نفس منطق `handle_connection` للسوق
+    /// العام لكن بتوجيه الرسائل إلى `dispatch_user_frame`.
+    async fn handle_user_connection(
+        mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        event_bus: EventTx
+    ) {
+        while let Some(msg) = ws_stream.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    Self::dispatch_user_frame(&text, &event_bus);
+                },
+                Ok(Message::Ping(payload)) => {
+                    let _ = ws_stream.send(Message::Pong(payload)).await;
+                },
+                Ok(Message::Close(_)) => {
+                    warn!("BINANCE_WS: User Data Stream server sent CLOSE frame.");
+                    break;
+                },
+                Err(e) => {
+                    error!("BINANCE_WS: User Data Stream Error: {}", e);
+                    break;
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// يفرز رسائل تدفق بيانات المستخدم حسب الحقل العلوي `e` (لا يوجد غلاف تدفق مُجمَّع هنا
+    /// كما في القنوات العامة) ويوجهها إلى دوال التفكيك المناسبة.
+    fn dispatch_user_frame(text: &str, event_bus: &EventTx) {
+        let envelope: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("BINANCE_WS: User stream parse error: {} | Payload: {:.50}...", e, text);
+                return;
+            }
+        };
+
+        let event_type = match envelope.get("e").and_then(Value::as_str) {
+            Some(t) => t,
+            None => {
+                debug!("BINANCE_WS: User stream frame without 'e' field ignored: {:.50}...", text);
+                return;
+            }
+        };
+
+        let event = match event_type {
+            "ORDER_TRADE_UPDATE" => Self::parse_order_trade_update(&envelope),
+            "ACCOUNT_UPDATE" => Self::parse_account_update(&envelope),
+            _ => {
+                debug!("BINANCE_WS: Unhandled user stream event type: {}", event_type);
+                None
+            }
+        };
+
+        if let Some(event) = event {
+            if let Err(e) = event_bus.try_send(event) {
+                error!("BINANCE_WS: Engine Backpressure! Dropping {} frame. {}", event_type, e);
+            }
+        }
+    }
+
+    fn parse_order_trade_update(envelope: &Value) -> Option<IngressEvent> {
+        let update: BinanceOrderTradeUpdateEnvelope = serde_json::from_value(envelope.clone()).ok()?;
+        let o = update.o;
+        Some(IngressEvent::OrderUpdate {
+            exchange_order_id: o.i,
+            client_order_id: o.c,
+            symbol: o.s,
+            exchange_status: o.status,
+            filled_quantity: Decimal::from_str_radix(&o.z, 10).ok()?,
+            average_fill_price: Decimal::from_str_radix(&o.avg_price, 10).ok()?,
+            commission_paid: Decimal::from_str_radix(&o.n, 10).unwrap_or_default(),
+            commission_asset: o.commission_asset.unwrap_or_default(),
+            timestamp: o.transaction_time,
+        })
+    }
+
+    fn parse_account_update(envelope: &Value) -> Option<IngressEvent> {
+        // قد تحمل الحمولة عدة مراكز دفعة واحدة؛ نصدر أول مركز فقط لأن `IngressEvent::AccountUpdate`
+        // يمثل رمزاً واحداً - الرموز الإضافية ستصل مجدداً في تحديثات لاحقة إن تغيرت.
+        let update: BinanceAccountUpdateEnvelope = serde_json::from_value(envelope.clone()).ok()?;
+        let position = update.a.positions.into_iter().next()?;
+
+        Some(IngressEvent::AccountUpdate {
+            symbol: position.s,
+            quantity: Decimal::from_str_radix(&position.position_amount, 10).ok()?,
+            entry_price: Decimal::from_str_radix(&position.entry_price, 10).ok()?,
+            unrealized_pnl: Decimal::from_str_radix(&position.unrealized_pnl, 10).ok()?,
+            timestamp: update.event_time,
+        })
+    }
+
+    /// تجديد `ListenKey` دورياً عبر REST لمنع Binance من إغلاق جلسة المستخدم (تنتهي الصلاحية
+    /// بعد 60 دقيقة من آخر تجديد)؛ نجدد كل `LISTEN_KEY_KEEPALIVE_SECS` لترك هامش أمان.
+    fn keepalive_user_stream(listen_key: String, api_key: String) {
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let url = format!("{}/fapi/v1/listenKey", BINANCE_FUTURES_REST);
+
+            loop {
+                sleep(Duration::from_secs(LISTEN_KEY_KEEPALIVE_SECS)).await;
+
+                let result = client.put(&url)
+                    .header("X-MBX-APIKEY", &api_key)
+                    .query(&[("listenKey", &listen_key)])
+                    .send()
+                    .await;
+
+                match result {
+                    Ok(resp) if resp.status().is_success() => {
+                        info!("BINANCE_WS: ListenKey renewed successfully.");
+                    },
+                    Ok(resp) => {
+                        error!("BINANCE_WS: ListenKey renewal rejected by exchange: HTTP {}", resp.status());
+                    },
+                    Err(e) => {
+                        error!("BINANCE_WS: ListenKey renewal request failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+}
@@ -10,19 +10,34 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use std::str::FromStr;
 use rust_decimal::Decimal;
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::interfaces::control::engine_control_server::EngineControl;
 use crate::interfaces::control::{ExecuteOrderRequest, ExecuteOrderResponse};
 use crate::matching::engine::MatchingEngine;
 use crate::models::order::{Order, OrderSide, OrderType};
+use crate::risk::engine::RiskEngine;
+use crate::risk::RiskContext;
+
+pub mod streaming;
+use streaming::ExecutionReport;
 
 pub struct AlphaServiceImpl {
     matching_engine: Arc<RwLock<MatchingEngine>>,
+    risk_engine: Arc<RiskEngine>,
 }
 
 impl AlphaServiceImpl {
-    pub fn new(matching_engine: Arc<RwLock<MatchingEngine>>) -> Self {
-        Self { matching_engine }
+    pub fn new(matching_engine: Arc<RwLock<MatchingEngine>>, risk_engine: Arc<RiskEngine>) -> Self {
+        Self { matching_engine, risk_engine }
+    }
+
+    /// يشترك في مركز بث تقارير التنفيذ (انظر `streaming::ExecutionReportHub`)، مُصفًّى
+    /// اختيارياً حسب `order_id`. هذا هو الجسر الجاهز لرد RPC من نوع Server-Streaming؛
+    /// كشفه فعلياً عبر `EngineControlServer` يتطلب إضافة الـ RPC المقابل إلى
+    /// `engine_control.proto` أولاً (انظر التعليق في `streaming::stream_for`).
+    pub fn stream_execution_reports(&self, order_id: Option<u64>) -> ReceiverStream<ExecutionReport> {
+        self.matching_engine.read().report_hub().stream_for(order_id)
     }
 }
 
@@ -77,17 +92,48 @@ impl EngineControl for AlphaServiceImpl {
             price,
         );
 
+        // 2.5 فحص المخاطر (Pre-Trade Risk Gate)
+        // مرفوض هنا يعني أن الأمر لا يصل للدفتر إطلاقاً - لا صفقة، لا أثر في المحرك
+        if let Err(e) = self.risk_engine.check_order(&order) {
+            tracing::warn!("API: Order {} rejected by risk engine: {:?}", internal_id, e);
+            return Err(Status::new(Code::FailedPrecondition, format!("Risk check failed: {:?}", e)));
+        }
+
+        // 2.6 خط أنابيب فحوص `RiskCheck` القابلة للتوصيل (انظر `RiskEngine::evaluate`) -
+        // منفصل تماماً عن `check_order` أعلاه: يشغّل كل فحص مُسجَّل عبر `RiskEngine::register`
+        // (انظر `main.rs`) بدل القيدين المسطّحين الوحيدين. لا يملك هذا الطرف من النظام بعد
+        // مصدراً حياً لقيمة المحفظة/المركز الحقيقية (انظر `InventoryManager` غير المربوط بعد
+        // بـ `MatchingEngine`)، لذا نُمرر لقطة محايدة - أي فحص يعتمد فعلياً على تلك الحقول
+        // (مثل `MarginGuardCheck`) لا يُسجَّل حتى يتوفر ذلك المصدر (انظر `main.rs`).
+        let risk_order = to_pipeline_order(&order);
+        let risk_context = RiskContext {
+            portfolio_value: Decimal::ZERO,
+            open_orders_count: 0,
+            daily_loss: Decimal::ZERO,
+            volatility_index: Decimal::ZERO,
+            current_position_notional: Decimal::ZERO,
+        };
+        if let Err(report) = self.risk_engine.evaluate(&risk_order, &risk_context) {
+            tracing::warn!("API: Order {} rejected by risk pipeline: {}", internal_id, report.message);
+            return Err(Status::new(Code::FailedPrecondition, format!("Risk check failed: {}", report.message)));
+        }
+
         // 3. التنفيذ
         let mut engine = self.matching_engine.write();
-        
+
         match engine.process_order(order) {
             Ok(result) => {
+                // ملاحظة: `ExecuteOrderResponse` لا يملك حقلاً صريحاً لـ `leaves_qty` أو لحالة
+                // `PendingCancel` لأن `engine_control.proto` المصدري غائب تماماً عن هذه اللقطة
+                // من المستودع (`schemas/proto` لا وجود له على القرص). نُدرج الكمية المتبقية
+                // داخل `message`، وتصل الحالة المشتقة (ومنها `PendingCancel`) نصاً عبر `status`
+                // إلى حين توفر العقد الكامل لتوسيع الرسالة بحقول مخصصة.
                 Ok(Response::new(ExecuteOrderResponse {
                     // FIX 2: تحويل الرد من u64 إلى String ليتوافق مع البروتو
-                    order_id: result.order.id.to_string(), 
-                    status: format!("{:?}", result.order.status),
-                    filled_qty: result.order.executed_qty.to_string(),
-                    message: "Order Accepted".to_string(),
+                    order_id: result.order.id.to_string(),
+                    status: format!("{:?}", result.derived_status),
+                    filled_qty: result.filled_qty.to_string(),
+                    message: format!("leaves_qty={}", result.leaves_qty),
                 }))
             },
             Err(e) => {
@@ -95,4 +141,29 @@ impl EngineControl for AlphaServiceImpl {
             }
         }
     }
+}
+
+/// يحوّل أمراً من عائلة `models::order::Order` الغنية (المستخدمة فعلياً في مسار الـ API)
+/// إلى عائلة `matching::Order` الخفيفة التي يتوقعها خط أنابيب `RiskCheck::evaluate` - الجسر
+/// الوحيد بين العائلتين بدل ازدواج كل حقل أمر فيهما معاً.
+fn to_pipeline_order(order: &Order) -> crate::matching::Order {
+    crate::matching::Order {
+        id: order.id,
+        symbol_id: 0, // خط الأنابيب لا يفحص symbol_id حالياً؛ تخطيط رمز<->معرف لم يُبنَ بعد
+        side: match order.side {
+            OrderSide::Buy => crate::matching::Side::Bid,
+            OrderSide::Sell => crate::matching::Side::Ask,
+        },
+        order_type: match order.order_type {
+            OrderType::Market => crate::matching::OrderType::Market,
+            _ => crate::matching::OrderType::Limit,
+        },
+        time_in_force: crate::matching::TimeInForce::GTC,
+        price: order.price.unwrap_or(Decimal::ZERO),
+        quantity: order.original_qty,
+        stop_price: order.stop_price,
+        expires_at_ms: None,
+        timestamp: order.created_at,
+        owner_id: order.strategy_id.clone(),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,100 @@
+/*
+ * ALPHA SOVEREIGN - LIVE EXECUTION REPORT STREAM
+ * =================================================================
+ * Component Name: engine/src/api/streaming.rs
+ * Core Responsibility: بث كل انتقال في دورة حياة الأمر (New/PartiallyFilled/Filled/
+ * Canceled/Rejected) حياً لكل عميل مشترك، بدل رد واحد يعجز عن تمثيل تنفيذ يصل على دفعات.
+ * Design Pattern: Broadcast Hub / Bridge to Server-Streaming gRPC
+ * Forensic Impact: يمنح كل طرف مشترك (الدماغ، لوحة المراقبة، محول FIX) نفس "سجل المحادثة"
+ * الحي للتنفيذ، فلا يتعارض ما يراه اثنان منهم عن نفس الأمر.
+ * =================================================================
+ */
+
+use rust_decimal::Decimal;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+const REPORT_CHANNEL_CAPACITY: usize = 4096;
+
+/// نوع انتقال دورة حياة الأمر الذي يصفه هذا التقرير.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecType {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+/// تقرير تنفيذ واحد، يعكس حالة الأمر لحظة صدوره — وليس فقط "هل نجح أم لا؟".
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub exec_type: ExecType,
+    /// كمية آخر تنفيذ جزئي إن وُجد (فارغة لتقارير New/Canceled/Rejected بلا تنفيذ)
+    pub last_fill_qty: Option<Decimal>,
+    pub last_fill_price: Option<Decimal>,
+    /// الكمية المنفذة تراكمياً حتى لحظة هذا التقرير
+    pub cumulative_qty: Decimal,
+    /// سبب الرفض/الإلغاء إن وُجد
+    pub reason: Option<String>,
+    pub timestamp: u64,
+}
+
+/// مركز البث المشترك: مصدر واحد من الحقيقة لكل انتقال في دورة حياة الأمر، سواء أتى
+/// الحدث من المطابقة الداخلية (`MatchingEngine`) أو من رد تنفيذ خارجي (35=8) عبر محول FIX.
+/// كلاهما ينشر على نفس المركز، وأي عميل (gRPC، لوحة مراقبة) يشترك فيه يرى نفس التدفق.
+pub struct ExecutionReportHub {
+    sender: broadcast::Sender<ExecutionReport>,
+}
+
+impl ExecutionReportHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(REPORT_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// نشر تقرير جديد؛ لا يُخطئ عند غياب مشتركين (القناة ببساطة تتجاهله عندها).
+    pub fn publish(&self, report: ExecutionReport) {
+        let _ = self.sender.send(report);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ExecutionReport> {
+        self.sender.subscribe()
+    }
+
+    /// يجسّر اشتراك بث (`broadcast::Receiver`) إلى `ReceiverStream` عادي، وهو الشكل الذي
+    /// تتوقعه طبقة tonic لرد RPC من نوع Server-Streaming، ويصفّيه حسب `order_id` عند تمريره.
+    ///
+    /// ملاحظة: الربط الفعلي بخدمة gRPC المولَّدة (`EngineControlServer`) يتطلب إضافة
+    /// `rpc StreamExecutionReports(...) returns (stream ExecutionReport)` إلى
+    /// `engine_control.proto` — وهذا الملف غائب تماماً عن هذه اللقطة من المستودع
+    /// (مجلد `schemas/proto` الذي يشير إليه `build.rs` غير موجود على القرص). هذه الدالة
+    /// جاهزة للاستخدام فور توفر ذلك العقد، ويستدعيها اليوم
+    /// `api::AlphaServiceImpl::stream_execution_reports`.
+    pub fn stream_for(&self, order_id: Option<u64>) -> ReceiverStream<ExecutionReport> {
+        let mut broadcast_rx = self.sender.subscribe();
+        let (tx, rx) = mpsc::channel(REPORT_CHANNEL_CAPACITY);
+
+        tokio::spawn(async move {
+            loop {
+                match broadcast_rx.recv().await {
+                    Ok(report) => {
+                        let matches_filter = order_id.map(|id| id == report.order_id).unwrap_or(true);
+                        if matches_filter && tx.send(report).await.is_err() {
+                            break; // لم يعد العميل مستمعاً
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("EXEC_REPORT_STREAM: Subscriber lagged, {} reports dropped", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+}
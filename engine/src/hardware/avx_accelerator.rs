@@ -1,173 +1,581 @@
-// SIMD Math Ops
-
-/*
- * ALPHA SOVEREIGN - SIMD/AVX2 MATHEMATICAL ACCELERATOR
- * =================================================================
- * Component Name: engine/src/hardware/avx_accelerator.rs
- * Core Responsibility: تسريع العمليات الرياضية باستخدام تعليمات المعالج المتقدمة (Performance Pillar).
- * Design Pattern: Hardware Intrinsic Wrapper / Runtime Dispatch
- * Forensic Impact: لا يؤثر على المنطق، لكنه يقلل الـ Latency بشكل كبير. الفشل هنا يعني العودة للوضع البطيء (Fallback).
- * =================================================================
- */
-
-#[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
-use tracing::{info, warn};
-
-/// واجهة المسرع الرياضي
-pub struct AvxAccelerator;
-
-impl AvxAccelerator {
-    /// حساب الضرب النقطي (Dot Product) لمتجهين بسرعة AVX.
-    /// يستخدم بكثرة في حساب التشابه (Cosine Similarity) في الذاكرة المتجهة.
-    pub fn dot_product(a: &[f64], b: &[f64]) -> f64 {
-        if a.len() != b.len() {
-            return 0.0; // أو يمكن إرجاع خطأ NaN
-        }
-
-        // الكشف الديناميكي عن دعم المعالج (Runtime Detection)
-        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
-            unsafe { Self::dot_product_avx2(a, b) }
-        } else {
-            // fallback للأجهزة القديمة
-            Self::dot_product_scalar(a, b)
-        }
-    }
-
-    /// حساب التباين (Variance) والوسط الحسابي بسرعة AVX.
-    /// يستخدم بكثرة في حسابات المخاطر (Bollinger Bands, Volatility).
-    pub fn calculate_stats(data: &[f64]) -> (f64, f64) { // (Mean, Variance)
-        if data.is_empty() {
-            return (0.0, 0.0);
-        }
-
-        if is_x86_feature_detected!("avx2") {
-            unsafe { Self::calculate_stats_avx2(data) }
-        } else {
-            Self::calculate_stats_scalar(data)
-        }
-    }
-
-    // ----------------------------------------------------------------
-    // التطبيق السكالار (البطيء / الآمن) - Reference Implementation
-    // ----------------------------------------------------------------
-    fn dot_product_scalar(a: &[f64], b: &[f64]) -> f64 {
-        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
-    }
-
-    fn calculate_stats_scalar(data: &[f64]) -> (f64, f64) {
-        let sum: f64 = data.iter().sum();
-        let mean = sum / data.len() as f64;
-        let variance_sum: f64 = data.iter().map(|x| (x - mean).powi(2)).sum();
-        (mean, variance_sum / data.len() as f64)
-    }
-
-    // ----------------------------------------------------------------
-    // تطبيق AVX2 (السريع / Unsafe) - SIMD Implementation
-    // ----------------------------------------------------------------
-    
-    #[target_feature(enable = "avx2", enable = "fma")]
-    unsafe fn dot_product_avx2(a: &[f64], b: &[f64]) -> f64 {
-        let len = a.len();
-        let mut sum_vec = _mm256_setzero_pd(); // سجل التجميع [0.0, 0.0, 0.0, 0.0]
-        
-        let mut i = 0;
-        // معالجة 4 عناصر في كل دورة (Unrolling Loop)
-        while i + 4 <= len {
-            // تحميل البيانات (Unaligned Load - أبطأ قليلاً من Aligned لكن أكثر أماناً مع Rust Slices)
-            let va = _mm256_loadu_pd(a.as_ptr().add(i));
-            let vb = _mm256_loadu_pd(b.as_ptr().add(i));
-            
-            // Fused Multiply-Add: res = (va * vb) + sum_vec
-            // هذه تعليمة واحدة في المعالج!
-            sum_vec = _mm256_fmadd_pd(va, vb, sum_vec);
-            
-            i += 4;
-        }
-
-        // تجميع النتائج الأربعة في رقم واحد
-        // [x1, x2, x3, x4] -> x1+x2+x3+x4
-        let mut temp_arr = [0.0; 4];
-        _mm256_storeu_pd(temp_arr.as_mut_ptr(), sum_vec);
-        let mut total_sum: f64 = temp_arr.iter().sum();
-
-        // معالجة العناصر المتبقية (Tail Processing)
-        // إذا كان الطول مثلاً 10، سيعالج 8 بالـ AVX و 2 بهذا اللوب
-        while i < len {
-            total_sum += a.get_unchecked(i) * b.get_unchecked(i);
-            i += 1;
-        }
-
-        total_sum
-    }
-
-    #[target_feature(enable = "avx2")]
-    unsafe fn calculate_stats_avx2(data: &[f64]) -> (f64, f64) {
-        let len = data.len();
-        
-        // 1. حساب المجموع (للوسط الحسابي)
-        let mut sum_vec = _mm256_setzero_pd();
-        let mut i = 0;
-        while i + 4 <= len {
-            let v = _mm256_loadu_pd(data.as_ptr().add(i));
-            sum_vec = _mm256_add_pd(sum_vec, v);
-            i += 4;
-        }
-        
-        let mut temp_arr = [0.0; 4];
-        _mm256_storeu_pd(temp_arr.as_mut_ptr(), sum_vec);
-        let mut total_sum: f64 = temp_arr.iter().sum();
-        
-        // الذيل
-        let mut j = i;
-        while j < len {
-            total_sum += *data.get_unchecked(j);
-            j += 1;
-        }
-        
-        let mean = total_sum / len as f64;
-
-        // 2. حساب التباين (Variance)
-        // Variance = Sum((x - mean)^2) / N
-        let mean_vec = _mm256_set1_pd(mean); // بث المتوسط لكل الخانات [mean, mean, mean, mean]
-        let mut var_sum_vec = _mm256_setzero_pd();
-        
-        i = 0;
-        while i + 4 <= len {
-            let v = _mm256_loadu_pd(data.as_ptr().add(i));
-            let diff = _mm256_sub_pd(v, mean_vec); // (x - mean)
-            let sq_diff = _mm256_mul_pd(diff, diff); // ^2
-            var_sum_vec = _mm256_add_pd(var_sum_vec, sq_diff); // Accumulate
-            i += 4;
-        }
-
-        _mm256_storeu_pd(temp_arr.as_mut_ptr(), var_sum_vec);
-        let mut total_var_sum: f64 = temp_arr.iter().sum();
-
-        // الذيل
-        while i < len {
-            let diff = *data.get_unchecked(i) - mean;
-            total_var_sum += diff * diff;
-            i += 1;
-        }
-
-        (mean, total_var_sum / len as f64)
-    }
-}
-
-// اختبار الأداء (Benchmark) عند التشغيل
-pub fn benchmark_avx() {
-    let size = 1_000_000;
-    let v1: Vec<f64> = vec![1.0; size];
-    let v2: Vec<f64> = vec![2.0; size];
-    
-    let start = std::time::Instant::now();
-    let res = AvxAccelerator::dot_product(&v1, &v2);
-    let duration = start.elapsed();
-    
-    if is_x86_feature_detected!("avx2") {
-        info!("AVX2_BENCHMARK: 1M DotProduct in {:?} (Res: {}). Hardware Acceleration Active.", duration, res);
-    } else {
-        warn!("AVX2_BENCHMARK: AVX2 NOT ACTIVE. Using scalar fallback.");
-    }
-}
\ No newline at end of file
+// SIMD Math Ops
+
+/*
+ * ALPHA SOVEREIGN - SIMD/AVX2 MATHEMATICAL ACCELERATOR
+ * =================================================================
+ * Component Name: engine/src/hardware/avx_accelerator.rs
+ * Core Responsibility: تسريع العمليات الرياضية باستخدام تعليمات المعالج المتقدمة (Performance Pillar).
+ * Design Pattern: Hardware Intrinsic Wrapper / Runtime Dispatch
+ * Forensic Impact: لا يؤثر على المنطق، لكنه يقلل الـ Latency بشكل كبير. الفشل هنا يعني العودة للوضع البطيء (Fallback).
+ * =================================================================
+ */
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// مستوى تسريع العتاد المتاح على هذه الآلة، من الأقوى للأضعف. يُحدَّد مرة واحدة فقط عند
+/// أول استدعاء (انظر `current_tier`) بدل إعادة تشغيل `is_x86_feature_detected!` في كل نداء،
+/// لأن الكشف عن الميزات عبر CPUID له تكلفة ملحوظة على مسار ساخن يُستدعى ملايين المرات.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdTier {
+    Avx512,
+    Avx2Fma,
+    Sse2,
+    Scalar,
+}
+
+static SIMD_TIER: OnceLock<SimdTier> = OnceLock::new();
+
+fn detect_tier() -> SimdTier {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") {
+            return SimdTier::Avx512;
+        }
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return SimdTier::Avx2Fma;
+        }
+        if is_x86_feature_detected!("sse2") {
+            return SimdTier::Sse2;
+        }
+    }
+    SimdTier::Scalar
+}
+
+/// المستوى المخزَّن مؤقتاً (Cached) لتسريع العتاد على هذه الآلة. أول استدعاء فقط يشغّل
+/// الكشف الفعلي عبر CPUID؛ كل نداء لاحق يقرأ القيمة المخزَّنة مباشرة.
+pub fn current_tier() -> SimdTier {
+    *SIMD_TIER.get_or_init(detect_tier)
+}
+
+/// نوع التباين المطلوب من `calculate_stats_with_variance`: التباين المجتمعي (Population،
+/// القسمة على `N`) يُستخدم عندما تمثل البيانات المجتمع الكامل؛ تباين العينة (Sample، القسمة
+/// على `N-1`، تصحيح Bessel) هو الصحيح لتقديرات المخاطر المبنية على عيّنة من نافذة أسعار.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarianceKind {
+    Population,
+    Sample,
+}
+
+/// واجهة المسرع الرياضي
+pub struct AvxAccelerator;
+
+impl AvxAccelerator {
+    // ----------------------------------------------------------------
+    // Dot Product (f64 / f32)
+    // ----------------------------------------------------------------
+
+    /// حساب الضرب النقطي (Dot Product) لمتجهين بسرعة AVX.
+    /// يستخدم بكثرة في حساب التشابه (Cosine Similarity) في الذاكرة المتجهة.
+    pub fn dot_product(a: &[f64], b: &[f64]) -> f64 {
+        if a.len() != b.len() {
+            return 0.0; // أو يمكن إرجاع خطأ NaN
+        }
+
+        match current_tier() {
+            SimdTier::Avx512 => unsafe { Self::dot_product_avx512(a, b) },
+            SimdTier::Avx2Fma => unsafe { Self::dot_product_avx2(a, b) },
+            SimdTier::Sse2 => unsafe { Self::dot_product_sse2(a, b) },
+            SimdTier::Scalar => Self::dot_product_scalar(a, b),
+        }
+    }
+
+    /// نسخة `f32` من الضرب النقطي - مفيدة عندما تُخزَّن المتجهات المُضمَّنة (Embeddings) كـ
+    /// `f32` أصلاً، فلا حاجة لتوسيعها إلى `f64` قبل الحساب.
+    pub fn dot_product_f32(a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() {
+            return 0.0;
+        }
+
+        // لا يوجد بعد مسار AVX-512 مخصص لـ f32؛ نؤول إلى AVX2+FMA وهو أسرع مسار متاح فعلياً.
+        match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::dot_product_f32_avx2(a, b) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::dot_product_f32_scalar(a, b),
+        }
+    }
+
+    // ----------------------------------------------------------------
+    // L2 Norm / Euclidean Distance / Cosine Similarity
+    // ----------------------------------------------------------------
+
+    /// المعيار الإقليدي (L2 Norm) لمتجه واحد: `sqrt(dot(a, a))`. يرث مسار التسريع من
+    /// `dot_product` مباشرة - لا حاجة لنسخة AVX مستقلة لأن الحساب نفسه هو ضرب نقطي.
+    pub fn l2_norm(a: &[f64]) -> f64 {
+        Self::dot_product(a, a).sqrt()
+    }
+
+    pub fn l2_norm_f32(a: &[f32]) -> f32 {
+        Self::dot_product_f32(a, a).sqrt()
+    }
+
+    /// المسافة الإقليدية بين متجهين: `sqrt(sum((a[i]-b[i])^2))`. مسار مخصص بدل `l2_norm` على
+    /// متجه فرق مؤقت، لتفادي تخصيص ذاكرة إضافية على المسار الساخن.
+    pub fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+        if a.len() != b.len() {
+            return f64::NAN;
+        }
+
+        let sum_sq = match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::squared_diff_sum_avx2(a, b) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::squared_diff_sum_scalar(a, b),
+        };
+
+        sum_sq.sqrt()
+    }
+
+    pub fn euclidean_distance_f32(a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() {
+            return f32::NAN;
+        }
+
+        let sum_sq = match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::squared_diff_sum_f32_avx2(a, b) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::squared_diff_sum_f32_scalar(a, b),
+        };
+
+        sum_sq.sqrt()
+    }
+
+    /// تشابه جيب التمام (Cosine Similarity) لمتجهين، يُستخدم في استرجاع الذاكرة المتجهة
+    /// (Vector Memory) لمقارنة التمثيلات المُضمَّنة. يعيد `0.0` لأي متجه صفري بدل `NaN`.
+    pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+        let denom = Self::l2_norm(a) * Self::l2_norm(b);
+        if denom == 0.0 {
+            return 0.0;
+        }
+        Self::dot_product(a, b) / denom
+    }
+
+    pub fn cosine_similarity_f32(a: &[f32], b: &[f32]) -> f32 {
+        let denom = Self::l2_norm_f32(a) * Self::l2_norm_f32(b);
+        if denom == 0.0 {
+            return 0.0;
+        }
+        Self::dot_product_f32(a, b) / denom
+    }
+
+    // ----------------------------------------------------------------
+    // AXPY: y[i] = alpha * x[i] + y[i]
+    // ----------------------------------------------------------------
+
+    /// عملية BLAS الكلاسيكية AXPY في المكان (In-Place): `y[i] = alpha * x[i] + y[i]`.
+    pub fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+        assert_eq!(x.len(), y.len(), "axpy: x and y must have equal length");
+
+        match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::axpy_avx2(alpha, x, y) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::axpy_scalar(alpha, x, y),
+        }
+    }
+
+    pub fn axpy_f32(alpha: f32, x: &[f32], y: &mut [f32]) {
+        assert_eq!(x.len(), y.len(), "axpy_f32: x and y must have equal length");
+
+        match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::axpy_f32_avx2(alpha, x, y) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::axpy_f32_scalar(alpha, x, y),
+        }
+    }
+
+    // ----------------------------------------------------------------
+    // EWMA - المتوسط المتحرك الأسي
+    // ----------------------------------------------------------------
+
+    /// المتوسط المتحرك الأسي: `y[0] = x[0]`، `y[i] = alpha*x[i] + (1-alpha)*y[i-1]`.
+    /// هذه متتالية تتابعية بطبيعتها - كل عنصر يعتمد مباشرة على ناتج العنصر السابق - فلا
+    /// يوجد مسار AVX حقيقي يُسرّعها عبر لاين متعددة؛ تبقى هنا حلقة سكالارية واحدة لكل
+    /// المستويات، محفوظة بنفس توقيع بقية النواة للتناسق.
+    pub fn ewma(data: &[f64], alpha: f64) -> Vec<f64> {
+        let mut out = Vec::with_capacity(data.len());
+        let mut prev = 0.0;
+        for (i, &x) in data.iter().enumerate() {
+            prev = if i == 0 { x } else { alpha * x + (1.0 - alpha) * prev };
+            out.push(prev);
+        }
+        out
+    }
+
+    pub fn ewma_f32(data: &[f32], alpha: f32) -> Vec<f32> {
+        let mut out = Vec::with_capacity(data.len());
+        let mut prev = 0.0f32;
+        for (i, &x) in data.iter().enumerate() {
+            prev = if i == 0 { x } else { alpha * x + (1.0 - alpha) * prev };
+            out.push(prev);
+        }
+        out
+    }
+
+    // ----------------------------------------------------------------
+    // التباين والوسط الحسابي (Variance / Mean) - انظر calculate_stats أدناه
+    // ----------------------------------------------------------------
+
+    /// حساب التباين (Variance) والوسط الحسابي بسرعة AVX - غلاف حول
+    /// `calculate_stats_with_variance` بتباين مجتمعي (Population)، محافظاً على التوقيع
+    /// القديم حتى لا تنكسر المواقع التي تستدعيه.
+    /// يستخدم بكثرة في حسابات المخاطر (Bollinger Bands, Volatility).
+    pub fn calculate_stats(data: &[f64]) -> (f64, f64) { // (Mean, Variance)
+        Self::calculate_stats_with_variance(data, VarianceKind::Population)
+    }
+
+    /// حساب الوسط الحسابي والتباين بخوارزمية Welford أحادية المرور (Single-Pass)، بدل
+    /// القراءة المزدوجة للبيانات (مجموع ثم مجموع مربعات الانحراف) التي تفقد دقتها على
+    /// النوافذ الطويلة شبه الثابتة القيمة. `kind` يحدد القاسم: `N` للتباين المجتمعي أو
+    /// `N-1` (تصحيح Bessel) لتباين العينة المستخدم في تقديرات المخاطر.
+    pub fn calculate_stats_with_variance(data: &[f64], kind: VarianceKind) -> (f64, f64) {
+        if data.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let (mean, m2, count) = match current_tier() {
+            SimdTier::Avx512 | SimdTier::Avx2Fma => unsafe { Self::calculate_stats_avx2(data) },
+            SimdTier::Sse2 | SimdTier::Scalar => Self::calculate_stats_scalar(data),
+        };
+
+        let divisor = match kind {
+            VarianceKind::Population => count as f64,
+            VarianceKind::Sample if count > 1 => (count - 1) as f64,
+            VarianceKind::Sample => return (mean, 0.0), // عينة من عنصر واحد: لا تباين معرَّف
+        };
+
+        (mean, m2 / divisor)
+    }
+
+    // ----------------------------------------------------------------
+    // التطبيق السكالار (البطيء / الآمن) - Reference Implementation
+    // ----------------------------------------------------------------
+    fn dot_product_scalar(a: &[f64], b: &[f64]) -> f64 {
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+    }
+
+    fn dot_product_f32_scalar(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+    }
+
+    fn squared_diff_sum_scalar(a: &[f64], b: &[f64]) -> f64 {
+        a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+    }
+
+    fn squared_diff_sum_f32_scalar(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+    }
+
+    fn axpy_scalar(alpha: f64, x: &[f64], y: &mut [f64]) {
+        for (xi, yi) in x.iter().zip(y.iter_mut()) {
+            *yi += alpha * xi;
+        }
+    }
+
+    fn axpy_f32_scalar(alpha: f32, x: &[f32], y: &mut [f32]) {
+        for (xi, yi) in x.iter().zip(y.iter_mut()) {
+            *yi += alpha * xi;
+        }
+    }
+
+    /// Welford أحادي المرور: يعيد `(mean, M2, count)` - القسمة على `N`/`N-1` مسؤولية المستدعي
+    /// (`calculate_stats_with_variance`) وليست هذه الدالة، لأنها لا تعرف نوع التباين المطلوب.
+    fn calculate_stats_scalar(data: &[f64]) -> (f64, f64, usize) {
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        let mut count: usize = 0;
+
+        for &x in data {
+            count += 1;
+            let delta = x - mean;
+            mean += delta / count as f64;
+            let delta2 = x - mean;
+            m2 += delta * delta2;
+        }
+
+        (mean, m2, count)
+    }
+
+    // ----------------------------------------------------------------
+    // تطبيق SSE2 (الحد الأدنى المضمون على x86_64) - 2 عناصر f64 لكل دورة
+    // ----------------------------------------------------------------
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn dot_product_sse2(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len();
+        let mut sum_vec = _mm_setzero_pd();
+
+        let mut i = 0;
+        while i + 2 <= len {
+            let va = _mm_loadu_pd(a.as_ptr().add(i));
+            let vb = _mm_loadu_pd(b.as_ptr().add(i));
+            sum_vec = _mm_add_pd(sum_vec, _mm_mul_pd(va, vb));
+            i += 2;
+        }
+
+        let mut temp_arr = [0.0; 2];
+        _mm_storeu_pd(temp_arr.as_mut_ptr(), sum_vec);
+        let mut total_sum: f64 = temp_arr.iter().sum();
+
+        while i < len {
+            total_sum += a.get_unchecked(i) * b.get_unchecked(i);
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    // ----------------------------------------------------------------
+    // تطبيق AVX2/AVX-512 (السريع / Unsafe) - SIMD Implementation
+    // ----------------------------------------------------------------
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn dot_product_avx2(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len();
+        let mut sum_vec = _mm256_setzero_pd(); // سجل التجميع [0.0, 0.0, 0.0, 0.0]
+
+        let mut i = 0;
+        // معالجة 4 عناصر في كل دورة (Unrolling Loop)
+        while i + 4 <= len {
+            // تحميل البيانات (Unaligned Load - أبطأ قليلاً من Aligned لكن أكثر أماناً مع Rust Slices)
+            let va = _mm256_loadu_pd(a.as_ptr().add(i));
+            let vb = _mm256_loadu_pd(b.as_ptr().add(i));
+
+            // Fused Multiply-Add: res = (va * vb) + sum_vec
+            // هذه تعليمة واحدة في المعالج!
+            sum_vec = _mm256_fmadd_pd(va, vb, sum_vec);
+
+            i += 4;
+        }
+
+        // تجميع النتائج الأربعة في رقم واحد
+        // [x1, x2, x3, x4] -> x1+x2+x3+x4
+        let mut temp_arr = [0.0; 4];
+        _mm256_storeu_pd(temp_arr.as_mut_ptr(), sum_vec);
+        let mut total_sum: f64 = temp_arr.iter().sum();
+
+        // معالجة العناصر المتبقية (Tail Processing)
+        // إذا كان الطول مثلاً 10، سيعالج 8 بالـ AVX و 2 بهذا اللوب
+        while i < len {
+            total_sum += a.get_unchecked(i) * b.get_unchecked(i);
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    /// مسار AVX-512 مخصص لرأس الحربة (Flagship) لهذه النواة: 8 عناصر `f64` لكل دورة بدل 4.
+    /// `l2_norm` و`cosine_similarity` يمران عبر `dot_product` فيستخدمان هذا المسار؛ أما
+    /// euclidean/axpy/calculate_stats ونسخ f32 فتؤول في طبقة AVX-512 إلى AVX2+FMA (انظر
+    /// `current_tier` في كل دالة)، و`ewma` حلقة سكالارية في كل الطبقات.
+    #[target_feature(enable = "avx512f")]
+    unsafe fn dot_product_avx512(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len();
+        let mut sum_vec = _mm512_setzero_pd();
+
+        let mut i = 0;
+        while i + 8 <= len {
+            let va = _mm512_loadu_pd(a.as_ptr().add(i));
+            let vb = _mm512_loadu_pd(b.as_ptr().add(i));
+            sum_vec = _mm512_fmadd_pd(va, vb, sum_vec);
+            i += 8;
+        }
+
+        let mut total_sum = _mm512_reduce_add_pd(sum_vec);
+
+        while i < len {
+            total_sum += a.get_unchecked(i) * b.get_unchecked(i);
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn dot_product_f32_avx2(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len();
+        let mut sum_vec = _mm256_setzero_ps();
+
+        let mut i = 0;
+        while i + 8 <= len {
+            let va = _mm256_loadu_ps(a.as_ptr().add(i));
+            let vb = _mm256_loadu_ps(b.as_ptr().add(i));
+            sum_vec = _mm256_fmadd_ps(va, vb, sum_vec);
+            i += 8;
+        }
+
+        let mut temp_arr = [0.0f32; 8];
+        _mm256_storeu_ps(temp_arr.as_mut_ptr(), sum_vec);
+        let mut total_sum: f32 = temp_arr.iter().sum();
+
+        while i < len {
+            total_sum += a.get_unchecked(i) * b.get_unchecked(i);
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn squared_diff_sum_avx2(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len();
+        let mut sum_vec = _mm256_setzero_pd();
+
+        let mut i = 0;
+        while i + 4 <= len {
+            let va = _mm256_loadu_pd(a.as_ptr().add(i));
+            let vb = _mm256_loadu_pd(b.as_ptr().add(i));
+            let diff = _mm256_sub_pd(va, vb);
+            sum_vec = _mm256_fmadd_pd(diff, diff, sum_vec);
+            i += 4;
+        }
+
+        let mut temp_arr = [0.0; 4];
+        _mm256_storeu_pd(temp_arr.as_mut_ptr(), sum_vec);
+        let mut total_sum: f64 = temp_arr.iter().sum();
+
+        while i < len {
+            let diff = a.get_unchecked(i) - b.get_unchecked(i);
+            total_sum += diff * diff;
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn squared_diff_sum_f32_avx2(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len();
+        let mut sum_vec = _mm256_setzero_ps();
+
+        let mut i = 0;
+        while i + 8 <= len {
+            let va = _mm256_loadu_ps(a.as_ptr().add(i));
+            let vb = _mm256_loadu_ps(b.as_ptr().add(i));
+            let diff = _mm256_sub_ps(va, vb);
+            sum_vec = _mm256_fmadd_ps(diff, diff, sum_vec);
+            i += 8;
+        }
+
+        let mut temp_arr = [0.0f32; 8];
+        _mm256_storeu_ps(temp_arr.as_mut_ptr(), sum_vec);
+        let mut total_sum: f32 = temp_arr.iter().sum();
+
+        while i < len {
+            let diff = a.get_unchecked(i) - b.get_unchecked(i);
+            total_sum += diff * diff;
+            i += 1;
+        }
+
+        total_sum
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn axpy_avx2(alpha: f64, x: &[f64], y: &mut [f64]) {
+        let len = x.len();
+        let alpha_vec = _mm256_set1_pd(alpha);
+
+        let mut i = 0;
+        while i + 4 <= len {
+            let vx = _mm256_loadu_pd(x.as_ptr().add(i));
+            let vy = _mm256_loadu_pd(y.as_ptr().add(i));
+            let result = _mm256_fmadd_pd(alpha_vec, vx, vy);
+            _mm256_storeu_pd(y.as_mut_ptr().add(i), result);
+            i += 4;
+        }
+
+        while i < len {
+            *y.get_unchecked_mut(i) += alpha * x.get_unchecked(i);
+            i += 1;
+        }
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn axpy_f32_avx2(alpha: f32, x: &[f32], y: &mut [f32]) {
+        let len = x.len();
+        let alpha_vec = _mm256_set1_ps(alpha);
+
+        let mut i = 0;
+        while i + 8 <= len {
+            let vx = _mm256_loadu_ps(x.as_ptr().add(i));
+            let vy = _mm256_loadu_ps(y.as_ptr().add(i));
+            let result = _mm256_fmadd_ps(alpha_vec, vx, vy);
+            _mm256_storeu_ps(y.as_mut_ptr().add(i), result);
+            i += 8;
+        }
+
+        while i < len {
+            *y.get_unchecked_mut(i) += alpha * x.get_unchecked(i);
+            i += 1;
+        }
+    }
+
+    /// Welford أحادي المرور على 4 لاينات AVX2 مستقلة (كل لاين يتابع عناصره الخاصة بخطوة 4)،
+    /// ثم دمج اللاينات الأربعة ببعضها بصيغة دمج التباين المتوازي:
+    /// `M2 = M2_a + M2_b + delta^2 * n_a*n_b/(n_a+n_b)`، ثم دمج ذيل العناصر المتبقية
+    /// (أقل من 4) بنفس الخوارزمية عنصراً بعنصر على المُجمِّع الموحَّد.
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn calculate_stats_avx2(data: &[f64]) -> (f64, f64, usize) {
+        let len = data.len();
+        let mut mean_vec = _mm256_setzero_pd();
+        let mut m2_vec = _mm256_setzero_pd();
+        let mut lane_count: u64 = 0;
+
+        let mut i = 0;
+        while i + 4 <= len {
+            lane_count += 1;
+            let x = _mm256_loadu_pd(data.as_ptr().add(i));
+            let delta = _mm256_sub_pd(x, mean_vec);
+            let count_vec = _mm256_set1_pd(lane_count as f64);
+            mean_vec = _mm256_add_pd(mean_vec, _mm256_div_pd(delta, count_vec));
+            let delta2 = _mm256_sub_pd(x, mean_vec);
+            m2_vec = _mm256_fmadd_pd(delta, delta2, m2_vec);
+            i += 4;
+        }
+
+        let mut means = [0.0; 4];
+        let mut m2s = [0.0; 4];
+        _mm256_storeu_pd(means.as_mut_ptr(), mean_vec);
+        _mm256_storeu_pd(m2s.as_mut_ptr(), m2_vec);
+
+        // دمج اللاينات الأربعة (كل لاين عالج `lane_count` عنصراً) بصيغة دمج التباين المتوازي
+        let mut combined_mean = means[0];
+        let mut combined_m2 = m2s[0];
+        let mut combined_n = lane_count;
+
+        for lane in 1..4 {
+            let n_b = lane_count;
+            if n_b == 0 {
+                continue;
+            }
+            let delta = means[lane] - combined_mean;
+            let total_n = combined_n + n_b;
+            combined_mean += delta * (n_b as f64 / total_n as f64);
+            combined_m2 += m2s[lane] + delta * delta * (combined_n as f64 * n_b as f64) / total_n as f64;
+            combined_n = total_n;
+        }
+
+        // الذيل: عناصر أقل من 4 متبقية، تُدمَج عنصراً واحداً في كل مرة عبر Welford القياسي
+        while i < len {
+            combined_n += 1;
+            let delta = data.get_unchecked(i) - combined_mean;
+            combined_mean += delta / combined_n as f64;
+            let delta2 = data.get_unchecked(i) - combined_mean;
+            combined_m2 += delta * delta2;
+            i += 1;
+        }
+
+        (combined_mean, combined_m2, combined_n as usize)
+    }
+}
+
+// اختبار الأداء (Benchmark) عند التشغيل
+pub fn benchmark_avx() {
+    let size = 1_000_000;
+    let v1: Vec<f64> = vec![1.0; size];
+    let v2: Vec<f64> = vec![2.0; size];
+
+    let start = std::time::Instant::now();
+    let res = AvxAccelerator::dot_product(&v1, &v2);
+    let duration = start.elapsed();
+
+    match current_tier() {
+        SimdTier::Scalar => warn!("AVX2_BENCHMARK: No SIMD tier active. Using scalar fallback."),
+        tier => info!("AVX2_BENCHMARK: 1M DotProduct in {:?} (Res: {}). Tier: {:?}.", duration, res, tier),
+    }
+}
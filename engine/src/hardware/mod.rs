@@ -3,6 +3,10 @@
 
 use core_affinity;
 
+pub mod avx_accelerator;   // مكتبة نواة SIMD عامة (dot_product/l2_norm/ewma/...) - أداة مستقلة، لا نقطة ربط حتمية واحدة في مسار المطابقة
+pub mod telemetry_recorder; // الصندوق الأسود الثنائي المتسلسل بتشفير BLAKE3 - مربوط في `matching::engine::MatchingEngine::with_telemetry` (انظر main.rs)
+pub mod affinity_manager;  // تثبيت الخيوط حسب الدور (`ThreadRole`) - انظر main.rs
+
 /// محاولة تثبيت الخيط الحالي على نواة معينة لتقليل الـ Context Switching
 pub fn apply_affinity(core_id: usize) -> Result<(), String> {
     // 1. الحصول على الأنوية المتاحة
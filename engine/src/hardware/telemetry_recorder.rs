@@ -1,178 +1,436 @@
-// Blackbox Recorder
-
-/*
- * ALPHA SOVEREIGN - HIGH-SPEED BLACK BOX RECORDER
- * =================================================================
- * Component Name: engine/src/hardware/telemetry_recorder.rs
- * Core Responsibility: تسجيل التليمترية بدقة النانوثانية للتحليل الجنائي (Explainability Pillar).
- * Design Pattern: Async Ring Buffer / Binary Logging
- * Forensic Impact: الدليل الوحيد القادر على إعادة بناء تسلسل الأحداث بدقة عندما تفشل كل الأنظمة الأخرى.
- * =================================================================
- */
-
-use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
-use crossbeam::channel::{bounded, Sender, Receiver};
-use serde::{Serialize, Deserialize};
-use tracing::{info, error};
-
-// حجم القناة (عدد الأحداث قبل أن نضطر للانتظار - يجب أن يكون كبيراً)
-const QUEUE_CAPACITY: usize = 1_000_000;
-const BATCH_SIZE: usize = 1000;
-
-/// أنواع الأحداث التي نسجلها (مضغوطة قدر الإمكان)
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[repr(u8)]
-pub enum EventType {
-    OrderIn = 1,       // استلام أمر
-    RiskCheckStart = 2,
-    RiskCheckEnd = 3,
-    MatchingStart = 4,
-    TradeExecuted = 5,
-    OrderOut = 6,      // إرسال للبورصة
-    Error = 255,
-}
-
-/// هيكل الحدث الثنائي (Fixed Size Struct)
-/// هذا ما يتم تخزينه في الذاكرة والقرص. لا نستخدم JSON هنا للسرعة.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[repr(C)] // لضمان ترتيب الذاكرة
-pub struct TelemetryFrame {
-    pub timestamp_ns: u64, // 8 bytes
-    pub event_type: EventType, // 1 byte
-    pub entity_id: u64,    // OrderID or TradeID (8 bytes)
-    pub value: i64,        // Price or Quantity (scaled) (8 bytes)
-    pub duration_ns: u32,  // Latency if applicable (4 bytes)
-    pub flags: u8,         // Extra info (1 byte)
-}
-
-pub struct TelemetryRecorder {
-    sender: Sender<TelemetryFrame>,
-    is_running: Arc<AtomicBool>,
-    worker_handle: Option<thread::JoinHandle<()>>,
-}
-
-impl TelemetryRecorder {
-    /// إنشاء مسجل جديد وتشغيل الخيط الخلفي
-    pub fn new(file_path: &str) -> Self {
-        let (tx, rx) = bounded(QUEUE_CAPACITY);
-        let is_running = Arc::new(AtomicBool::new(true));
-        
-        let should_run = is_running.clone();
-        let path = file_path.to_string();
-
-        // تشغيل العامل الخلفي (Background Writer)
-        let handle = thread::spawn(move || {
-            Self::writer_loop(rx, path, should_run);
-        });
-
-        Self {
-            sender: tx,
-            is_running,
-            worker_handle: Some(handle),
-        }
-    }
-
-    /// تسجيل حدث (Hot Path - Zero Allocation)
-    /// هذه الدالة يجب أن تكون سريعة جداً (nanoseconds).
-    #[inline(always)]
-    pub fn record(&self, event_type: EventType, entity_id: u64, value: i64, duration: u32) {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos() as u64;
-
-        let frame = TelemetryFrame {
-            timestamp_ns: now,
-            event_type,
-            entity_id,
-            value,
-            duration_ns: duration,
-            flags: 0,
-        };
-
-        // محاولة الإرسال للقناة.
-        // نستخدم try_send لتجنب تجميد المحرك إذا امتلأت القناة (نفضل فقدان السجل على توقف التداول)
-        if let Err(_) = self.sender.try_send(frame) {
-            // في حالة الامتلاء، يمكننا زيادة عداد "Dropped Frames" ذرياً
-            // (لا نقوم بالطباعة هنا لأن الطباعة بطيئة)
-        }
-    }
-
-    /// حلقة الكتابة الخلفية
-    fn writer_loop(rx: Receiver<TelemetryFrame>, path: String, is_running: Arc<AtomicBool>) {
-        // فتح الملف في وضع الإلحاق (Append)
-        let file = match OpenOptions::new().create(true).append(true).open(&path) {
-            Ok(f) => f,
-            Err(e) => {
-                error!("TELEMETRY_FAIL: Could not open file {}: {}", path, e);
-                return;
-            }
-        };
-
-        let mut writer = BufWriter::with_capacity(64 * 1024, file); // 64KB Buffer
-        let mut buffer = Vec::with_capacity(BATCH_SIZE);
-
-        info!("TELEMETRY: Black box recorder active at {}", path);
-
-        while is_running.load(Ordering::Relaxed) || !rx.is_empty() {
-            // تجميع دفعة من الأحداث
-            while let Ok(frame) = rx.try_recv() {
-                buffer.push(frame);
-                if buffer.len() >= BATCH_SIZE {
-                    break;
-                }
-            }
-
-            if buffer.is_empty() {
-                thread::sleep(std::time::Duration::from_millis(10));
-                continue;
-            }
-
-            // كتابة البيانات الثنائية
-            // نستخدم bincode للتسلسل السريع جداً
-            for frame in &buffer {
-                if let Ok(bytes) = bincode::serialize(frame) {
-                     if let Err(e) = writer.write_all(&bytes) {
-                         error!("TELEMETRY_WRITE_ERR: {}", e);
-                     }
-                }
-            }
-            
-            // تفريغ المخزن المؤقت للقرص
-            let _ = writer.flush();
-            buffer.clear();
-        }
-        
-        info!("TELEMETRY: Recorder stopped.");
-    }
-
-    /// إغلاق نظيف
-    pub fn shutdown(&mut self) {
-        self.is_running.store(false, Ordering::SeqCst);
-        if let Some(handle) = self.worker_handle.take() {
-            let _ = handle.join();
-        }
-    }
-}
-
-// ----------------------------------------------------------------
-// أداة استعادة البيانات (Forensic Reader)
-// ----------------------------------------------------------------
-// هذا الكود يستخدم لقراءة الملف الثنائي لاحقاً وتحويله لنص
-pub fn replay_telemetry(path: &str) {
-    use std::io::Read;
-    
-    let mut file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return,
-    };
-
-    println!("--- BLACK BOX REPLAY ---");
-    // (Logic to read binary structs back and print readable text)
-    // ...
-}
\ No newline at end of file
+// Blackbox Recorder
+
+/*
+ * ALPHA SOVEREIGN - HIGH-SPEED BLACK BOX RECORDER
+ * =================================================================
+ * Component Name: engine/src/hardware/telemetry_recorder.rs
+ * Core Responsibility: تسجيل التليمترية بدقة النانوثانية للتحليل الجنائي (Explainability Pillar).
+ * Design Pattern: Async Ring Buffer / Binary Logging
+ * Forensic Impact: الدليل الوحيد القادر على إعادة بناء تسلسل الأحداث بدقة عندما تفشل كل الأنظمة الأخرى.
+ * =================================================================
+ */
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use bytemuck::{Pod, Zeroable};
+use crossbeam::channel::{bounded, Sender, Receiver};
+use tracing::{info, error};
+
+// حجم القناة (عدد الأحداث قبل أن نضطر للانتظار - يجب أن يكون كبيراً)
+const QUEUE_CAPACITY: usize = 1_000_000;
+const BATCH_SIZE: usize = 1000;
+
+/// أنواع الأحداث التي نسجلها (مضغوطة قدر الإمكان). يُخزَّن في `TelemetryFrame` كـ `u8` خام
+/// لا كـ enum مباشرة - `bytemuck::Pod` يتطلب أن يكون كل نمط بِت للحقل صالحاً، وenum بقيم
+/// غير متصلة (1..7, 254, 255) لا يحقق هذا الشرط.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EventType {
+    OrderIn = 1,       // استلام أمر
+    RiskCheckStart = 2,
+    RiskCheckEnd = 3,
+    MatchingStart = 4,
+    TradeExecuted = 5,
+    OrderOut = 6,      // إرسال للبورصة
+    /// تداخل آخذ/مُقيم من نفس الحساب (`strategy_id`) مُنع من التنفيذ بقرار STP بدلاً من
+    /// تسجيله كصفقة (انظر `matching::engine::MatchingEngine::apply_self_trade`).
+    SelfTradePrevented = 7,
+    /// إطار اصطناعي لا يمثّل حدثاً تجارياً: يحمل `checkpoint_digest` صالحاً فقط، ويُستبعد
+    /// من سطور `replay_telemetry` المقروءة بشرياً (انظر `TelemetryFrame::is_checkpoint`).
+    ChainCheckpoint = 254,
+    Error = 255,
+}
+
+/// العلم المحجوز في `TelemetryFrame::flags` للدلالة على أن `checkpoint_digest` لهذا الإطار
+/// يحمل هاشاً كاملاً صالحاً (وليس أصفاراً)؛ يُضبط فقط على إطارات `EventType::ChainCheckpoint`.
+const FLAG_HAS_CHECKPOINT: u8 = 0b0000_0001;
+
+/// هيكل الحدث الثنائي (Fixed Size Struct, Plain-Old-Data)
+/// هذا ما يتم تخزينه في الذاكرة والقرص حرفياً عبر `bytemuck::bytes_of` - لا تسلسل (serde)
+/// ولا تخصيص لكل إطار على مسار الكتابة الساخن. ترتيب الحقول هنا مقصود: من الأكبر محاذاة
+/// للأصغر، بلا أي حشو ضمني بين الحقول ولا في نهاية الهيكل (`_reserved` يسد آخر بايتين
+/// يحتاجها المحاذي كي يبقى الحجم الكلي مضاعفاً لمحاذاة `u64`: 8+8+8+4+1+1+8+32+2 = 72).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct TelemetryFrame {
+    pub timestamp_ns: u64, // 8 bytes
+    pub entity_id: u64,    // OrderID or TradeID (8 bytes)
+    pub value: i64,        // Price or Quantity (scaled) (8 bytes)
+    pub duration_ns: u32,  // Latency if applicable (4 bytes)
+    pub event_type: u8,    // قيمة `EventType` الخام (1 byte)
+    pub flags: u8,         // Extra info + FLAG_HAS_CHECKPOINT (1 byte)
+    /// بادئة 8 بايت (مقتطعة) من سلسلة الهاش المتدحرجة `h_n = BLAKE3(h_{n-1} || frame_bytes)`.
+    pub chain_digest: [u8; 8],
+    /// الهاش الكامل (32 بايت) للسلسلة؛ صالح فقط حين `flags & FLAG_HAS_CHECKPOINT != 0`
+    /// (إطارات `EventType::ChainCheckpoint` كل `BATCH_SIZE` إطار عادي)، أصفار غير ذلك -
+    /// يبقي هذا الملف مصفوفة متجانسة من `TelemetryFrame` فقط، قابلة لإعادة تفسيرها دفعة
+    /// واحدة عبر `bytemuck::cast_slice` دون الحاجة لنوع قيد ثانٍ مختلف الحجم.
+    pub checkpoint_digest: [u8; 32],
+    _reserved: [u8; 2],
+}
+
+unsafe impl Zeroable for TelemetryFrame {}
+unsafe impl Pod for TelemetryFrame {}
+
+impl TelemetryFrame {
+    fn is_checkpoint(&self) -> bool {
+        self.flags & FLAG_HAS_CHECKPOINT != 0
+    }
+}
+
+/// رأس الملف: بايتات سحرية + حجم الإطار الثابت، يكتب مرة واحدة عند إنشاء الملف، ويُتحقق
+/// منه عند كل إعادة فتح/قراءة - يرفض القارئ أي ملف لا يطابق تخطيط `TelemetryFrame` الحالي
+/// (مثلاً بعد تغيير حقول الهيكل في إصدار لاحق من هذا الملف) بدل تفسير بايتاته خطأً.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct FileHeader {
+    magic: [u8; 8],
+    frame_size: u32,
+    version: u32,
+}
+
+unsafe impl Zeroable for FileHeader {}
+unsafe impl Pod for FileHeader {}
+
+const FILE_MAGIC: [u8; 8] = *b"ALPHATLM";
+const FORMAT_VERSION: u32 = 1;
+
+impl FileHeader {
+    fn current() -> Self {
+        Self {
+            magic: FILE_MAGIC,
+            frame_size: std::mem::size_of::<TelemetryFrame>() as u32,
+            version: FORMAT_VERSION,
+        }
+    }
+
+    fn matches_current(&self) -> bool {
+        self.magic == FILE_MAGIC
+            && self.frame_size == std::mem::size_of::<TelemetryFrame>() as u32
+            && self.version == FORMAT_VERSION
+    }
+}
+
+/// فشل تحقق سلسلة التليمترية: إطار لا يطابق هاشه المخزَّن الهاش المعاد حسابه من سابقه،
+/// أو نقطة تحقق لا تطابق حالة السلسلة عند موضعها - أي الاثنين يعني حذفاً أو تعديلاً لإطار.
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryIntegrityError {
+    #[error("telemetry frame at index {0} does not chain from its expected predecessor digest")]
+    ChainBroken(usize),
+    #[error("telemetry file header does not match this build's TelemetryFrame layout (path: {0})")]
+    HeaderMismatch(String),
+}
+
+pub struct TelemetryRecorder {
+    sender: Sender<TelemetryFrame>,
+    is_running: Arc<AtomicBool>,
+    worker_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl TelemetryRecorder {
+    /// إنشاء مسجل جديد وتشغيل الخيط الخلفي
+    pub fn new(file_path: &str) -> Self {
+        let (tx, rx) = bounded(QUEUE_CAPACITY);
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let should_run = is_running.clone();
+        let path = file_path.to_string();
+
+        // تشغيل العامل الخلفي (Background Writer)
+        let handle = thread::spawn(move || {
+            Self::writer_loop(rx, path, should_run);
+        });
+
+        Self {
+            sender: tx,
+            is_running,
+            worker_handle: Some(handle),
+        }
+    }
+
+    /// تسجيل حدث (Hot Path - Zero Allocation)
+    /// هذه الدالة يجب أن تكون سريعة جداً (nanoseconds).
+    #[inline(always)]
+    pub fn record(&self, event_type: EventType, entity_id: u64, value: i64, duration: u32) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+
+        let frame = TelemetryFrame {
+            timestamp_ns: now,
+            entity_id,
+            value,
+            duration_ns: duration,
+            event_type: event_type as u8,
+            flags: 0,
+            // تُحسب في writer_loop وحده، حيث تُصان حالة السلسلة بالترتيب التسلسلي للكتابة.
+            chain_digest: [0u8; 8],
+            checkpoint_digest: [0u8; 32],
+            _reserved: [0u8; 2],
+        };
+
+        // محاولة الإرسال للقناة.
+        // نستخدم try_send لتجنب تجميد المحرك إذا امتلأت القناة (نفضل فقدان السجل على توقف التداول)
+        if let Err(_) = self.sender.try_send(frame) {
+            // في حالة الامتلاء، يمكننا زيادة عداد "Dropped Frames" ذرياً
+            // (لا نقوم بالطباعة هنا لأن الطباعة بطيئة)
+        }
+    }
+
+    /// نواة سلسلة الهاش: `h_n = BLAKE3(h_{n-1} || حقول الإطار ما عدا chain_digest/checkpoint_digest)`.
+    /// استبعاد حقلي الهاش من المدخل متعمَّد - وإلا لأصبح حساب الهاش دائرياً.
+    fn advance_chain(prev: &[u8; 32], frame: &TelemetryFrame) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(prev);
+        hasher.update(&frame.timestamp_ns.to_le_bytes());
+        hasher.update(&frame.entity_id.to_le_bytes());
+        hasher.update(&frame.value.to_le_bytes());
+        hasher.update(&frame.duration_ns.to_le_bytes());
+        hasher.update(&[frame.event_type]);
+        hasher.update(&[frame.flags]);
+        *hasher.finalize().as_bytes()
+    }
+
+    /// نونس بداية تشغيل جديد للمسجل (`h_0` حين لا يوجد ملف سابق أو كان فارغاً تماماً) -
+    /// مشتق من المسار والوقت، فلا تتكرر نفس سلسلة الهاش بين تشغيلتين مختلفتين لنفس الملف.
+    fn start_nonce_digest(path: &str) -> [u8; 32] {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let seed = format!("TELEMETRY_CHAIN_START:{}:{}", path, now);
+        *blake3::hash(seed.as_bytes()).as_bytes()
+    }
+
+    /// يقرأ كل الإطارات الصالحة (مضاعف كامل لحجم `TelemetryFrame`) من ملف مفتوح للقراءة،
+    /// متجاوزاً رأس الملف أولاً. يُعيد أيضاً عدد البايتات المتبقية بعد آخر إطار كامل - ذيل
+    /// مكتوب جزئياً (مثلاً بسبب تعطّل أثناء الكتابة) وليس خطأً، بل حقيقة تُبلَّغ لا تُخفى.
+    fn read_frames(file: &mut File) -> Result<(Vec<TelemetryFrame>, usize), TelemetryIntegrityError> {
+        let mut bytes = Vec::new();
+        if file.read_to_end(&mut bytes).is_err() {
+            return Ok((Vec::new(), 0));
+        }
+
+        let header_size = std::mem::size_of::<FileHeader>();
+        if bytes.len() < header_size {
+            return Ok((Vec::new(), bytes.len()));
+        }
+
+        let header: FileHeader = bytemuck::pod_read_unaligned(&bytes[..header_size]);
+        // ملف بلا رأس صالح، أو مكتوب بتخطيط إطار مختلف (إصدار أقدم/أحدث) - نرفضه صراحة
+        // بدل إعادة تفسير بايتاته بتخطيط خاطئ بصمت.
+        if !header.matches_current() {
+            return Err(TelemetryIntegrityError::HeaderMismatch(String::new()));
+        }
+
+        let body = &bytes[header_size..];
+        let frame_size = std::mem::size_of::<TelemetryFrame>();
+        let full_frames = body.len() / frame_size;
+        let trailing_partial_bytes = body.len() % frame_size;
+
+        let frames = match bytemuck::try_cast_slice::<u8, TelemetryFrame>(&body[..full_frames * frame_size]) {
+            Ok(slice) => slice.to_vec(),
+            // محاذاة المخزن المؤقت غير كافية لإعادة التفسير دفعة واحدة (نادر لكنه ممكن) -
+            // نقع احتياطياً لقراءة كل إطار بشكل منفرد غير مُحاذى، أبطأ لكن يبقى صحيحاً.
+            Err(_) => (0..full_frames)
+                .map(|i| bytemuck::pod_read_unaligned(&body[i * frame_size..(i + 1) * frame_size]))
+                .collect(),
+        };
+
+        Ok((frames, trailing_partial_bytes))
+    }
+
+    /// يستعيد حالة السلسلة (آخر هاش وعدد الإطارات) من ملف موجود مسبقاً، بإعادة حساب
+    /// الهاش فقط منذ آخر إطار `EventType::ChainCheckpoint` وما بعده (Bounded Replay) - لا
+    /// حاجة لإعادة حساب الملف كاملاً منذ أول إطار كُتب فيه على الإطلاق.
+    fn recover_chain_state(path: &str, fresh_genesis: [u8; 32]) -> ([u8; 32], u64) {
+        let mut file = match File::open(path) {
+            Ok(f) => f,
+            Err(_) => return (fresh_genesis, 0),
+        };
+
+        let Ok((frames, _trailing)) = Self::read_frames(&mut file) else {
+            // رأس غير مطابق - لا يمكن الوثوق بأي شيء في هذا الملف؛ نتعامل معه كملف جديد
+            // تماماً (سيُرفض لاحقاً بوضوح عند أي كتابة إضافية إن أعاد المستدعي تسميته).
+            return (fresh_genesis, 0);
+        };
+
+        let mut checkpoint_digest = fresh_genesis;
+        let mut checkpoint_seq = 0u64;
+        let mut pending: Vec<TelemetryFrame> = Vec::new();
+
+        for frame in frames {
+            if frame.is_checkpoint() {
+                checkpoint_digest = frame.checkpoint_digest;
+                checkpoint_seq = frame.entity_id; // أُعيد استخدام entity_id لحمل after_seq هنا
+                pending.clear();
+            } else {
+                pending.push(frame);
+            }
+        }
+
+        let mut chain_state = checkpoint_digest;
+        let mut frame_count = checkpoint_seq;
+        for frame in &pending {
+            chain_state = Self::advance_chain(&chain_state, frame);
+            frame_count += 1;
+        }
+        (chain_state, frame_count)
+    }
+
+    /// حلقة الكتابة الخلفية
+    fn writer_loop(rx: Receiver<TelemetryFrame>, path: String, is_running: Arc<AtomicBool>) {
+        // استعادة حالة السلسلة قبل أي كتابة - لو كان الملف موجوداً (إعادة فتح بالإلحاق)،
+        // يجب أن تتابع السلسلة من حيث توقفت لا أن تبدأ من الصفر.
+        let file_is_new = !std::path::Path::new(&path).exists();
+        let (mut chain_state, mut frame_count) =
+            Self::recover_chain_state(&path, Self::start_nonce_digest(&path));
+
+        // فتح الملف في وضع الإلحاق (Append)
+        let file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("TELEMETRY_FAIL: Could not open file {}: {}", path, e);
+                return;
+            }
+        };
+
+        let mut writer = BufWriter::with_capacity(64 * 1024, file); // 64KB Buffer
+        let mut buffer = Vec::with_capacity(BATCH_SIZE);
+
+        if file_is_new {
+            let _ = writer.write_all(bytemuck::bytes_of(&FileHeader::current()));
+            // نُثبِّت h_0 كأول إطار في الملف (نقطة تحقق عند after_seq=0، entity_id يحمل ذلك
+            // الرقم) كي يستطيع كل من إعادة الفتح اللاحقة و`replay_telemetry` استرجاع النونس
+            // الأصلي دون إعادة اشتقاقه - اشتقاقه يعتمد على الوقت فلا يمكن إعادة توليده لاحقاً.
+            let genesis = Self::checkpoint_frame(0, chain_state);
+            let _ = writer.write_all(bytemuck::bytes_of(&genesis));
+            let _ = writer.flush();
+        }
+
+        info!("TELEMETRY: Black box recorder active at {} (chain resumed at frame {})", path, frame_count);
+
+        while is_running.load(Ordering::Relaxed) || !rx.is_empty() {
+            // تجميع دفعة من الأحداث
+            while let Ok(frame) = rx.try_recv() {
+                buffer.push(frame);
+                if buffer.len() >= BATCH_SIZE {
+                    break;
+                }
+            }
+
+            if buffer.is_empty() {
+                thread::sleep(std::time::Duration::from_millis(10));
+                continue;
+            }
+
+            // كتابة البيانات الثنائية كبايتات خام (bytemuck::bytes_of) بلا أي تخصيص أو
+            // تسلسل (serde) لكل إطار - كل إطار يُربط بسلسلة الهاش المتدحرجة قبل كتابته،
+            // ثم يُلحق إطار نقطة تحقق كامل كل BATCH_SIZE إطار.
+            for mut frame in buffer.drain(..) {
+                let digest = Self::advance_chain(&chain_state, &frame);
+                frame.chain_digest = digest[..8].try_into().expect("blake3 digest is 32 bytes");
+                chain_state = digest;
+                frame_count += 1;
+
+                if let Err(e) = writer.write_all(bytemuck::bytes_of(&frame)) {
+                    error!("TELEMETRY_WRITE_ERR: {}", e);
+                }
+
+                if frame_count % BATCH_SIZE as u64 == 0 {
+                    let checkpoint = Self::checkpoint_frame(frame_count, chain_state);
+                    if let Err(e) = writer.write_all(bytemuck::bytes_of(&checkpoint)) {
+                        error!("TELEMETRY_WRITE_ERR: {}", e);
+                    }
+                }
+            }
+
+            // تفريغ المخزن المؤقت للقرص
+            let _ = writer.flush();
+        }
+
+        info!("TELEMETRY: Recorder stopped.");
+    }
+
+    /// يبني إطار نقطة تحقق اصطناعياً: لا حدث تجاري حقيقي وراءه، فقط حامل لهاش كامل عند
+    /// تسلسل معيّن. `after_seq` يُخزَّن في `entity_id` عمداً (انظر `recover_chain_state`).
+    fn checkpoint_frame(after_seq: u64, digest: [u8; 32]) -> TelemetryFrame {
+        TelemetryFrame {
+            timestamp_ns: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64,
+            entity_id: after_seq,
+            value: 0,
+            duration_ns: 0,
+            event_type: EventType::ChainCheckpoint as u8,
+            flags: FLAG_HAS_CHECKPOINT,
+            chain_digest: digest[..8].try_into().expect("blake3 digest is 32 bytes"),
+            checkpoint_digest: digest,
+            _reserved: [0u8; 2],
+        }
+    }
+
+    /// إغلاق نظيف
+    pub fn shutdown(&mut self) {
+        self.is_running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.worker_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// أداة استعادة البيانات (Forensic Reader)
+// ----------------------------------------------------------------
+// يقرأ الملف الثنائي كاملاً ويُعيد تفسيره كمصفوفة متجانسة من `TelemetryFrame` عبر
+// `bytemuck::cast_slice` (بلا تسلسل/serde)، يطبع كل إطار حدث فعلي كسطر مقروء، ويتحقق من
+// سلامة سلسلة الهاش أثناء القراءة. يبلّغ أيضاً عن أي ذيل ملف مكتوب جزئياً بدل تجاهله بصمت.
+pub fn replay_telemetry(path: &str) -> Result<(), TelemetryIntegrityError> {
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Ok(()),
+    };
+
+    println!("--- BLACK BOX REPLAY ---");
+
+    let (frames, trailing_partial_bytes) = TelemetryRecorder::read_frames(&mut file).map_err(|e| match e {
+        TelemetryIntegrityError::HeaderMismatch(_) => TelemetryIntegrityError::HeaderMismatch(path.to_string()),
+        other => other,
+    })?;
+
+    let mut chain_state = [0u8; 32];
+    let mut index = 0usize;
+
+    for frame in &frames {
+        if frame.is_checkpoint() {
+            // القيد الأول في الملف (after_seq == 0، أي entity_id == 0) يحمل h_0 نفسه،
+            // فلا سابق ليُقارَن به.
+            if frame.entity_id == 0 {
+                chain_state = frame.checkpoint_digest;
+            } else if frame.checkpoint_digest != chain_state {
+                println!("--- CHAIN BROKEN at checkpoint for frame {} ---", frame.entity_id);
+                return Err(TelemetryIntegrityError::ChainBroken(index));
+            }
+            continue;
+        }
+
+        let expected = TelemetryRecorder::advance_chain(&chain_state, frame);
+        if frame.chain_digest != expected[..8] {
+            println!("--- CHAIN BROKEN at frame {} ---", index);
+            return Err(TelemetryIntegrityError::ChainBroken(index));
+        }
+        chain_state = expected;
+
+        println!(
+            "[{}] ts={} event={} entity={} value={} dur_ns={}",
+            index, frame.timestamp_ns, frame.event_type, frame.entity_id, frame.value, frame.duration_ns
+        );
+        index += 1;
+    }
+
+    if trailing_partial_bytes > 0 {
+        println!("--- {} trailing partial bytes ignored (incomplete frame, likely a crash mid-write) ---", trailing_partial_bytes);
+    }
+    println!("--- {} frames verified, chain intact ---", index);
+    Ok(())
+}
@@ -11,6 +11,7 @@ pub mod hardware;
 pub mod transport;
 pub mod adapters;
 pub mod api;
+pub mod telemetry;
 
 // 2. التصدير العام (Re-exports)
 pub use error::{AlphaError, AlphaResult};
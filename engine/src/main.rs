@@ -17,18 +17,27 @@ use tonic::transport::Server;
 // 1. استيراد مكونات المكتبة الأساسية
 use alpha_engine::utils::logger::init_logger;
 use alpha_engine::risk::engine::{RiskEngine, RiskConfig};
+use alpha_engine::risk::signing::SigningKey;
+use alpha_engine::risk::pre_trade_check::{PreTradeCheck, TradeConstraints};
+use alpha_engine::risk::circuit_breaker::{CircuitBreaker, CircuitBreakerCheck, BreakerConfig};
+use alpha_engine::risk;
+use std::time::Duration;
 use alpha_engine::matching::engine::MatchingEngine;
+use alpha_engine::transport::tcp_server::TcpAdminServer;
 use alpha_engine::hardware;
+use alpha_engine::hardware::affinity_manager::{AffinityManager, ThreadRole};
+use alpha_engine::hardware::telemetry_recorder::TelemetryRecorder;
 
 // 2. استيراد طبقة الاتصال (API Layer)
 // هذه المكونات أصبحت متاحة لأننا أضفنا pub mod api في lib.rs
 use alpha_engine::api::AlphaServiceImpl;
+use alpha_engine::api::streaming::ExecutionReportHub;
 use alpha_engine::interfaces::control::engine_control_server::EngineControlServer;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // أ. تهيئة الصندوق الأسود (Logging)
-    let _guard = init_logger("./logs", "alpha_core.log", "info");
+    let _guard = init_logger("./logs", "alpha_core.log", "info", None);
     
     info!("🚀 ALPHA ENGINE: Boot sequence initiated...");
     info!("   - Version: 1.0.0 (Sovereign Edition)");
@@ -42,20 +51,107 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("✅ CPU Affinity applied. Main thread pinned to Core 0.");
     }
 
+    // تثبيت هذا الخيط (حلقة الإقلاع/المطابقة الرئيسية) بدور `MatchingEngine` عبر
+    // `AffinityManager` (انظر `hardware::affinity_manager`): يعيد تثبيته على نواة الدور (آخر
+    // نواة) فيحل محل تثبيت النواة 0 أعلاه؛ `enable_realtime_priority` تسجّل المحاولة فقط ولا تغيّر الأولوية
+    match AffinityManager::new() {
+        Ok(affinity_manager) => {
+            affinity_manager.pin_current_thread(ThreadRole::MatchingEngine);
+            affinity_manager.enable_realtime_priority();
+            info!("✅ AffinityManager: main thread pinned as MatchingEngine role.");
+        }
+        Err(e) => {
+            warn!("⚠️ Failed to initialize AffinityManager: {}. Running without role-based pinning.", e);
+        }
+    }
+
     // ج. تهيئة المحركات (Core Engines)
     
     // 1. درع المخاطر
     info!("🛡️ Initializing Risk Engine...");
-    // ملاحظة: نحتفظ به هنا للتوسعات المستقبلية، حتى لو لم يتم ربطه بالـ API حالياً
-    let _risk_engine = Arc::new(RiskEngine::new(Some(RiskConfig::default())));
+    let risk_engine = Arc::new(RiskEngine::new(Some(RiskConfig::default())));
+
+    // تسجيل خط أنابيب `RiskCheck` القابل للتوصيل (انظر `RiskEngine::evaluate` و
+    // `api::AlphaServiceImpl::execute_order`) - منفصل تماماً عن `RiskConfig`/`check_order`
+    // أعلاه. كل فحص هنا يُشغَّل بترتيب التسجيل على كل أمر وارد.
+    risk_engine.register(Box::new(
+        PreTradeCheck::new(TradeConstraints {
+            min_price: rust_decimal::Decimal::ZERO,
+            max_price: rust_decimal::Decimal::new(10_000_000, 0),
+            min_quantity: rust_decimal::Decimal::new(1, 8),
+            max_quantity: rust_decimal::Decimal::new(1_000_000, 0),
+            min_notional: rust_decimal::Decimal::new(1, 2),
+            max_notional: rust_decimal::Decimal::new(5_000_000, 0),
+            max_price_deviation: rust_decimal::Decimal::new(10, 2), // 10%
+            band_up: rust_decimal::Decimal::new(5, 2),   // 5%
+            band_down: rust_decimal::Decimal::new(5, 2), // 5%
+        })
+    ));
+    risk_engine.register(Box::new(
+        CircuitBreakerCheck::new(Arc::new(CircuitBreaker::new(BreakerConfig {
+            max_drawdown_per_minute: rust_decimal::Decimal::new(10_000, 0),
+            max_consecutive_errors: 5,
+            cooldown_period: Duration::from_secs(30),
+        })))
+    ));
+    // ملاحظة: `MarginGuardCheck` (انظر `risk::margin_guard`) غير مُسجَّل هنا عمداً - يحتاج
+    // قيمة محفظة/مركز حيّة (`current_equity`/`current_position_notional`) لا يملكها هذا
+    // المستودع بعد (`InventoryManager` في `matching::inventory_mgr` ما زال غير مربوط بـ
+    // `MatchingEngine`)؛ تسجيله بقيم صفرية وهمية سيرفض كل أمر فوراً بسبب "Zero Equity" بدل
+    // أن يحميه فعلاً. سجّله هنا بمجرد ربط مصدر حقيقي لهذين الحقلين.
+    info!("✅ Risk pipeline registered: PRE_TRADE_CHECK, CIRCUIT_BREAKER.");
+
+    // مفتاح توقيع العقدة: يُحمَّل من متغير بيئة (Hex، 32 بايت) إن وُجد، ليستطيع
+    // `trigger_emergency_stop` لاحقاً إصدار شهادة إيقاف موقَّعة وغير قابلة للإنكار. غيابه
+    // لا يوقف الإقلاع - فقط تحذير، إذ يبقى النظام صالحاً للعمل بدون توقيع جنائي (بيئات
+    // تطوير/اختبار محلية).
+    match std::env::var("ENGINE_SIGNING_KEY_HEX")
+        .ok()
+        .and_then(|hex_seed| hex::decode(hex_seed).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+    {
+        Some(seed) => {
+            let key = SigningKey::from_bytes(&seed);
+            info!("🔏 Node signing key loaded (pubkey {})", hex::encode(key.verifying_key()));
+            risk::set_node_signing_key(key);
+        }
+        None => {
+            warn!("⚠️ ENGINE_SIGNING_KEY_HEX not set or invalid - emergency stop events will NOT be signed.");
+        }
+    }
 
     // 2. محرك المطابقة (القلب النابض)
     info!("⚙️ Initializing Matching Engine...");
-    let matching_engine = Arc::new(RwLock::new(MatchingEngine::new()));
+    // مركز بث تقارير التنفيذ: يغذي طبقة الـ API ومحولات البورصات (FIX وغيرها) بنفس
+    // "سجل المحادثة" الحي لدورة حياة كل أمر
+    let report_hub = Arc::new(ExecutionReportHub::new());
+
+    // الصندوق الأسود الجنائي للتليمترية (انظر `hardware::telemetry_recorder`) - يسجل
+    // OrderIn/TradeExecuted/SelfTradePrevented من مسار المطابقة دون حجز مسار الأداء الساخن
+    let telemetry_path = std::env::var("TELEMETRY_BLACKBOX_PATH")
+        .unwrap_or_else(|_| "./logs/telemetry_blackbox.bin".to_string());
+    let telemetry_recorder = Arc::new(TelemetryRecorder::new(&telemetry_path));
+    info!("📼 TELEMETRY: forensic black box recording to {}", telemetry_path);
+
+    let matching_engine = Arc::new(RwLock::new(
+        MatchingEngine::new(report_hub.clone()).with_telemetry(telemetry_recorder.clone())
+    ));
 
     // د. إعداد الخدمة (Service Injection)
-    // نقوم بحقن محرك المطابقة داخل طبقة الـ API
-    let alpha_service = AlphaServiceImpl::new(matching_engine.clone());
+    // نقوم بحقن محرك المطابقة ودرع المخاطر داخل طبقة الـ API
+    let alpha_service = AlphaServiceImpl::new(matching_engine.clone(), risk_engine.clone());
+
+    // وحدة تحكم الطوارئ الخام عبر TCP (HELP/PING/HEALTH/SEQ/STATUS/PANIC) - منفصلة تماماً
+    // عن gRPC، ومقصودة لتبقى حية حتى لو تعطّلت طبقة الـ API. `HEALTH` يعرض آخر
+    // `PortfolioHealth` مسجَّلة عبر `MarginGuard::evaluate_health`؛ إلى أن يُربط مصدر حيّ
+    // لتلك الدالة (انظر ملاحظة `MarginGuardCheck` أعلاه) ستطبع "NO PORTFOLIO HEALTH
+    // RECORDED YET." دائماً - هذا صحيح وصادق، لا عطل في وحدة التحكم نفسها.
+    let admin_port: u16 = std::env::var("ADMIN_CONSOLE_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(7878);
+    TcpAdminServer::new(admin_port).start().await?;
+    info!("🛠️ ADMIN_CONSOLE: Emergency TCP console listening on 127.0.0.1:{}", admin_port);
 
     // هـ. إعداد الشبكة (Network Binding)
     let port = std::env::var("ENGINE_PORT").unwrap_or_else(|_| "50051".to_string());
@@ -1,16 +1,45 @@
 use crate::error::{AlphaError, AlphaResult};
-use crate::models::order::{Order, OrderSide, OrderType, OrderStatus};
+use crate::models::order::{Order, OrderSide, OrderType, OrderStatus, OrderReason, SelfTradePolicy};
 use crate::utils::logger::log_trade;
+use crate::api::streaming::{ExecutionReportHub, ExecutionReport, ExecType};
+use crate::hardware::telemetry_recorder::{TelemetryRecorder, EventType};
+use super::journal::{JournalEntry, JournalOp, MatchJournal};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use parking_lot::RwLock;
 use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// يحوِّل `OrderReason` لرمز نصي ثابت يُنشَر في `ExecutionReport::reason`، بنفس أسلوب
+/// الرموز الحرفية المستخدمة أصلاً هنا (`"OCO_SIBLING_FILLED"`, `"GTD_EXPIRED"`, ...).
+fn order_reason_code(reason: OrderReason) -> String {
+    match reason {
+        OrderReason::Manual => "MANUAL",
+        OrderReason::Expired => "EXPIRED",
+        OrderReason::RiskStop => "RISK_STOP",
+        OrderReason::SelfTradePrevention => "SELF_TRADE_PREVENTION",
+        OrderReason::NoLiquidity => "NO_LIQUIDITY",
+    }.to_string()
+}
+
 #[derive(Debug, Clone)]
 pub struct MatchingResult {
     pub order: Order,
     pub trades: Vec<TradeExecution>,
+
+    /// مجموع الكميات المنفذة عبر `trades` (يطابق `pending.executed_qty()`).
+    pub filled_qty: Decimal,
+    /// الكمية المتبقية دون تنفيذ بناءً على `order.original_qty`.
+    pub leaves_qty: Decimal,
+    /// الحالة المشتقة من مجموع الصفقات وليس من علم ثنائي واحد؛ تبقى `PendingCancel`
+    /// حتى يؤكَّد التنفيذ أو يُسترجع (انظر `MatchingEngine::confirm_match`/`rollback_match`).
+    pub derived_status: OrderStatus,
+
+    /// أوامر مُقيمة أُلغيت فوراً (جزئياً أو كلياً) ضمن هذه المطابقة بقرار منع تداول
+    /// النفس (STP)، لا ضمن `trades` لأنها لم تُنفَّذ أي صفقة عليها إطلاقاً. نهائية فور
+    /// وقوعها - خلافاً للمطابقات المتفائلة - إذ لا تُتراجَع عنها عبر `rollback_match`.
+    pub stp_cancellations: Vec<Order>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,44 +55,157 @@ pub struct TradeExecution {
     pub taker_order_id: String,
 }
 
+/// لقطة أمر مُقيم (Maker) كما كان مباشرة قبل مطابقة واحدة محدَّدة، بما يكفي لاستعادته
+/// بالضبط (كميته المنفذة، حالته، وموقعه في الطابور) إن استُرجعت تلك المطابقة لاحقاً.
+#[derive(Debug, Clone)]
+struct MakerSnapshot {
+    price_level: Decimal,
+    order: Order,
+    match_qty: Decimal,
+}
+
+/// يربط أمراً آخذاً (Taker) بتسلسل الصفقات التي طابقته في استدعاء `process` واحد، مع لقطات
+/// كافية للتراجع الكامل (Rollback) عن هذه المطابقات إن لم يصل تأكيد تنفيذ خارجي لها أبداً
+/// (مثلاً رفض FIX صريح، 35=8 مع 150=8). التدفق المؤسسي الحقيقي لا يُؤكِّد كل تنفيذ فوراً،
+/// لذا نُعامل كل مطابقة كـ"معلّقة" (Optimistic) حتى يُستدعى `MatchingEngine::confirm_match`.
+#[derive(Debug, Clone)]
+pub struct ExecutableMatch {
+    pub order_id: u64,
+    symbol: String,
+    trades: Vec<TradeExecution>,
+    taker_snapshot: Order,
+    maker_snapshots: Vec<MakerSnapshot>,
+    /// وقت دخول هذه المطابقة لحالة "معلّقة" (Unix Millis)؛ تُستخدم فقط لاكتشاف الآخذين
+    /// الذين لم يصل تأكيد/رفض تنفيذهم أبداً (انظر `MatchingEngine::expire_stale_matches`).
+    created_at_ms: u64,
+}
+
+impl ExecutableMatch {
+    /// مجموع الكميات المنفذة عبر كل الصفقات المسجلة هنا حتى الآن.
+    pub fn executed_qty(&self) -> Decimal {
+        self.trades.iter().map(|t| t.quantity).sum()
+    }
+
+    /// الكمية المتبقية دون تنفيذ بناءً على الكمية الأصلية للآخذ قبل هذه المطابقة.
+    pub fn leaves_qty(&self) -> Decimal {
+        (self.taker_snapshot.original_qty - self.executed_qty()).max(Decimal::ZERO)
+    }
+}
+
 struct OrderBook {
     symbol: String,
     bids: BTreeMap<Decimal, VecDeque<Order>>,
     asks: BTreeMap<Decimal, VecDeque<Order>>,
+
+    /// أوامر `OrderType::TrailingStop` مسلَّحة بانتظار تفعيلها؛ لا تنتمي لهيكل أولوية
+    /// السعر-الزمن (بلا سعر محدد تتحرك ضده)، لذا تُحفظ هنا مفهرسة بمعرفها حتى يحركها
+    /// `update_trailing_stops` مع كل تحديث سعر جديد (انظر `MatchingEngine::on_market_tick`).
+    trailing_stops: HashMap<u64, Order>,
+
+    /// أوامر `OrderType::StopLoss`/`StopLimit` الكامنة على جانب الشراء، مرتَّبة تصاعدياً
+    /// بـ `stop_price` كي يبقى أولها دوماً الأقرب للتفعيل (انظر `check_stop_triggers`).
+    stop_buys: Vec<Order>,
+
+    /// نفس الشيء لجانب البيع، لكن مرتَّبة تنازلياً (سعر التفعيل الأعلى هو الأقرب للتفعيل
+    /// لأمر بيع كامن، إذ يتفعّل متى هبط السعر إليه أو تحته).
+    stop_sells: Vec<Order>,
+
+    /// سياسة منع التداول مع النفس الافتراضية لهذا الدفتر؛ تُستبدَل بـ `order.stp_policy`
+    /// إن حدَّدها الأمر الوارد صراحة (انظر `MatchingEngine::set_stp_policy`).
+    stp_policy: SelfTradePolicy,
 }
 
 impl OrderBook {
-    fn new(symbol: &str) -> Self {
+    fn new(symbol: &str, stp_policy: SelfTradePolicy) -> Self {
         Self {
             symbol: symbol.to_string(),
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
+            trailing_stops: HashMap::new(),
+            stop_buys: Vec::new(),
+            stop_sells: Vec::new(),
+            stp_policy,
         }
     }
 
-    fn process(&mut self, mut order: Order) -> MatchingResult {
+    fn process(&mut self, order: Order) -> (MatchingResult, ExecutableMatch) {
+        // أوامر Stop/StopLimit الكامنة لا تُطابَق أبداً بنوعها الأصلي؛ تُحجز هنا بانتظار
+        // `check_stop_triggers`، ثم تُعاد لهذه الدالة بنوعها التنفيذي الفعلي (Market/Limit).
+        if matches!(order.order_type, OrderType::StopLoss | OrderType::StopLimit) {
+            return self.park_stop_order(order);
+        }
+
+        let taker_snapshot = order.clone();
+        let mut order = order;
         let mut trades = Vec::new();
+        let mut maker_snapshots = Vec::new();
+        let mut stp_cancellations = Vec::new();
         match order.side {
-            OrderSide::Buy => self.match_bid(&mut order, &mut trades),
-            OrderSide::Sell => self.match_ask(&mut order, &mut trades),
+            OrderSide::Buy => self.match_bid(&mut order, &mut trades, &mut maker_snapshots, &mut stp_cancellations),
+            OrderSide::Sell => self.match_ask(&mut order, &mut trades, &mut maker_snapshots, &mut stp_cancellations),
         }
-        
+
         if !order.is_closed() {
             match order.order_type {
                 OrderType::Limit => self.add_limit_order(order.clone()),
-                OrderType::Market => { order.status = OrderStatus::Canceled; },
+                OrderType::Market => {
+                    order.status = OrderStatus::Canceled;
+                    order.order_reason = OrderReason::NoLiquidity;
+                    order.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+                },
+                OrderType::TrailingStop => { self.trailing_stops.insert(order.id, order.clone()); },
                 _ => {}
             }
         }
-        
-        MatchingResult { order, trades }
+
+        let filled_qty: Decimal = trades.iter().map(|t| t.quantity).sum();
+        let leaves_qty = (order.original_qty - filled_qty).max(Decimal::ZERO);
+        // المطابقة تبقى "معلّقة" (PendingCancel) حتى تُؤكَّد صراحة؛ لا نفترض النجاح هنا
+        let derived_status = if filled_qty.is_zero() {
+            order.status
+        } else {
+            OrderStatus::PendingCancel
+        };
+
+        let pending = ExecutableMatch {
+            order_id: order.id,
+            symbol: self.symbol.clone(),
+            trades: trades.clone(),
+            taker_snapshot,
+            maker_snapshots,
+            created_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+        };
+
+        (MatchingResult { order, trades, filled_qty, leaves_qty, derived_status, stp_cancellations }, pending)
+    }
+
+    /// يعيد أمراً مُقيماً إلى حالته قبل مطابقة واحدة محدَّدة: إما بتصحيح كميته المنفذة
+    /// إن كان لا يزال موجوداً في الطابور، أو بإعادة إدراج لقطته كاملة إن كان قد أُزيل
+    /// من الطابور (أو حتى المستوى بأكمله) لامتلائه.
+    fn restore_maker(&mut self, snapshot: &MakerSnapshot) {
+        let book_side = match snapshot.order.side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+
+        let queue = book_side.entry(snapshot.price_level).or_insert_with(VecDeque::new);
+        if let Some(existing) = queue.iter_mut().find(|o| o.id == snapshot.order.id) {
+            existing.executed_qty -= snapshot.match_qty;
+            existing.status = if existing.executed_qty.is_zero() {
+                OrderStatus::New
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+        } else {
+            queue.push_front(snapshot.order.clone());
+        }
     }
 
-    fn match_bid(&mut self, order: &mut Order, trades: &mut Vec<TradeExecution>) {
+    fn match_bid(&mut self, order: &mut Order, trades: &mut Vec<TradeExecution>, maker_snapshots: &mut Vec<MakerSnapshot>, stp_cancellations: &mut Vec<Order>) {
         while order.is_active() {
             // FIX 2: Decouple borrow. Get the queue entry first.
             let mut best_ask_entry = self.asks.iter_mut().next();
-            
+
             // We need to extract what we need to avoid holding 'self' borrow
             match best_ask_entry {
                 Some((price, queue)) => {
@@ -71,16 +213,26 @@ impl OrderBook {
                     if let Some(limit) = order.price {
                         if *price > limit { break; }
                     }
-                    
+
                     // Process the order in the queue
                     if let Some(maker) = queue.front_mut() {
+                        if maker.strategy_id == order.strategy_id {
+                            Self::apply_self_trade(order, maker, self.stp_policy, stp_cancellations);
+                        } else {
                         // FIX 3: Inlined execution logic to satisfy borrow checker
                         // Instead of calling self.execute_match (which borrows self again)
                         // we do the logic right here.
-                        
+
                         let match_qty = (order.original_qty - order.executed_qty).min(maker.original_qty - maker.executed_qty);
                         let exec_price = *price;
-                        
+
+                        // لقطة المُقيم قبل المطابقة، لاستعادتها إن استُرجعت هذه المطابقة لاحقاً
+                        maker_snapshots.push(MakerSnapshot {
+                            price_level: *price,
+                            order: maker.clone(),
+                            match_qty,
+                        });
+
                         // Update Maker
                         maker.executed_qty += match_qty;
                         if maker.executed_qty >= maker.original_qty {
@@ -88,7 +240,7 @@ impl OrderBook {
                         } else {
                             maker.status = OrderStatus::PartiallyFilled;
                         }
-                        
+
                         // Update Taker (Order)
                         order.executed_qty += match_qty;
                         if order.executed_qty >= order.original_qty {
@@ -96,11 +248,11 @@ impl OrderBook {
                         } else {
                             order.status = OrderStatus::PartiallyFilled;
                         }
-                        
+
                         // Record Trade
                         let trade_id = Uuid::new_v4().simple().to_string();
                         log_trade(&order.symbol, "MATCH", &exec_price.to_string(), &match_qty.to_string(), &trade_id);
-                        
+
                         trades.push(TradeExecution {
                             trade_id,
                             symbol: order.symbol.clone(),
@@ -111,10 +263,11 @@ impl OrderBook {
                             maker_order_id: maker.client_order_id.clone(),
                             taker_order_id: order.client_order_id.clone(),
                         });
-                        
+
                         // Cleanup happens outside
+                        }
                     }
-                    
+
                     // Remove closed maker orders from queue
                     if let Some(maker) = queue.front() {
                         if maker.is_closed() {
@@ -124,45 +277,54 @@ impl OrderBook {
                 },
                 None => break,
             }
-            
+
             // Garbage collect empty levels
             self.asks.retain(|_, queue| !queue.is_empty());
         }
     }
 
-    fn match_ask(&mut self, order: &mut Order, trades: &mut Vec<TradeExecution>) {
+    fn match_ask(&mut self, order: &mut Order, trades: &mut Vec<TradeExecution>, maker_snapshots: &mut Vec<MakerSnapshot>, stp_cancellations: &mut Vec<Order>) {
         while order.is_active() {
             // FIX 2: Same fix for Ask side (Decouple borrow)
             let mut best_bid_entry = self.bids.iter_mut().rev().next();
-            
+
             match best_bid_entry {
                 Some((price, queue)) => {
                     if let Some(limit) = order.price {
                         if *price < limit { break; }
                     }
-                    
+
                     if let Some(maker) = queue.front_mut() {
+                        if maker.strategy_id == order.strategy_id {
+                            Self::apply_self_trade(order, maker, self.stp_policy, stp_cancellations);
+                        } else {
                         // FIX 3: Inlined execution logic
                         let match_qty = (order.original_qty - order.executed_qty).min(maker.original_qty - maker.executed_qty);
                         let exec_price = *price;
-                        
+
+                        maker_snapshots.push(MakerSnapshot {
+                            price_level: *price,
+                            order: maker.clone(),
+                            match_qty,
+                        });
+
                         maker.executed_qty += match_qty;
                         if maker.executed_qty >= maker.original_qty {
                             maker.status = OrderStatus::Filled;
                         } else {
                             maker.status = OrderStatus::PartiallyFilled;
                         }
-                        
+
                         order.executed_qty += match_qty;
                         if order.executed_qty >= order.original_qty {
                             order.status = OrderStatus::Filled;
                         } else {
                             order.status = OrderStatus::PartiallyFilled;
                         }
-                        
+
                         let trade_id = Uuid::new_v4().simple().to_string();
                         log_trade(&order.symbol, "MATCH", &exec_price.to_string(), &match_qty.to_string(), &trade_id);
-                        
+
                         trades.push(TradeExecution {
                             trade_id,
                             symbol: order.symbol.clone(),
@@ -173,8 +335,9 @@ impl OrderBook {
                             maker_order_id: maker.client_order_id.clone(),
                             taker_order_id: order.client_order_id.clone(),
                         });
+                        }
                     }
-                    
+
                     if let Some(maker) = queue.front() {
                         if maker.is_closed() {
                             queue.pop_front();
@@ -187,6 +350,50 @@ impl OrderBook {
         }
     }
 
+    /// يطبّق سياسة منع تداول النفس (STP) على تداخل آخذ/مُقيم من نفس `strategy_id`، بدلاً
+    /// من تنفيذ أي صفقة عليه: `CancelNewest` يُلغي الآخذ (الأحدث) فقط، `CancelOldest` يُلغي
+    /// المُقيم (الأقدم) فقط، `CancelBoth` يُلغي كليهما بالكامل، و`DecrementCancel` يخفِّض
+    /// كليهما بأقل الكميتين المتبقيتين ويُلغي من يصل للصفر - دون تسجيل أي صفقة في كل الحالات.
+    /// السياسة الفعلية هي `order.stp_policy` إن حدَّدها الأمر الوارد صراحة، وإلا سياسة
+    /// الدفتر الافتراضية (انظر `MatchingEngine::set_stp_policy`).
+    fn apply_self_trade(order: &mut Order, maker: &mut Order, book_default_policy: SelfTradePolicy, stp_cancellations: &mut Vec<Order>) {
+        let policy = order.stp_policy.unwrap_or(book_default_policy);
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+
+        let cancel_taker = |order: &mut Order| {
+            order.status = OrderStatus::Canceled;
+            order.order_reason = OrderReason::SelfTradePrevention;
+            order.updated_at = now;
+        };
+        let cancel_maker = |maker: &mut Order, stp_cancellations: &mut Vec<Order>| {
+            maker.status = OrderStatus::Canceled;
+            maker.order_reason = OrderReason::SelfTradePrevention;
+            maker.updated_at = now;
+            stp_cancellations.push(maker.clone());
+        };
+
+        match policy {
+            SelfTradePolicy::CancelNewest => cancel_taker(order),
+            SelfTradePolicy::CancelOldest => cancel_maker(maker, stp_cancellations),
+            SelfTradePolicy::CancelBoth => {
+                cancel_maker(maker, stp_cancellations);
+                cancel_taker(order);
+            }
+            SelfTradePolicy::DecrementCancel => {
+                let decrement = (order.original_qty - order.executed_qty).min(maker.original_qty - maker.executed_qty);
+                order.original_qty -= decrement;
+                maker.original_qty -= decrement;
+
+                if maker.original_qty <= maker.executed_qty {
+                    cancel_maker(maker, stp_cancellations);
+                }
+                if order.original_qty <= order.executed_qty {
+                    cancel_taker(order);
+                }
+            }
+        }
+    }
+
     fn add_limit_order(&mut self, order: Order) {
         if let Some(price) = order.price {
             let book = match order.side {
@@ -196,22 +403,809 @@ impl OrderBook {
             book.entry(price).or_insert_with(VecDeque::new).push_back(order);
         }
     }
+
+    /// يكتسح كلا الجانبين بحثاً عن أوامر GTD مُقيمة تجاوزت `expire_at`، ينقل كل واحد
+    /// منها إلى `OrderStatus::Expired` عبر `Order::check_expiry`، يزيله من طابوره، وينظّف
+    /// أي مستوى سعري أصبح فارغاً بعد الإزالة. يعيد نسخة من كل أمر انتهت صلاحيته تواً.
+    fn sweep_expired(&mut self, now: u64) -> Vec<Order> {
+        let mut expired = Vec::new();
+
+        for book in [&mut self.bids, &mut self.asks] {
+            for queue in book.values_mut() {
+                queue.retain_mut(|order| {
+                    if order.check_expiry(now) {
+                        expired.push(order.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            book.retain(|_, queue| !queue.is_empty());
+        }
+
+        expired
+    }
+
+    /// يبحث عن أمر مُقيم بمعرفه عبر كلا الجانبين ويزيله من الدفتر، ناقلاً حالته إلى
+    /// `OrderStatus::Canceled`. يُستخدم حالياً لإلغاء شقيق OCO فوراً متى امتلأ الآخر
+    /// (انظر `MatchingEngine::apply_oco_fill`)؛ المسح بطيء خطياً O(n) عمداً لبساطته، إذ لا
+    /// يوجد في هذا الدفتر فهرس O(1) بمعرف الأمر.
+    fn cancel_order(&mut self, order_id: u64) -> Option<Order> {
+        for book in [&mut self.bids, &mut self.asks] {
+            for queue in book.values_mut() {
+                if let Some(idx) = queue.iter().position(|o| o.id == order_id) {
+                    let mut removed = queue.remove(idx).unwrap();
+                    removed.status = OrderStatus::Canceled;
+                    removed.order_reason = crate::models::order::OrderReason::Manual;
+                    removed.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+                    return Some(removed);
+                }
+            }
+            book.retain(|_, queue| !queue.is_empty());
+        }
+        None
+    }
+
+    /// يمرّر آخر سعر للرمز على كل أمر `OrderType::TrailingStop` مسلَّح عبر
+    /// `Order::update_trailing_stop`، ويزيل من `trailing_stops` كل ما تجاوز الآن
+    /// (triggered)، مُعيداً نسخة عنه جاهزة لإعادة إصدارها كأمر سوق فعلي.
+    fn update_trailing_stops(&mut self, last_price: Decimal) -> Vec<Order> {
+        let triggered_ids: Vec<u64> = self
+            .trailing_stops
+            .values_mut()
+            .filter(|order| order.update_trailing_stop(last_price))
+            .map(|order| order.id)
+            .collect();
+
+        triggered_ids
+            .into_iter()
+            .filter_map(|id| self.trailing_stops.remove(&id))
+            .collect()
+    }
+
+    /// يحجز أمر `StopLoss`/`StopLimit` وارداً في مجموعته الكامنة المرتَّبة بدلاً من تمريره
+    /// لمنطق المطابقة، دون أي صفقات أو مطابقة معلّقة حقيقية (`ExecutableMatch` فارغة هنا
+    /// فقط لتطابق توقيع `process`؛ لا يوجد ما يُسترجَع لأمر لم يلمس الدفتر أصلاً).
+    fn park_stop_order(&mut self, mut order: Order) -> (MatchingResult, ExecutableMatch) {
+        let taker_snapshot = order.clone();
+        order.status = OrderStatus::New;
+
+        match order.side {
+            OrderSide::Buy => {
+                let idx = self.stop_buys.partition_point(|o| o.stop_price <= order.stop_price);
+                self.stop_buys.insert(idx, order.clone());
+            }
+            OrderSide::Sell => {
+                let idx = self.stop_sells.partition_point(|o| o.stop_price >= order.stop_price);
+                self.stop_sells.insert(idx, order.clone());
+            }
+        }
+
+        let leaves_qty = order.original_qty;
+        let pending = ExecutableMatch {
+            order_id: order.id,
+            symbol: self.symbol.clone(),
+            trades: Vec::new(),
+            taker_snapshot,
+            maker_snapshots: Vec::new(),
+            created_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+        };
+
+        (MatchingResult {
+            order,
+            trades: Vec::new(),
+            filled_qty: Decimal::ZERO,
+            leaves_qty,
+            derived_status: OrderStatus::New,
+            stp_cancellations: Vec::new(),
+        }, pending)
+    }
+
+    /// يفحص أوامر `StopLoss`/`StopLimit` الكامنة في كلا الجانبين مقابل `last_price`، ويفعّل
+    /// كل ما عبر سعر تفعيله (الشراء يتفعّل عند `last_price >= stop_price`، البيع عند
+    /// `last_price <= stop_price`)، محوِّلاً كل أمر مفعَّل لنوعه التنفيذي الفعلي (`StopLoss`
+    /// -> `Market`، `StopLimit` -> `Limit`) جاهزاً لإعادة تمريره عبر `process`. تُعالَج كل
+    /// التفعيلات بترتيب حتمي - سعر التفعيل أولاً ثم معرف الأمر - كي يبقى ضمان إعادة البناء
+    /// الحدثي (Event Sourcing) صحيحاً عند إعادة التشغيل.
+    fn check_stop_triggers(&mut self, last_price: Decimal) -> Vec<Order> {
+        // مرتَّبة تصاعدياً: البادئة المؤهّلة (stop_price <= last_price) تقع دوماً في البداية
+        let buy_cutoff = self.stop_buys.partition_point(|o| o.stop_price.map_or(false, |sp| sp <= last_price));
+        let mut triggered: Vec<Order> = self.stop_buys.drain(..buy_cutoff).collect();
+
+        // مرتَّبة تنازلياً: البادئة المؤهّلة (stop_price >= last_price) تقع دوماً في البداية
+        let sell_cutoff = self.stop_sells.partition_point(|o| o.stop_price.map_or(false, |sp| sp >= last_price));
+        triggered.extend(self.stop_sells.drain(..sell_cutoff));
+
+        triggered.sort_by(|a, b| a.stop_price.cmp(&b.stop_price).then(a.id.cmp(&b.id)));
+
+        for order in &mut triggered {
+            order.order_type = match order.order_type {
+                OrderType::StopLoss => OrderType::Market,
+                OrderType::StopLimit => OrderType::Limit,
+                other => other,
+            };
+        }
+
+        triggered
+    }
+}
+
+/// مستوى سعر واحد مجمَّع في لقطة العمق: مجموع `original_qty - executed_qty` لكل الأوامر
+/// المرتاحة عند هذا السعر تحديداً، لا أمراً بمفرده.
+#[derive(Debug, Clone)]
+pub struct DepthLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// لقطة عمق مجمَّعة للقراءة فقط لرمز واحد، أفضل `levels` سعر لكل جانب - انظر
+/// `MatchingEngine::depth`.
+#[derive(Debug, Clone)]
+pub struct DepthSnapshot {
+    pub symbol: String,
+    /// من الأفضل سعراً (الأعلى) إلى الأدنى
+    pub bids: Vec<DepthLevel>,
+    /// من الأفضل سعراً (الأدنى) إلى الأعلى
+    pub asks: Vec<DepthLevel>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    /// إجمالي الكمية المرتاحة على كامل الدفتر لكل جانب، وليس فقط أفضل `levels` مستوى
+    pub total_bid_volume: Decimal,
+    pub total_ask_volume: Decimal,
+}
+
+/// تراكم صفقات أمر واحد (مُعرَّف بـ `client_order_id`) عبر استدعاءات `process_order`
+/// متعددة؛ `notional` يُقسَم على `total_qty` عند الاستعلام لاشتقاق VWAP بدلاً من تخزينه
+/// مباشرة، كي يبقى التحديث التراكمي دقيقاً دون إعادة حساب المتوسط من الصفقات كل مرة.
+struct FillAccumulator {
+    original_qty: Decimal,
+    total_qty: Decimal,
+    notional: Decimal,
+    trade_ids: Vec<String>,
+}
+
+/// ملخص تنفيذ أمر واحد للقراءة فقط، مُشتق من كل `TradeExecution` لمسته حتى الآن - انظر
+/// `MatchingEngine::fills_for_order`.
+#[derive(Debug, Clone)]
+pub struct OrderFillSummary {
+    /// مجموع الكميات المنفذة عبر كل الصفقات المسجَّلة لهذا الأمر
+    pub total_qty: Decimal,
+    /// متوسط سعر التنفيذ المرجَّح بالحجم (Volume-Weighted Average Price)
+    pub vwap: Decimal,
+    pub trade_ids: Vec<String>,
+    /// الكمية المتبقية دون تنفيذ بناءً على الكمية الأصلية المرصودة أول مرة ظهر فيها هذا الأمر
+    pub remaining: Decimal,
 }
 
 pub struct MatchingEngine {
     order_books: HashMap<String, Arc<RwLock<OrderBook>>>,
+
+    /// مطابقات متفائلة (Optimistic) بانتظار تأكيد تنفيذ خارجي، مفهرسة بمعرف الآخذ.
+    /// تبقى هنا حتى `confirm_match` (تُثبَّت نهائياً) أو `rollback_match` (تُسترجع بالكامل).
+    pending_matches: RwLock<HashMap<u64, ExecutableMatch>>,
+
+    /// مركز البث المشترك لتقارير التنفيذ الحية؛ نفس المركز يغذي طبقة gRPC ومحول FIX
+    /// بنفس "سجل المحادثة" (انظر `crate::api::streaming`).
+    report_hub: Arc<ExecutionReportHub>,
+
+    /// أطفال أقواس (TP/SL) لم يُفعَّلوا بعد بانتظار امتلاء أمر الدخول، مفهرسون بمعرف الدخول
+    /// (`Order::parent_id`). يُزالان من هنا ويُرسلان فعلياً للدفتر في `activate_bracket_children`.
+    pending_brackets: RwLock<HashMap<u64, (Order, Order)>>,
+
+    /// يربط كل طفل OCO نشط بمعرف شقيقه داخل نفس `oco_group`، كي نستطيع إلغاء الشقيق
+    /// فوراً متى امتلأ أحدهما جزئياً أو كلياً (انظر `apply_oco_fill`).
+    oco_siblings: RwLock<HashMap<u64, u64>>,
+
+    /// سياسة منع التداول مع النفس (STP) الافتراضية لكل دفتر أوامر جديد يُنشَأ من الآن
+    /// فصاعداً (انظر `set_stp_policy`). لا تُغيِّر الدفاتر الموجودة مسبقاً بأثر رجعي.
+    default_stp_policy: RwLock<SelfTradePolicy>,
+
+    /// تراكم صفقات كل أمر عبر كل الدفاتر، مفهرس بـ `client_order_id` (انظر
+    /// `fills_for_order`)؛ يُحدَّث بكل من طرفي كل `TradeExecution` (الآخذ والمُقيم معاً).
+    fills: RwLock<HashMap<String, FillAccumulator>>,
+
+    /// الصندوق الأسود الجنائي (انظر `hardware::telemetry_recorder`) - `None` افتراضياً (لا
+    /// تسجيل) حتى يُحقَن صراحة عبر `with_telemetry`. التسجيل نفسه غير حاجز (`try_send`)
+    /// فلا يُبطئ مسار المطابقة الساخن.
+    telemetry: Option<Arc<TelemetryRecorder>>,
+
+    /// السجل الجنائي المتسلسل (Hash Chain) لكل عملية مقبولة على مستوى المحرك: إنشاء سوق،
+    /// وضع أمر، إلغاء أمر. انظر `matching::journal::MatchJournal` و`verify_journal`/`replay`.
+    journal: MatchJournal,
 }
 
 impl MatchingEngine {
-    pub fn new() -> Self {
-        Self { order_books: HashMap::new() }
+    pub fn new(report_hub: Arc<ExecutionReportHub>) -> Self {
+        Self {
+            order_books: HashMap::new(),
+            pending_matches: RwLock::new(HashMap::new()),
+            report_hub,
+            pending_brackets: RwLock::new(HashMap::new()),
+            oco_siblings: RwLock::new(HashMap::new()),
+            default_stp_policy: RwLock::new(SelfTradePolicy::CancelNewest),
+            fills: RwLock::new(HashMap::new()),
+            telemetry: None,
+            journal: MatchJournal::new(),
+        }
+    }
+
+    /// يحقن مسجِّل التليمترية الثنائي لتسجيل أحداث `OrderIn`/`TradeExecuted`/
+    /// `SelfTradePrevented` من `process_order` (انظر أيضاً `main.rs`). بلا استدعاء لهذه
+    /// الدالة، يبقى المحرك يعمل طبيعياً دون أي تسجيل جنائي ثنائي.
+    pub fn with_telemetry(mut self, recorder: Arc<TelemetryRecorder>) -> Self {
+        self.telemetry = Some(recorder);
+        self
+    }
+
+    /// مقبض مشترك على مركز البث، يستخدمه المستدعي (مثلاً طبقة gRPC) للاشتراك في تدفق
+    /// تقارير التنفيذ دون الحاجة لقفل المحرك بأكمله.
+    pub fn report_hub(&self) -> Arc<ExecutionReportHub> {
+        self.report_hub.clone()
+    }
+
+    /// يضبط سياسة منع التداول مع النفس الافتراضية لكل دفتر أوامر يُنشَأ بعد هذا الاستدعاء
+    /// (أوامر فردية قد تتجاوزها صراحة عبر `order.stp_policy`). لا تُطبَّق بأثر رجعي على
+    /// دفاتر أنشئت مسبقاً - ضعها قبل أول `process_order` لكل رمز جديد إن أردت سلوكاً موحَّداً.
+    pub fn set_stp_policy(&self, policy: SelfTradePolicy) {
+        *self.default_stp_policy.write() = policy;
     }
-    
+
+    /// يتحقق من سلامة كامل سلسلة السجل الجنائي (`MatchJournal`) المُحتفَظ بها في هذه
+    /// العملية منذ إقلاعها؛ يفشل إن عُبث بقيد سابق (مستحيل عبر الواجهة العامة، لكنه خط
+    /// دفاع أخير ضد فساد الذاكرة أو خطأ برمجي مستقبلي في `journal.append`).
+    pub fn verify_journal(&self) -> AlphaResult<()> {
+        self.journal.verify_integrity().map_err(Into::into)
+    }
+
+    /// آخر رقم تسلسلي مُسجَّل في السجل الجنائي - يستخدمه المستهلك (تدقيق/تسوية) كنقطة
+    /// مرجعية لطلب `journal_tail` لاحقاً.
+    pub fn journal_last_seq(&self) -> u64 {
+        self.journal.last_seq()
+    }
+
+    /// كل قيود السجل الجنائي التي تلي `after_seq` (حصرياً)، جاهزة للتدقيق الخارجي أو
+    /// كمُدخَل لـ `MatchingEngine::replay`.
+    pub fn journal_tail(&self, after_seq: u64) -> Vec<JournalEntry> {
+        self.journal.tail_after(after_seq)
+    }
+
+    /// يعيد بناء محرك جديد بإعادة تشغيل كل عملية في `entries` بالضبط كما وقعت أصلاً
+    /// (`CreateMarket` ضمنية عبر أول `process_order` لكل رمز، و`CancelOrder` مباشرة على
+    /// الدفتر المعني). يتحقق أولاً من سلامة سلسلة الهاش قبل أي إعادة تشغيل. المحرك الناتج
+    /// يطابق حالة السوق (الدفاتر والأوامر المرتاحة) عند آخر قيد في `entries`، لكن سجله
+    /// الجنائي الخاص يبدأ سلسلة جديدة من الصفر (الطوابع الزمنية تُعاد كتابتها بوقت إعادة
+    /// البناء لا وقت الحدث الأصلي) - فلا يصلح كبديل لمقارنة هاش بهاش، بل لتسوية النزاعات
+    /// ("ما الذي كان يجب أن يحدث فعلاً بهذه الأوامر؟").
+    pub fn replay(entries: &[JournalEntry], report_hub: Arc<ExecutionReportHub>) -> AlphaResult<Self> {
+        MatchJournal::verify_entries(entries)?;
+
+        let mut engine = Self::new(report_hub);
+        for entry in entries {
+            match &entry.op {
+                JournalOp::CreateMarket { .. } => {
+                    // تُنشأ ضمنياً أول `process_order` يصل لهذا الرمز أدناه؛ لا حاجة لفعل شيء هنا.
+                }
+                JournalOp::PlaceOrder { order } => {
+                    engine.process_order(order.clone())?;
+                }
+                JournalOp::CancelOrder { symbol, order_id } => {
+                    if let Some(book) = engine.order_books.get(symbol) {
+                        book.write().cancel_order(*order_id);
+                    }
+                }
+            }
+        }
+        Ok(engine)
+    }
+
+    /// لقطة عمق مجمَّعة للقراءة فقط: أفضل `levels` مستوى سعر لكل جانب من دفتر `symbol`، مع
+    /// أفضل سعر وإجمالي الحجم المرتاح لكل جانب. تأخذ قفل قراءة (`read()`) فقط على الدفتر -
+    /// لا تُعدّله ولا تحجز قفل الكتابة الذي تستخدمه `process_order` - فتسمح لمستهلكي بيانات
+    /// السوق باستطلاع حالة الدفتر بتكرار دون حجب معالجة الأوامر الحيّة. تعيد `None` إن لم
+    /// يوجد دفتر لهذا الرمز بعد.
+    pub fn depth(&self, symbol: &str, levels: usize) -> Option<DepthSnapshot> {
+        let book = self.order_books.get(symbol)?.read();
+
+        let bids: Vec<DepthLevel> = book.bids.iter().rev().take(levels)
+            .map(|(price, queue)| DepthLevel { price: *price, quantity: Self::level_quantity(queue) })
+            .collect();
+        let asks: Vec<DepthLevel> = book.asks.iter().take(levels)
+            .map(|(price, queue)| DepthLevel { price: *price, quantity: Self::level_quantity(queue) })
+            .collect();
+
+        let total_bid_volume = book.bids.values().map(Self::level_quantity).sum();
+        let total_ask_volume = book.asks.values().map(Self::level_quantity).sum();
+
+        Some(DepthSnapshot {
+            symbol: symbol.to_string(),
+            best_bid: bids.first().map(|l| l.price),
+            best_ask: asks.first().map(|l| l.price),
+            bids,
+            asks,
+            total_bid_volume,
+            total_ask_volume,
+        })
+    }
+
+    /// مجموع `original_qty - executed_qty` لكل أمر مرتاح ضمن مستوى سعر واحد (طابور FIFO واحد).
+    fn level_quantity(queue: &VecDeque<Order>) -> Decimal {
+        queue.iter().map(|o| o.original_qty - o.executed_qty).sum()
+    }
+
+    /// يراكم صفقة واحدة على ملخص أمر معين بمعرفه (`client_order_id`)؛ `original_qty` لا
+    /// يُحدَّث بعد أول تسجيل كي يبقى `remaining` مبنياً على الكمية الأصلية الحقيقية للأمر
+    /// حتى لو أُعيد استدعاء هذه الدالة من مطابقات STP لاحقة بكمية أصلية مخفَّضة.
+    fn record_fill(&self, client_order_id: &str, original_qty: Decimal, trade: &TradeExecution) {
+        let mut fills = self.fills.write();
+        let acc = fills.entry(client_order_id.to_string()).or_insert_with(|| FillAccumulator {
+            original_qty,
+            total_qty: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            trade_ids: Vec::new(),
+        });
+        acc.total_qty += trade.quantity;
+        acc.notional += trade.price * trade.quantity;
+        acc.trade_ids.push(trade.trade_id.clone());
+    }
+
+    /// ملخص تنفيذ أمر واحد مُجمَّع من كل `TradeExecution` مسَّته حتى الآن عبر كل الرموز؛
+    /// يعيد `None` إن لم يُنفَّذ أي جزء من هذا الأمر إطلاقاً بعد (لا يوجد تمييز هنا بين أمر
+    /// غير موجود وأمر لم يُطابَق بعد - كلاهما بلا صفقات مسجَّلة).
+    pub fn fills_for_order(&self, client_order_id: &str) -> Option<OrderFillSummary> {
+        let fills = self.fills.read();
+        let acc = fills.get(client_order_id)?;
+
+        let vwap = if acc.total_qty.is_zero() { Decimal::ZERO } else { acc.notional / acc.total_qty };
+
+        Some(OrderFillSummary {
+            total_qty: acc.total_qty,
+            vwap,
+            trade_ids: acc.trade_ids.clone(),
+            remaining: (acc.original_qty - acc.total_qty).max(Decimal::ZERO),
+        })
+    }
+
     pub fn process_order(&mut self, order: Order) -> AlphaResult<MatchingResult> {
         let symbol = order.symbol.clone();
+        let order_id = order.id;
+        if let Some(rec) = &self.telemetry {
+            rec.record(EventType::OrderIn, order_id, order.original_qty.to_i64().unwrap_or(0), 0);
+        }
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let default_stp_policy = *self.default_stp_policy.read();
+        let is_new_market = !self.order_books.contains_key(&symbol);
         let book = self.order_books.entry(symbol.clone())
-            .or_insert_with(|| Arc::new(RwLock::new(OrderBook::new(&symbol))));
-        
-        Ok(book.write().process(order))
+            .or_insert_with(|| Arc::new(RwLock::new(OrderBook::new(&symbol, default_stp_policy))));
+        if is_new_market {
+            self.journal.append(JournalOp::CreateMarket { symbol: symbol.clone() }, now_ms);
+        }
+
+        // السجل الجنائي يُسجِّل الأمر الوارد كما وصل فعلاً للمحرك، قبل أي تحويل تُحدثه
+        // المطابقة عليه - هذا ما يعيد بناؤه `replay` لاحقاً عبر `MatchingEngine::process_order`.
+        self.journal.append(JournalOp::PlaceOrder { order: order.clone() }, now_ms);
+
+        let (result, pending) = book.write().process(order);
+
+        // كل صفقة ومُقيمها مرتَّبان بنفس الترتيب (انظر `match_bid`/`match_ask`)؛ الآخذ
+        // واحد طوال هذا الاستدعاء (`result.order`) بينما المُقيم يختلف لكل صفقة
+        for (trade, maker_snapshot) in pending.trades.iter().zip(pending.maker_snapshots.iter()) {
+            self.record_fill(&trade.maker_order_id, maker_snapshot.order.original_qty, trade);
+            self.record_fill(&trade.taker_order_id, result.order.original_qty, trade);
+            if let Some(rec) = &self.telemetry {
+                rec.record(EventType::TradeExecuted, order_id, trade.quantity.to_i64().unwrap_or(0), 0);
+            }
+        }
+
+        // لا نسجّل مطابقة "معلّقة" إلا إذا وُجدت صفقات فعلاً لها ما تُتراجع عنه
+        if !pending.trades.is_empty() {
+            self.pending_matches.write().insert(pending.order_id, pending);
+        }
+
+        self.publish_report(&result);
+        self.publish_stp_cancellations(&result.stp_cancellations);
+
+        if let Some(rec) = &self.telemetry {
+            for cancelled in &result.stp_cancellations {
+                rec.record(EventType::SelfTradePrevented, cancelled.id, 0, 0);
+            }
+        }
+
+        // كلا الفحصين أدناه no-op فوري لأي أمر عادي غير مرتبط بقوس أو مجموعة OCO
+        if matches!(result.order.status, OrderStatus::PartiallyFilled | OrderStatus::Filled) {
+            self.activate_bracket_children(result.order.id)?;
+            self.apply_oco_fill(&result.order)?;
+        }
+
+        Ok(result)
+    }
+
+    /// يُستدعى مع كل `IngressEvent::MarketData`/صفقة واردة لرمز معين: يحرّك كل أمر
+    /// `OrderType::TrailingStop` مسلَّح في دفتر ذلك الرمز، ويفحص أوامر `StopLoss`/`StopLimit`
+    /// الكامنة عبر `check_stop_triggers` (بترتيب حتمي: سعر التفعيل ثم معرف الأمر)، ثم يعيد
+    /// إصدار كل ما تجاوز الآن (triggered) كأمر فعلي عبر `process_order` العادي (بما يشمل أي
+    /// ربط بقوس/OCO قد يحمله). ملاحظة: لا يوجد حالياً حلقة استهلاك فعلية تُفرغ
+    /// `transport::EventRx` وتستدعي هذه الدالة تلقائياً؛ هذا يبقى نقطة الربط الجاهزة لمن
+    /// يضيف تلك الحلقة، تماماً كحال `sweep_expired_orders` التي تُستدعى من مؤقّت خارجي وليس ذاتياً.
+    pub fn on_market_tick(&mut self, symbol: &str, last_price: Decimal) -> AlphaResult<Vec<MatchingResult>> {
+        let Some(book) = self.order_books.get(symbol) else { return Ok(Vec::new()); };
+        let mut triggered = book.write().update_trailing_stops(last_price);
+        triggered.extend(book.write().check_stop_triggers(last_price));
+
+        let mut results = Vec::with_capacity(triggered.len());
+        for order in triggered {
+            results.push(self.process_order(order)?);
+        }
+        Ok(results)
+    }
+
+    /// يضع أمر دخول وطفليه (جني أرباح/وقف خسارة) معاً: يُرسل الدخول فوراً للمطابقة عبر
+    /// `process_order` العادي، بينما يبقى الطفلان معلَّقين (لم يُرسَلا للدفتر بعد) حتى
+    /// يمتلئ الدخول ولو جزئياً (انظر `activate_bracket_children`).
+    pub fn place_bracket(&mut self, bracket: crate::models::order::Bracket) -> AlphaResult<MatchingResult> {
+        let crate::models::order::Bracket { entry, take_profit, stop_loss } = bracket;
+        self.pending_brackets.write().insert(entry.id, (take_profit, stop_loss));
+        self.process_order(entry)
+    }
+
+    /// يفعِّل طفلي القوس المعلَّقين لأمر دخول معين (إن وُجدا) فور امتلائه ولو جزئياً:
+    /// يرسلهما فعلياً للدفتر عبر `process_order` وكل منهما يصبح `New`، ثم يسجِّل كلاً
+    /// منهما كشقيق OCO للآخر كي يُلغى أحدهما تلقائياً متى امتلأ الآخر.
+    /// ملاحظة: إن امتلأ أحد الطفلين فوراً عند التفعيل (عبر عبوره للدفتر مباشرة) قبل أن
+    /// يُرسَل شقيقه بعد، فلن يُعثر على الشقيق لإلغائه؛ سيناريو القوس السليم عملياً (جني
+    /// الأرباح فوق السوق ووقف الخسارة تحته لمركز شراء، والعكس لمركز بيع) لا يعبر الدفتر
+    /// عند التفعيل، لذا هذا القيد نادر الحدوث وغير معالج هنا عمداً إبقاءً للمنطق بسيطاً.
+    fn activate_bracket_children(&mut self, parent_id: u64) -> AlphaResult<()> {
+        let children = self.pending_brackets.write().remove(&parent_id);
+        let Some((take_profit, stop_loss)) = children else { return Ok(()); };
+
+        let tp_id = take_profit.id;
+        let sl_id = stop_loss.id;
+        self.oco_siblings.write().insert(tp_id, sl_id);
+        self.oco_siblings.write().insert(sl_id, tp_id);
+
+        self.process_order(take_profit)?;
+        self.process_order(stop_loss)?;
+        Ok(())
+    }
+
+    /// إن كان الأمر الممتلئ (جزئياً أو كلياً) طرفاً في مجموعة OCO، يلغي شقيقه فوراً من
+    /// دفتره وينشر تقرير `Canceled` له بسبب `"OCO_SIBLING_FILLED"`.
+    fn apply_oco_fill(&mut self, filled: &Order) -> AlphaResult<()> {
+        if filled.oco_group.is_none() {
+            return Ok(());
+        }
+
+        let Some(sibling_id) = self.oco_siblings.write().remove(&filled.id) else { return Ok(()); };
+        self.oco_siblings.write().remove(&sibling_id);
+
+        let book = self.order_books.get(&filled.symbol)
+            .ok_or_else(|| AlphaError::InternalError(format!(
+                "Cannot cancel OCO sibling {}: order book for {} no longer exists", sibling_id, filled.symbol
+            )))?;
+
+        if let Some(cancelled) = book.write().cancel_order(sibling_id) {
+            self.journal.append(
+                JournalOp::CancelOrder { symbol: cancelled.symbol.clone(), order_id: cancelled.id },
+                chrono::Utc::now().timestamp_millis() as u64,
+            );
+            self.report_hub.publish(ExecutionReport {
+                order_id: cancelled.id,
+                client_order_id: cancelled.client_order_id.clone(),
+                symbol: cancelled.symbol.clone(),
+                exec_type: ExecType::Canceled,
+                last_fill_qty: None,
+                last_fill_price: None,
+                cumulative_qty: cancelled.executed_qty,
+                reason: Some("OCO_SIBLING_FILLED".to_string()),
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// يبني وينشر تقرير التنفيذ المقابل لنتيجة مطابقة واحدة بحسب `result.order.status`:
+    /// `Filled` أو `Canceled` مباشرة، وإلا `PartiallyFilled` إن وقعت صفقة و`New` إن لم تقع،
+    /// مع بيانات آخر صفقة وقعت. إن ألغي الأمر (STP أو نفاد السيولة)، يُرفق
+    /// `result.order.order_reason` كسبب نصي للتقرير.
+    fn publish_report(&self, result: &MatchingResult) {
+        let last_trade = result.trades.last();
+
+        let exec_type = match result.order.status {
+            OrderStatus::Filled => ExecType::Filled,
+            OrderStatus::Canceled => ExecType::Canceled,
+            _ if !result.trades.is_empty() => ExecType::PartiallyFilled,
+            _ => ExecType::New,
+        };
+
+        let reason = match result.order.status {
+            OrderStatus::Canceled => Some(order_reason_code(result.order.order_reason)),
+            _ => None,
+        };
+
+        self.report_hub.publish(ExecutionReport {
+            order_id: result.order.id,
+            client_order_id: result.order.client_order_id.clone(),
+            symbol: result.order.symbol.clone(),
+            exec_type,
+            last_fill_qty: last_trade.map(|t| t.quantity),
+            last_fill_price: last_trade.map(|t| t.price),
+            cumulative_qty: result.filled_qty,
+            reason,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+    }
+
+    /// ينشر تقرير `Canceled` لكل أمر مُقيم أُلغي بقرار STP ضمن مطابقة واحدة (انظر
+    /// `apply_self_trade`)؛ هذه الأوامر لم تمرّ عبر `publish_report` لأنها ليست الأمر
+    /// الآخذ (Taker) الذي تعيده `process_order` مباشرة.
+    fn publish_stp_cancellations(&self, cancellations: &[Order]) {
+        for cancelled in cancellations {
+            self.report_hub.publish(ExecutionReport {
+                order_id: cancelled.id,
+                client_order_id: cancelled.client_order_id.clone(),
+                symbol: cancelled.symbol.clone(),
+                exec_type: ExecType::Canceled,
+                last_fill_qty: None,
+                last_fill_price: None,
+                cumulative_qty: cancelled.executed_qty,
+                reason: Some(order_reason_code(cancelled.order_reason)),
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            });
+        }
+    }
+
+    /// يُثبِّت مطابقة معلّقة نهائياً بعد وصول تأكيد تنفيذ خارجي (مثلاً 150=2/150=1 عبر FIX):
+    /// تُزال من قائمة الانتظار ولا يبقى لها أثر قابل للتراجع بعد ذلك.
+    pub fn confirm_match(&self, order_id: u64) {
+        self.pending_matches.write().remove(&order_id);
+    }
+
+    /// يتراجع عن مطابقة معلّقة بالكامل: يعيد كل أمر مُقيم لمسته هذه المطابقة إلى حالته قبلها
+    /// ويعيد الأمر الآخذ (Taker) لحالته الأصلية قبل أي تنفيذ، وينشر تقرير `Canceled` أو
+    /// `Rejected` (بحسب `reason`) على مركز البث. يُستدعى عندما يفشل تأكيد التنفيذ الخارجي
+    /// (مثلاً رفض FIX صريح، 35=8 مع 150=8).
+    pub fn rollback_match(&self, order_id: u64, reason: Option<String>) -> AlphaResult<Order> {
+        let pending = self.pending_matches.write().remove(&order_id)
+            .ok_or_else(|| AlphaError::InternalError(format!(
+                "Cannot rollback order {}: no pending match found (already confirmed or never matched)", order_id
+            )))?;
+
+        let book = self.order_books.get(&pending.symbol)
+            .ok_or_else(|| AlphaError::InternalError(format!(
+                "Cannot rollback order {}: order book for {} no longer exists", order_id, pending.symbol
+            )))?;
+
+        {
+            let mut book = book.write();
+            for snapshot in pending.maker_snapshots.iter().rev() {
+                book.restore_maker(snapshot);
+            }
+        }
+
+        self.report_hub.publish(ExecutionReport {
+            order_id: pending.taker_snapshot.id,
+            client_order_id: pending.taker_snapshot.client_order_id.clone(),
+            symbol: pending.symbol.clone(),
+            exec_type: if reason.is_some() { ExecType::Rejected } else { ExecType::Canceled },
+            last_fill_qty: None,
+            last_fill_price: None,
+            cumulative_qty: Decimal::ZERO,
+            reason,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+
+        Ok(pending.taker_snapshot)
+    }
+
+    /// حلقة جني أخرى مستقلة عن `sweep_expired_orders`: تبحث في `pending_matches` عن أي
+    /// مطابقة متفائلة تجاوز عمرها `max_age_ms` دون تأكيد أو تراجع صريح (تأكيد/رفض FIX لم
+    /// يصل أبداً)، وتتراجع عنها تلقائياً عبر `rollback_match` بسبب `"PENDING_MATCH_EXPIRED"`.
+    /// تُستدعى دورياً من مؤقّت خارجي تماماً كـ `sweep_expired_orders`؛ لا حلقة داخلية هنا.
+    pub fn expire_stale_matches(&self, now: u64, max_age_ms: u64) -> Vec<Order> {
+        let stale_ids: Vec<u64> = self.pending_matches.read().iter()
+            .filter(|(_, pending)| now.saturating_sub(pending.created_at_ms) >= max_age_ms)
+            .map(|(order_id, _)| *order_id)
+            .collect();
+
+        stale_ids.into_iter()
+            .filter_map(|order_id| self.rollback_match(order_id, Some("PENDING_MATCH_EXPIRED".to_string())).ok())
+            .collect()
+    }
+
+    /// حلقة الجني (Reaper): تكتسح كل دفاتر الأوامر بحثاً عن أوامر GTD مُقيمة تجاوزت
+    /// وقت انتهائها، تنشر تقرير `Canceled` مع `reason = "GTD_EXPIRED"` لكل واحد منها على
+    /// مركز البث، وتعيدها كلها للمستدعي (مثلاً `main.rs`، عبر مؤقّت دوري) لأي معالجة
+    /// لاحقة كفك حجز الأموال المرتبطة بها.
+    pub fn sweep_expired_orders(&self, now: u64) -> Vec<Order> {
+        let mut expired = Vec::new();
+
+        for book in self.order_books.values() {
+            expired.extend(book.write().sweep_expired(now));
+        }
+
+        for order in &expired {
+            self.report_hub.publish(ExecutionReport {
+                order_id: order.id,
+                client_order_id: order.client_order_id.clone(),
+                symbol: order.symbol.clone(),
+                exec_type: ExecType::Canceled,
+                last_fill_qty: None,
+                last_fill_price: None,
+                cumulative_qty: order.executed_qty,
+                reason: Some("GTD_EXPIRED".to_string()),
+                timestamp: now,
+            });
+        }
+
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn limit(strategy: &str, side: OrderSide, price: f64, qty: f64) -> Order {
+        Order::new(
+            rand_id(), strategy.into(), "BTCUSDT".into(), "BINANCE".into(),
+            side, OrderType::Limit, dec!(qty), Some(Decimal::try_from(price).unwrap()),
+        )
+    }
+
+    // مولّد معرفات بسيط ومتسلسل للاختبارات فقط (لا علاقة له بـ utils::id الحقيقي)
+    fn rand_id() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_cancel_newest_skips_self_trade_and_leaves_maker_resting() {
+        let mut book = OrderBook::new("BTCUSDT", SelfTradePolicy::CancelNewest);
+
+        let (resting_result, _) = book.process(limit("ALPHA", OrderSide::Sell, 100.0, 1.0));
+        assert_eq!(resting_result.order.status, OrderStatus::New);
+
+        let (taker_result, _) = book.process(limit("ALPHA", OrderSide::Buy, 100.0, 1.0));
+
+        assert!(taker_result.trades.is_empty(), "CancelNewest must not execute a self-trade");
+        assert_eq!(taker_result.order.status, OrderStatus::Canceled);
+        assert_eq!(taker_result.order.order_reason, OrderReason::SelfTradePrevention);
+        assert!(taker_result.stp_cancellations.is_empty(), "maker must not be cancelled under CancelNewest");
+    }
+
+    #[test]
+    fn test_cancel_oldest_cancels_maker_and_reports_it_in_stp_cancellations() {
+        let mut book = OrderBook::new("BTCUSDT", SelfTradePolicy::CancelOldest);
+
+        book.process(limit("ALPHA", OrderSide::Sell, 100.0, 1.0));
+        let (taker_result, _) = book.process(limit("ALPHA", OrderSide::Buy, 100.0, 1.0));
+
+        assert!(taker_result.trades.is_empty());
+        assert_eq!(taker_result.stp_cancellations.len(), 1);
+        assert_eq!(taker_result.stp_cancellations[0].order_reason, OrderReason::SelfTradePrevention);
+        assert_eq!(taker_result.stp_cancellations[0].status, OrderStatus::Canceled);
+    }
+
+    #[test]
+    fn test_cancel_both_cancels_taker_and_maker_with_no_trade() {
+        let mut book = OrderBook::new("BTCUSDT", SelfTradePolicy::CancelBoth);
+
+        book.process(limit("ALPHA", OrderSide::Sell, 100.0, 1.0));
+        let (taker_result, _) = book.process(limit("ALPHA", OrderSide::Buy, 100.0, 1.0));
+
+        assert!(taker_result.trades.is_empty());
+        assert_eq!(taker_result.order.status, OrderStatus::Canceled);
+        assert_eq!(taker_result.order.order_reason, OrderReason::SelfTradePrevention);
+        assert_eq!(taker_result.stp_cancellations.len(), 1);
+    }
+
+    #[test]
+    fn test_decrement_cancel_reduces_taker_and_maker_without_trading() {
+        let mut book = OrderBook::new("BTCUSDT", SelfTradePolicy::DecrementCancel);
+
+        book.process(limit("ALPHA", OrderSide::Sell, 100.0, 3.0));
+        let (taker_result, _) = book.process(limit("ALPHA", OrderSide::Buy, 100.0, 1.0));
+
+        assert!(taker_result.trades.is_empty());
+        // الآخذ (1.0) يُستنفد بالكامل فيُلغى، والمُقيم يتقلص إلى 2.0 ويبقى في الدفتر
+        assert_eq!(taker_result.order.status, OrderStatus::Canceled);
+        assert_eq!(taker_result.order.order_reason, OrderReason::SelfTradePrevention);
+        assert!(taker_result.stp_cancellations.is_empty(), "maker still has leaves qty, so it is not cancelled");
+    }
+
+    #[test]
+    fn test_market_order_with_no_liquidity_is_canceled_with_structured_reason() {
+        let mut book = OrderBook::new("BTCUSDT", SelfTradePolicy::CancelNewest);
+
+        let market_order = Order::new(
+            rand_id(), "ALPHA".into(), "BTCUSDT".into(), "BINANCE".into(),
+            OrderSide::Buy, OrderType::Market, dec!(1.0), None,
+        );
+        let (result, _) = book.process(market_order);
+
+        assert_eq!(result.order.status, OrderStatus::Canceled);
+        assert_eq!(result.order.order_reason, OrderReason::NoLiquidity);
+    }
+
+    fn stop_order(strategy: &str, side: OrderSide, order_type: OrderType, stop_price: f64, qty: f64) -> Order {
+        // للبساطة في الاختبار: سعر الحد (لأوامر `StopLimit` فقط) يساوي سعر التفعيل نفسه
+        let limit_price = matches!(order_type, OrderType::StopLimit).then(|| Decimal::try_from(stop_price).unwrap());
+        let mut order = Order::new(
+            rand_id(), strategy.into(), "BTCUSDT".into(), "BINANCE".into(),
+            side, order_type, dec!(qty), limit_price,
+        );
+        order.stop_price = Some(Decimal::try_from(stop_price).unwrap());
+        order
+    }
+
+    fn new_engine() -> MatchingEngine {
+        MatchingEngine::new(Arc::new(ExecutionReportHub::new()))
+    }
+
+    #[test]
+    fn test_stop_market_order_parks_without_trading_until_triggered() {
+        let mut engine = new_engine();
+
+        let result = engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLoss, 110.0, 1.0)).unwrap();
+        assert!(result.trades.is_empty());
+        assert_eq!(result.order.status, OrderStatus::New, "parked stop order must not be matched or closed yet");
+    }
+
+    #[test]
+    fn test_stop_buy_triggers_when_price_rises_to_or_above_stop_and_converts_to_market() {
+        let mut engine = new_engine();
+
+        // مُقيم بيع بسعر 110 لتنفيذ أمر وقف-الشراء السوقي بمجرد تفعيله
+        engine.process_order(limit("BETA", OrderSide::Sell, 110.0, 1.0)).unwrap();
+        engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLoss, 110.0, 1.0)).unwrap();
+
+        let results = engine.on_market_tick("BTCUSDT", dec!(110.0)).unwrap();
+
+        assert_eq!(results.len(), 1, "the parked stop order must fire exactly once when its trigger is crossed");
+        assert_eq!(results[0].trades.len(), 1, "StopLoss converts to Market and must execute against the resting maker");
+        assert_eq!(results[0].order.order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn test_stop_sell_does_not_trigger_below_its_activation_price() {
+        let mut engine = new_engine();
+
+        engine.process_order(limit("BETA", OrderSide::Buy, 95.0, 1.0)).unwrap();
+        engine.process_order(stop_order("ALPHA", OrderSide::Sell, OrderType::StopLoss, 90.0, 1.0)).unwrap();
+
+        // لا يزال السعر أعلى من سعر التفعيل (90) لأمر بيع كامن، فلا يجب أن يتفعّل بعد
+        let results = engine.on_market_tick("BTCUSDT", dec!(95.0)).unwrap();
+        assert!(results.is_empty(), "stop-sell must only trigger once price falls to or below its stop price");
+    }
+
+    #[test]
+    fn test_stop_limit_converts_to_limit_and_rests_if_unfilled() {
+        let mut engine = new_engine();
+
+        engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLimit, 110.0, 1.0)).unwrap();
+        let results = engine.on_market_tick("BTCUSDT", dec!(110.0)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].order.order_type, OrderType::Limit, "StopLimit must convert to Limit, not Market, on activation");
+    }
+
+    #[test]
+    fn test_stop_triggers_activate_in_deterministic_price_then_id_order() {
+        let mut engine = new_engine();
+
+        // ثلاثة أوامر وقف-شراء بأسعار تفعيل مختلفة، تُودَع بترتيب عشوائي
+        engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLimit, 102.0, 1.0)).unwrap();
+        engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLimit, 100.0, 1.0)).unwrap();
+        engine.process_order(stop_order("ALPHA", OrderSide::Buy, OrderType::StopLimit, 101.0, 1.0)).unwrap();
+
+        let results = engine.on_market_tick("BTCUSDT", dec!(102.0)).unwrap();
+
+        assert_eq!(results.len(), 3);
+        let triggered_prices: Vec<Decimal> = results.iter().map(|r| r.order.stop_price.unwrap()).collect();
+        assert_eq!(triggered_prices, vec![dec!(100.0), dec!(101.0), dec!(102.0)], "activations must be ordered by trigger price first");
     }
 }
@@ -0,0 +1,108 @@
+// Maker/Taker Fee Schedule
+
+/*
+ * ALPHA SOVEREIGN - MAKER/TAKER FEE SCHEDULE
+ * =================================================================
+ * Component Name: engine/src/matching/fee_schedule.rs
+ * Core Responsibility: تحديد رسوم الصانع والآخذ لكل صفقة، مع دعم مستويات تدرّجية حسب حجم التداول (Revenue Pillar).
+ * Design Pattern: Tiered Rate Table (على غرار جداول رسوم OpenBook/Binance)
+ * Forensic Impact: أي خطأ هنا يعني إيرادات غير محصّلة أو خصماً خاطئاً من حساب المتداول.
+ * =================================================================
+ */
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// مستوى رسوم واحد: يُطبَّق عندما يبلغ الحجم المتداول المتراكم (30 يوماً) `notional_threshold` أو أكثر.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    /// الحد الأدنى للحجم التراكمي (Rolling 30d Notional) كي يُطبَّق هذا المستوى
+    pub notional_threshold: Decimal,
+    /// رسوم الصانع بالنقاط الأساسية (bps)؛ قد تكون سالبة (خصم/Rebate)
+    pub maker_bps: Decimal,
+    /// رسوم الآخذ بالنقاط الأساسية (bps)
+    pub taker_bps: Decimal,
+}
+
+/// جدول الرسوم الكامل لدفتر أوامر واحد: مستويات مرتبة تصاعدياً حسب `notional_threshold`.
+/// المستوى الأول يجب أن يبدأ عند `Decimal::ZERO` (الرسوم الافتراضية لمن لا حجم تداول له بعد).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeSchedule {
+    tiers: Vec<FeeTier>,
+}
+
+impl FeeSchedule {
+    /// جدول رسوم مسطّح (بدون مستويات): نفس السعر بغض النظر عن حجم التداول
+    pub fn flat(maker_bps: Decimal, taker_bps: Decimal) -> Self {
+        Self {
+            tiers: vec![FeeTier {
+                notional_threshold: Decimal::ZERO,
+                maker_bps,
+                taker_bps,
+            }],
+        }
+    }
+
+    /// إضافة مستوى جديد يُفعَّل عند `notional_threshold` من الحجم التراكمي (يعيد ترتيب المستويات تصاعدياً)
+    pub fn with_tier(mut self, notional_threshold: Decimal, maker_bps: Decimal, taker_bps: Decimal) -> Self {
+        self.tiers.push(FeeTier {
+            notional_threshold,
+            maker_bps,
+            taker_bps,
+        });
+        self.tiers.sort_by(|a, b| a.notional_threshold.cmp(&b.notional_threshold));
+        self
+    }
+
+    /// يجد أعلى مستوى ينطبق على `rolling_notional` (آخر مستوى بحد أدنى <= الحجم التراكمي)
+    fn tier_for(&self, rolling_notional: Decimal) -> &FeeTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.notional_threshold <= rolling_notional)
+            .unwrap_or(&self.tiers[0])
+    }
+
+    /// رسوم الصانع المستحقة على هذه الصفقة (قد تكون سالبة = خصم يُضاف لرصيد الصانع)
+    pub fn maker_fee(&self, notional: Decimal, rolling_notional: Decimal) -> Decimal {
+        notional * self.tier_for(rolling_notional).maker_bps / Decimal::from(10_000)
+    }
+
+    /// رسوم الآخذ المستحقة على هذه الصفقة
+    pub fn taker_fee(&self, notional: Decimal, rolling_notional: Decimal) -> Decimal {
+        notional * self.tier_for(rolling_notional).taker_bps / Decimal::from(10_000)
+    }
+}
+
+impl Default for FeeSchedule {
+    /// الرسوم الافتراضية لأي دفتر جديد لم يُمرَّر له جدول مخصص: لا خصم للصانع، 5 نقاط أساس للآخذ
+    fn default() -> Self {
+        Self::flat(Decimal::ZERO, dec!(5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn flat_schedule_applies_same_rate_regardless_of_volume() {
+        let schedule = FeeSchedule::flat(dec!(-1), dec!(5));
+        assert_eq!(schedule.maker_fee(dec!(100000), dec!(0)), dec!(-10));
+        assert_eq!(schedule.maker_fee(dec!(100000), dec!(999999999)), dec!(-10));
+        assert_eq!(schedule.taker_fee(dec!(100000), dec!(0)), dec!(50));
+    }
+
+    #[test]
+    fn tier_crossing_lowers_taker_fee() {
+        let schedule = FeeSchedule::flat(dec!(0), dec!(10))
+            .with_tier(dec!(1_000_000), dec!(0), dec!(4));
+
+        // تحت الحد: الرسوم الأساسية
+        assert_eq!(schedule.taker_fee(dec!(100000), dec!(500_000)), dec!(100));
+        // عند تجاوز الحد التراكمي: الرسوم المخفّضة
+        assert_eq!(schedule.taker_fee(dec!(100000), dec!(1_000_000)), dec!(40));
+        assert_eq!(schedule.taker_fee(dec!(100000), dec!(5_000_000)), dec!(40));
+    }
+}
@@ -1,214 +1,435 @@
-// Asset Inventory
-
-/*
- * ALPHA SOVEREIGN - REAL-TIME INVENTORY & POSITION MANAGER
- * =================================================================
- * Component Name: engine/src/matching/inventory_mgr.rs
- * Core Responsibility: تتبع الأصول والمراكز المفتوحة بدقة ذرية (Risk Management Pillar).
- * Design Pattern: Ledger / Double-Entry Lite
- * Forensic Impact: يمنع "الأموال الشبحية". كل ساتوشي يجب أن يكون له مكان (إما حر أو محجوز).
- * =================================================================
- */
-
-use std::collections::HashMap;
-use rust_decimal::Decimal;
-use tracing::{info, warn, error};
-use crate::error::{AlphaError, AlphaResult};
-use super::Side;
-
-/// تمثل حالة أصل واحد (مثلاً USD أو BTC)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssetBalance {
-    pub asset_name: String,
-    
-    /// الرصيد الكلي (الحر + المحجوز)
-    pub total: Decimal,
-    
-    /// الرصيد المحجوز في أوامر نشطة
-    pub locked: Decimal,
-    
-    /// متوسط سعر الدخول (لحساب الـ PnL)
-    pub avg_entry_price: Decimal,
-}
-
-impl AssetBalance {
-    pub fn new(name: &str) -> Self {
-        Self {
-            asset_name: name.to_string(),
-            total: Decimal::ZERO,
-            locked: Decimal::ZERO,
-            avg_entry_price: Decimal::ZERO,
-        }
-    }
-
-    /// الرصيد المتاح للتداول أو السحب
-    pub fn available(&self) -> Decimal {
-        self.total - self.locked
-    }
-}
-
-pub struct InventoryManager {
-    /// سجل الأرصدة: Map<Asset_Symbol, Balance>
-    balances: HashMap<String, AssetBalance>,
-}
-
-impl InventoryManager {
-    pub fn new() -> Self {
-        Self {
-            balances: HashMap::new(),
-        }
-    }
-
-    // ----------------------------------------------------------------
-    // عمليات التمويل الخارجية (Deposit / Withdraw)
-    // ----------------------------------------------------------------
-
-    pub fn deposit(&mut self, asset: &str, amount: Decimal) {
-        let entry = self.balances.entry(asset.to_string())
-            .or_insert_with(|| AssetBalance::new(asset));
-        
-        entry.total += amount;
-        info!("INVENTORY: Deposited {} {}. New Total: {}", amount, asset, entry.total);
-    }
-
-    pub fn withdraw(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
-        let entry = self.balances.get_mut(asset)
-            .ok_or_else(|| AlphaError::UnknownAsset(asset.to_string()))?;
-
-        if entry.available() < amount {
-            return Err(AlphaError::RiskViolation {
-                rule: "Insufficient Funds".to_string(),
-                limit: entry.available().to_string(),
-                actual: amount.to_string(),
-            });
-        }
-
-        entry.total -= amount;
-        info!("INVENTORY: Withdrawn {} {}. Remaining: {}", amount, asset, entry.total);
-        Ok(())
-    }
-
-    // ----------------------------------------------------------------
-    // إدارة الأوامر (Locking / Unlocking)
-    // ----------------------------------------------------------------
-
-    /// حجز أموال لأمر جديد (Pre-Trade Check)
-    pub fn lock_funds(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
-        let entry = self.balances.entry(asset.to_string())
-            .or_insert_with(|| AssetBalance::new(asset));
-
-        if entry.available() < amount {
-            return Err(AlphaError::RiskViolation {
-                rule: "Insufficient Balance for Order".to_string(),
-                limit: entry.available().to_string(),
-                actual: amount.to_string(),
-            });
-        }
-
-        entry.locked += amount;
-        // لا نسجل Log هنا لتجنب إغراق السجلات، لأن الحجز يحدث بكثرة
-        Ok(())
-    }
-
-    /// فك الحجز (عند الإلغاء أو الرفض)
-    pub fn unlock_funds(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
-        let entry = self.balances.get_mut(asset)
-            .ok_or_else(|| AlphaError::UnknownAsset(asset.to_string()))?;
-
-        if entry.locked < amount {
-            // هذا خطأ خطير يعني وجود خلل في المنطق (Bug)
-            error!("CRITICAL: Attempted to unlock more than locked! Asset: {}, Locked: {}, Req: {}", asset, entry.locked, amount);
-            // نصحح الوضع قسرياً لمنع الانهيار، لكن يجب التحقيق
-            entry.locked = Decimal::ZERO;
-        } else {
-            entry.locked -= amount;
-        }
-        Ok(())
-    }
-
-    // ----------------------------------------------------------------
-    // تسوية الصفقات (Trade Settlement)
-    // ----------------------------------------------------------------
-
-    /// تحديث الأرصدة بعد تنفيذ صفقة ناجحة
-    /// side: هو جانب الـ User (هل نحن اشترينا أم بعنا؟)
-    pub fn commit_trade(&mut self, 
-                        base_asset: &str, 
-                        quote_asset: &str, 
-                        side: Side, 
-                        qty: Decimal, 
-                        price: Decimal, 
-                        fee: Decimal) -> AlphaResult<()> {
-        
-        let cost = qty * price;
-
-        match side {
-            Side::Bid => {
-                // شراء: (Buy Base, Pay Quote)
-                
-                // 1. خصم التكلفة من الـ Quote (المحجوزة سابقاً)
-                let quote_bal = self.balances.get_mut(quote_asset)
-                    .ok_or_else(|| AlphaError::Fatal("Quote asset missing during settlement".into()))?;
-                
-                // نفترض أن الأموال كانت محجوزة. نقلل الـ Locked والـ Total
-                // ملاحظة: التكلفة الفعلية قد تختلف قليلاً عن المحجوزة، نعالج الفرق
-                quote_bal.total -= cost; 
-                // نفترض أننا قمنا بفك الحجز قبل الـ commit أو نقلل الـ locked هنا بمقدار التكلفة
-                // للتبسيط: سنقلل الـ locked بنفس القيمة (بافتراض الحجز الدقيق)
-                if quote_bal.locked >= cost {
-                     quote_bal.locked -= cost;
-                } else {
-                     // هذا يحدث إذا نفذنا بسعر أفضل من المحدد (Slippage favorable)
-                     quote_bal.locked = Decimal::ZERO; 
-                }
-
-                // 2. إضافة الكمية للـ Base
-                let base_bal = self.balances.entry(base_asset.to_string())
-                    .or_insert_with(|| AssetBalance::new(base_asset));
-                
-                // تحديث متوسط السعر (Weighted Average)
-                // NewAvg = ((OldTotal * OldAvg) + (NewQty * BuyPrice)) / (OldTotal + NewQty)
-                let old_val = base_bal.total * base_bal.avg_entry_price;
-                let new_val = qty * price;
-                let new_total = base_bal.total + qty - fee; // خصم الرسوم من العملة المستلمة
-                
-                if new_total > Decimal::ZERO {
-                    base_bal.avg_entry_price = (old_val + new_val) / (base_bal.total + qty);
-                }
-                
-                base_bal.total = new_total;
-            },
-
-            Side::Ask => {
-                // بيع: (Sell Base, Receive Quote)
-                
-                // 1. خصم الكمية من الـ Base (المحجوزة)
-                let base_bal = self.balances.get_mut(base_asset)
-                    .ok_or_else(|| AlphaError::Fatal("Base asset missing during settlement".into()))?;
-                
-                base_bal.total -= qty;
-                if base_bal.locked >= qty {
-                    base_bal.locked -= qty;
-                } else {
-                    base_bal.locked = Decimal::ZERO;
-                }
-
-                // 2. إضافة التكلفة للـ Quote
-                let quote_bal = self.balances.entry(quote_asset.to_string())
-                    .or_insert_with(|| AssetBalance::new(quote_asset));
-                
-                quote_bal.total += cost - fee; // خصم الرسوم من الـ Quote المستلمة
-            }
-        }
-
-        info!("SETTLEMENT: Trade committed for {}/{} (Qty: {}, Price: {})", base_asset, quote_asset, qty, price);
-        Ok(())
-    }
-
-    /// الحصول على تقرير المحفظة الكامل
-    pub fn get_portfolio_snapshot(&self) -> HashMap<String, Decimal> {
-        self.balances.iter()
-            .map(|(k, v)| (k.clone(), v.total))
-            .collect()
-    }
-}
\ No newline at end of file
+// Asset Inventory
+
+/*
+ * ALPHA SOVEREIGN - REAL-TIME INVENTORY & POSITION MANAGER
+ * =================================================================
+ * Component Name: engine/src/matching/inventory_mgr.rs
+ * Core Responsibility: تتبع الأصول والمراكز المفتوحة بدقة ذرية (Risk Management Pillar).
+ * Design Pattern: Ledger / Double-Entry Lite + Append-Only Journal
+ * Forensic Impact: يمنع "الأموال الشبحية". كل ساتوشي يجب أن يكون له مكان (إما حر أو محجوز)،
+ *                  وكل حركة مسجلة في دفتر لا يمكن تعديله لاحقاً، فتصبح قابلة لإعادة البناء والتدقيق.
+ * =================================================================
+ */
+
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+use serde::{Serialize, Deserialize};
+use tracing::{info, warn, error};
+use crate::error::{AlphaError, AlphaResult};
+use super::Side;
+
+/// تمثل حالة أصل واحد (مثلاً USD أو BTC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetBalance {
+    pub asset_name: String,
+
+    /// الرصيد الكلي (الحر + المحجوز)
+    pub total: Decimal,
+
+    /// الرصيد المحجوز في أوامر نشطة
+    pub locked: Decimal,
+
+    /// متوسط سعر الدخول (لحساب الـ PnL)
+    pub avg_entry_price: Decimal,
+
+    /// الربح/الخسارة المحققة (Realized PnL) المتراكمة من صفقات البيع فقط
+    pub realized_pnl: Decimal,
+}
+
+impl AssetBalance {
+    pub fn new(name: &str) -> Self {
+        Self {
+            asset_name: name.to_string(),
+            total: Decimal::ZERO,
+            locked: Decimal::ZERO,
+            avg_entry_price: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    /// الرصيد المتاح للتداول أو السحب
+    pub fn available(&self) -> Decimal {
+        self.total - self.locked
+    }
+}
+
+/// قيد دفتر أستاذ واحد غير قابل للتعديل (Append-Only). يلتقط أثر حركة واحدة على رصيد أصل،
+/// بحيث يمكن إعادة بناء `total`/`locked` الحاليين بجمع كل القيود السابقة والتحقق من التطابق.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: u64,
+    pub asset: String,
+    /// التغيّر على الرصيد الكلي في هذه الحركة
+    pub delta_total: Decimal,
+    /// التغيّر على الرصيد المحجوز في هذه الحركة
+    pub delta_locked: Decimal,
+    /// سبب الحركة (e.g. "DEPOSIT", "LOCK", "SETTLEMENT_QUOTE_LEG")
+    pub reason: String,
+    /// الرصيد الكلي الناتج بعد هذه الحركة (للتدقيق المباشر دون إعادة بناء كاملة)
+    pub resulting_total: Decimal,
+    /// الرصيد المحجوز الناتج بعد هذه الحركة
+    pub resulting_locked: Decimal,
+}
+
+/// تقرير الربح والخسارة لأصل واحد
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlReport {
+    pub asset: String,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Option<Decimal>,
+    pub avg_entry_price: Decimal,
+    pub position: Decimal,
+}
+
+pub struct InventoryManager {
+    /// سجل الأرصدة: Map<Asset_Symbol, Balance>
+    balances: HashMap<String, AssetBalance>,
+
+    /// دفتر الأستاذ الجنائي: كل حركة على أي رصيد، بالترتيب، لا تُحذف ولا تُعدَّل أبداً
+    journal: Vec<JournalEntry>,
+}
+
+impl InventoryManager {
+    pub fn new() -> Self {
+        Self {
+            balances: HashMap::new(),
+            journal: Vec::new(),
+        }
+    }
+
+    /// تسجيل حركة واحدة في دفتر الأستاذ بعد تطبيقها على الرصيد (`resulting_*` يُقرأ من الحالة الحالية)
+    fn record(&mut self, asset: &str, delta_total: Decimal, delta_locked: Decimal, reason: &str) {
+        let (resulting_total, resulting_locked) = self.balances.get(asset)
+            .map(|b| (b.total, b.locked))
+            .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+
+        self.journal.push(JournalEntry {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            asset: asset.to_string(),
+            delta_total,
+            delta_locked,
+            reason: reason.to_string(),
+            resulting_total,
+            resulting_locked,
+        });
+    }
+
+    /// يحوّل رسماً مُحصَّلاً إلى رصيد مُخصَّص لحساب إيرادات البورصة، بحيث تبقى الرسوم أصلاً حقيقياً
+    /// قابلاً للتدقيق بدلاً من أن "تختفي" عند الخصم من رصيد المتداول. رسوم سالبة (خصم/Rebate) تُنقص
+    /// رصيد المُحصِّل بدلاً من زيادته.
+    fn accrue_fee(&mut self, asset: &str, fee: Decimal) {
+        if fee == Decimal::ZERO {
+            return;
+        }
+        let collector_asset = Self::fee_collector_asset(asset);
+        let entry = self.balances.entry(collector_asset.clone())
+            .or_insert_with(|| AssetBalance::new(&collector_asset));
+        entry.total += fee;
+        self.record(&collector_asset, fee, Decimal::ZERO, "FEE_ACCRUAL");
+    }
+
+    /// اسم رصيد حساب تحصيل الرسوم المخصَّص لأصل معيّن (مفصول عن أرصدة المتداولين العاديين)
+    fn fee_collector_asset(asset: &str) -> String {
+        format!("FEES:{}", asset)
+    }
+
+    /// رصيد حساب تحصيل الرسوم المتراكم لأصل معيّن (لمراقبة إيرادات البورصة)
+    pub fn fee_collector_balance(&self, asset: &str) -> Decimal {
+        self.balances.get(&Self::fee_collector_asset(asset))
+            .map(|b| b.total)
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    // ----------------------------------------------------------------
+    // عمليات التمويل الخارجية (Deposit / Withdraw)
+    // ----------------------------------------------------------------
+
+    pub fn deposit(&mut self, asset: &str, amount: Decimal) {
+        let entry = self.balances.entry(asset.to_string())
+            .or_insert_with(|| AssetBalance::new(asset));
+
+        entry.total += amount;
+        let new_total = entry.total;
+        self.record(asset, amount, Decimal::ZERO, "DEPOSIT");
+        info!("INVENTORY: Deposited {} {}. New Total: {}", amount, asset, new_total);
+    }
+
+    pub fn withdraw(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
+        let entry = self.balances.get_mut(asset)
+            .ok_or_else(|| AlphaError::ValidationFailed(format!("Unknown asset: {}", asset)))?;
+
+        if entry.available() < amount {
+            return Err(AlphaError::RiskViolation {
+                rule: "Insufficient Funds".to_string(),
+                limit: entry.available().to_string(),
+                actual: amount.to_string(),
+            });
+        }
+
+        entry.total -= amount;
+        let new_total = entry.total;
+        self.record(asset, -amount, Decimal::ZERO, "WITHDRAW");
+        info!("INVENTORY: Withdrawn {} {}. Remaining: {}", amount, asset, new_total);
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------
+    // إدارة الأوامر (Locking / Unlocking)
+    // ----------------------------------------------------------------
+
+    /// حجز أموال لأمر جديد (Pre-Trade Check)
+    pub fn lock_funds(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
+        let entry = self.balances.entry(asset.to_string())
+            .or_insert_with(|| AssetBalance::new(asset));
+
+        if entry.available() < amount {
+            return Err(AlphaError::RiskViolation {
+                rule: "Insufficient Balance for Order".to_string(),
+                limit: entry.available().to_string(),
+                actual: amount.to_string(),
+            });
+        }
+
+        entry.locked += amount;
+        self.record(asset, Decimal::ZERO, amount, "LOCK");
+        Ok(())
+    }
+
+    /// فك الحجز (عند الإلغاء أو الرفض)
+    pub fn unlock_funds(&mut self, asset: &str, amount: Decimal) -> AlphaResult<()> {
+        let entry = self.balances.get_mut(asset)
+            .ok_or_else(|| AlphaError::ValidationFailed(format!("Unknown asset: {}", asset)))?;
+
+        let actually_unlocked = if entry.locked < amount {
+            // هذا خطأ خطير يعني وجود خلل في المنطق (Bug)
+            error!("CRITICAL: Attempted to unlock more than locked! Asset: {}, Locked: {}, Req: {}", asset, entry.locked, amount);
+            // نصحح الوضع قسرياً لمنع الانهيار، لكن يجب التحقيق
+            let unlocked = entry.locked;
+            entry.locked = Decimal::ZERO;
+            unlocked
+        } else {
+            entry.locked -= amount;
+            amount
+        };
+        self.record(asset, Decimal::ZERO, -actually_unlocked, "UNLOCK");
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------
+    // تسوية الصفقات (Trade Settlement)
+    // ----------------------------------------------------------------
+
+    /// تحديث الأرصدة بعد تنفيذ صفقة ناجحة
+    /// side: هو جانب الـ User (هل نحن اشترينا أم بعنا؟)
+    pub fn commit_trade(&mut self,
+                        base_asset: &str,
+                        quote_asset: &str,
+                        side: Side,
+                        qty: Decimal,
+                        price: Decimal,
+                        fee: Decimal) -> AlphaResult<()> {
+
+        let cost = qty * price;
+
+        match side {
+            Side::Bid => {
+                // شراء: (Buy Base, Pay Quote)
+
+                // 1. خصم التكلفة من الـ Quote (المحجوزة سابقاً)
+                let quote_bal = self.balances.get_mut(quote_asset)
+                    .ok_or_else(|| AlphaError::Fatal("Quote asset missing during settlement".into()))?;
+
+                // نفترض أن الأموال كانت محجوزة. نقلل الـ Locked والـ Total
+                // ملاحظة: التكلفة الفعلية قد تختلف قليلاً عن المحجوزة، نعالج الفرق
+                quote_bal.total -= cost;
+                // نفترض أننا قمنا بفك الحجز قبل الـ commit أو نقلل الـ locked هنا بمقدار التكلفة
+                // للتبسيط: سنقلل الـ locked بنفس القيمة (بافتراض الحجز الدقيق)
+                let locked_delta = if quote_bal.locked >= cost {
+                     quote_bal.locked -= cost;
+                     cost
+                } else {
+                     // هذا يحدث إذا نفذنا بسعر أفضل من المحدد (Slippage favorable)
+                     let had = quote_bal.locked;
+                     quote_bal.locked = Decimal::ZERO;
+                     had
+                };
+                self.record(quote_asset, -cost, -locked_delta, "SETTLEMENT_QUOTE_LEG_BUY");
+
+                // 2. إضافة الكمية للـ Base
+                let base_bal = self.balances.entry(base_asset.to_string())
+                    .or_insert_with(|| AssetBalance::new(base_asset));
+
+                // تحديث متوسط السعر (Weighted Average)
+                // NewAvg = ((OldTotal * OldAvg) + (NewQty * BuyPrice)) / NewTotal
+                // المقام يجب أن يكون `new_total` (بعد خصم الرسوم) لا `base_bal.total + qty` قبلها:
+                // الرسوم تُخصَم من كمية الـ base المستلمة فعلياً، فلو قسمنا على الكمية قبل خصمها
+                // لأصبح `avg_entry_price` أقل مما يجب نسبة للكمية المخزَّنة فعلاً في `total`،
+                // فيفسد `realized_pnl`/`unrealized_pnl` المبنيّان عليه من أول تنفيذ فيه رسوم.
+                let old_val = base_bal.total * base_bal.avg_entry_price;
+                let new_val = qty * price;
+                let old_total = base_bal.total;
+                let new_total = base_bal.total + qty - fee; // خصم الرسوم من العملة المستلمة
+
+                if new_total > Decimal::ZERO {
+                    base_bal.avg_entry_price = (old_val + new_val) / new_total;
+                }
+
+                base_bal.total = new_total;
+                self.record(base_asset, new_total - old_total, Decimal::ZERO, "SETTLEMENT_BASE_LEG_BUY");
+                self.accrue_fee(base_asset, fee);
+            },
+
+            Side::Ask => {
+                // بيع: (Sell Base, Receive Quote)
+
+                // 1. خصم الكمية من الـ Base (المحجوزة)، وتسجيل الربح/الخسارة المحققة مقابل متوسط سعر الدخول
+                let base_bal = self.balances.get_mut(base_asset)
+                    .ok_or_else(|| AlphaError::Fatal("Base asset missing during settlement".into()))?;
+
+                base_bal.total -= qty;
+                let locked_delta = if base_bal.locked >= qty {
+                    base_bal.locked -= qty;
+                    qty
+                } else {
+                    let had = base_bal.locked;
+                    base_bal.locked = Decimal::ZERO;
+                    had
+                };
+                base_bal.realized_pnl += (price - base_bal.avg_entry_price) * qty - fee;
+                self.record(base_asset, -qty, -locked_delta, "SETTLEMENT_BASE_LEG_SELL");
+
+                // 2. إضافة التكلفة للـ Quote
+                let quote_bal = self.balances.entry(quote_asset.to_string())
+                    .or_insert_with(|| AssetBalance::new(quote_asset));
+
+                let proceeds = cost - fee; // خصم الرسوم من الـ Quote المستلمة
+                quote_bal.total += proceeds;
+                self.record(quote_asset, proceeds, Decimal::ZERO, "SETTLEMENT_QUOTE_LEG_SELL");
+                self.accrue_fee(quote_asset, fee);
+            }
+        }
+
+        info!("SETTLEMENT: Trade committed for {}/{} (Qty: {}, Price: {})", base_asset, quote_asset, qty, price);
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------
+    // الربح والخسارة (PnL) والتدقيق الجنائي
+    // ----------------------------------------------------------------
+
+    /// الربح/الخسارة غير المحققة لأصل بناءً على سعر السوق الحالي ومتوسط سعر الدخول المسجّل
+    pub fn unrealized_pnl(&self, asset: &str, mark_price: Decimal) -> AlphaResult<Decimal> {
+        let bal = self.balances.get(asset)
+            .ok_or_else(|| AlphaError::ValidationFailed(format!("Unknown asset: {}", asset)))?;
+
+        Ok((mark_price - bal.avg_entry_price) * bal.total)
+    }
+
+    /// تقرير ربح/خسارة لكل الأصول؛ `unrealized_pnl` محسوب فقط للأصول المزوّدة بسعر سوق في `mark_prices`
+    pub fn get_pnl_report(&self, mark_prices: &HashMap<String, Decimal>) -> Vec<PnlReport> {
+        self.balances.values()
+            .map(|b| PnlReport {
+                asset: b.asset_name.clone(),
+                realized_pnl: b.realized_pnl,
+                unrealized_pnl: mark_prices.get(&b.asset_name).map(|mark| (*mark - b.avg_entry_price) * b.total),
+                avg_entry_price: b.avg_entry_price,
+                position: b.total,
+            })
+            .collect()
+    }
+
+    /// يعيد بناء `total`/`locked` من دفتر الأستاذ من الصفر ويقارنها بالأرصدة الحية.
+    /// يفشل بصوت عالٍ (خطأ) عند أول تباعد، لأن هذا يعني أموالاً "شبحية" لا مكان لها.
+    pub fn verify_ledger_integrity(&self) -> AlphaResult<()> {
+        let mut reconstructed: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+        for entry in &self.journal {
+            let acc = reconstructed.entry(entry.asset.as_str()).or_insert((Decimal::ZERO, Decimal::ZERO));
+            acc.0 += entry.delta_total;
+            acc.1 += entry.delta_locked;
+        }
+
+        for (asset, balance) in &self.balances {
+            let (recon_total, recon_locked) = reconstructed.get(asset.as_str())
+                .copied()
+                .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+
+            if recon_total != balance.total || recon_locked != balance.locked {
+                let msg = format!(
+                    "LEDGER INTEGRITY VIOLATION for {}: journal reconstructs (total={}, locked={}), live balance is (total={}, locked={})",
+                    asset, recon_total, recon_locked, balance.total, balance.locked
+                );
+                warn!("{}", msg);
+                return Err(AlphaError::InternalError(msg));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// الحصول على تقرير المحفظة الكامل
+    pub fn get_portfolio_snapshot(&self) -> HashMap<String, Decimal> {
+        self.balances.iter()
+            .map(|(k, v)| (k.clone(), v.total))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    /// يُجهِّز مدير مخزون ومتداولاً حاجزاً بالكامل سيولة الـ Quote اللازمة لشراء `qty` بسعر
+    /// `price`، مطابقاً لما يفترضه `commit_trade::Side::Bid` (تكلفة محجوزة مسبقاً بدقة).
+    fn manager_with_locked_quote(quote_asset: &str, qty: Decimal, price: Decimal) -> InventoryManager {
+        let mut mgr = InventoryManager::new();
+        mgr.deposit(quote_asset, qty * price);
+        mgr.lock_funds(quote_asset, qty * price).unwrap();
+        mgr
+    }
+
+    #[test]
+    fn test_buy_with_fee_computes_avg_entry_price_over_post_fee_quantity() {
+        // شراء 1.0 BTC بسعر 100 مع رسم 0.01 BTC: يجب أن يُقسَّم متوسط سعر الدخول على
+        // الكمية الفعلية المستلمة بعد الرسم (0.99)، لا على الكمية الإجمالية قبل خصمه (1.0).
+        let mut mgr = manager_with_locked_quote("USDT", dec!(1.0), dec!(100));
+        mgr.commit_trade("BTC", "USDT", Side::Bid, dec!(1.0), dec!(100), dec!(0.01)).unwrap();
+
+        let snapshot = mgr.get_portfolio_snapshot();
+        assert_eq!(snapshot["BTC"], dec!(0.99), "fee must be deducted from the received base quantity");
+
+        let report = mgr.get_pnl_report(&HashMap::new());
+        let btc = report.iter().find(|r| r.asset == "BTC").unwrap();
+        let expected_avg = (dec!(1.0) * dec!(100)) / dec!(0.99);
+        assert_eq!(btc.avg_entry_price, expected_avg, "avg_entry_price denominator must be the post-fee quantity");
+    }
+
+    #[test]
+    fn test_buy_with_fee_then_sell_yields_correct_realized_pnl() {
+        // بدون هذا الإصلاح، avg_entry_price يكون أقل مما يجب، فيبالغ realized_pnl عند البيع
+        // لاحقاً بفارق يعادل الرسم المخصوم في ساق الشراء.
+        let mut mgr = manager_with_locked_quote("USDT", dec!(1.0), dec!(100));
+        mgr.commit_trade("BTC", "USDT", Side::Bid, dec!(1.0), dec!(100), dec!(0.01)).unwrap();
+
+        let avg_entry_price = {
+            let report = mgr.get_pnl_report(&HashMap::new());
+            report.iter().find(|r| r.asset == "BTC").unwrap().avg_entry_price
+        };
+
+        mgr.lock_funds("BTC", dec!(0.99)).unwrap();
+        mgr.commit_trade("BTC", "USDT", Side::Ask, dec!(0.99), dec!(120), dec!(0)).unwrap();
+
+        let report = mgr.get_pnl_report(&HashMap::new());
+        let btc = report.iter().find(|r| r.asset == "BTC").unwrap();
+        let expected_pnl = (dec!(120) - avg_entry_price) * dec!(0.99);
+        assert_eq!(btc.realized_pnl, expected_pnl);
+        assert_eq!(btc.position, Decimal::ZERO, "entire post-fee position was sold");
+    }
+
+    #[test]
+    fn test_verify_ledger_integrity_passes_after_fee_bearing_buy() {
+        let mut mgr = manager_with_locked_quote("USDT", dec!(1.0), dec!(100));
+        mgr.commit_trade("BTC", "USDT", Side::Bid, dec!(1.0), dec!(100), dec!(0.01)).unwrap();
+        mgr.verify_ledger_integrity().unwrap();
+    }
+}
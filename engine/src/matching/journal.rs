@@ -0,0 +1,130 @@
+// Hash-Chained Match Journal (forensic audit trail for the live matching engine)
+
+/*
+ * ALPHA SOVEREIGN - MATCH JOURNAL
+ * =================================================================
+ * Component Name: engine/src/matching/journal.rs
+ * Core Responsibility: سجل جنائي إضافي فقط (Append-Only) لكل عملية "مقبولة" على مستوى
+ *                       `MatchingEngine`: إنشاء سوق، وضع أمر، إلغاء أمر (Forensic Impact:
+ *                       تطبيقاً حرفياً لمبدأ المحرك المُعلَن - "إن لم يمر الأمر من هنا، فهو لم يحدث").
+ * Design Pattern: Hash Chain (على غرار `risk::ledger::ForensicLedger`، لكن في الذاكرة فقط
+ *                  بلا كتابة للقرص، لأن هذا مسار المطابقة الساخن ولا يحتمل زمن I/O متزامن
+ *                  لكل أمر - انظر `matching::wal` الذي يحمل مسؤولية الاستمرار عبر الانهيار
+ *                  بتسلسل رقمي بدل سلسلة هاش لنفس السبب المعاكس هناك)
+ * =================================================================
+ */
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AlphaError, AlphaResult};
+use crate::models::order::Order;
+
+/// عملية واحدة مقبولة على مستوى المحرك، مسجَّلة في `MatchJournal`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalOp {
+    CreateMarket { symbol: String },
+    PlaceOrder { order: Order },
+    CancelOrder { symbol: String, order_id: u64 },
+}
+
+/// قيد واحد في السجل الجنائي: رقم تسلسلي متصاعد وهاش يربط كل قيد بسابقه مباشرة
+/// (`entry_hash = H(prev_hash || serialized_op)`، بنفس أسلوب `risk::ledger::LedgerEntry`)،
+/// فيصبح أي تعديل أو حذف لقيد سابق قابلاً للكشف فوراً عبر `MatchJournal::verify_integrity`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub recorded_at_ms: u64,
+    pub op: JournalOp,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+/// القيمة الجذرية التي تبدأ منها كل سلسلة سجل جديدة (لا يوجد `prev_hash` قبلها).
+const JOURNAL_GENESIS_HASH: &str = "GENESIS_MATCH_JOURNAL_ALPHA_SOVEREIGN";
+
+/// فشل سلامة سلسلة الهاش: قيد لا يربط هاشه بالقيد الذي يسبقه مباشرة (أو بالجذر إن كان
+/// الأول) - يعني هذا إما عبثاً بالسجل أو ذيلاً (`tail_after`) غير مرتب/ناقص.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalIntegrityError {
+    #[error("journal entry at seq {0} does not chain from its expected predecessor hash")]
+    ChainBroken(u64),
+}
+
+impl From<JournalIntegrityError> for AlphaError {
+    fn from(err: JournalIntegrityError) -> Self {
+        AlphaError::ValidationFailed(err.to_string())
+    }
+}
+
+/// سجل جنائي إضافي فقط (Append-Only) لكل عملية مقبولة على مستوى `MatchingEngine`: إنشاء
+/// سوق، وضع أمر، إلغاء أمر. كل قيد يحمل هاشاً متسلسلاً من سابقه، وكل قيد رقمه التسلسلي
+/// فريد ومتصاعد. في الذاكرة فقط - انظر رأس الملف لسبب عدم كتابته للقرص كـ `risk::ledger`.
+#[derive(Debug, Default)]
+pub struct MatchJournal {
+    entries: Vec<JournalEntry>,
+    next_seq: u64,
+    last_hash: String,
+}
+
+impl MatchJournal {
+    pub fn new() -> Self {
+        Self { entries: Vec::new(), next_seq: 1, last_hash: JOURNAL_GENESIS_HASH.to_string() }
+    }
+
+    fn hash_of(prev_hash: &str, op: &JournalOp) -> String {
+        let serialized = serde_json::to_string(op).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.as_bytes());
+        hasher.update(serialized.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// يُسجِّل عملية مقبولة حديثاً، ويُرجِع رقمها التسلسلي الجديد.
+    pub fn append(&mut self, op: JournalOp, recorded_at_ms: u64) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        let prev_hash = self.last_hash.clone();
+        let entry_hash = Self::hash_of(&prev_hash, &op);
+
+        self.last_hash = entry_hash.clone();
+        self.entries.push(JournalEntry { seq, recorded_at_ms, op, prev_hash, entry_hash });
+        seq
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&JournalEntry> {
+        self.entries.iter().find(|e| e.seq == seq)
+    }
+
+    /// آخر رقم تسلسلي مُسجَّل، أو 0 إن كان السجل فارغاً بعد.
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// كل القيود التي تتبع `after_seq` (حصرياً) بترتيب تسلسلي - ذيل جاهز للتدقيق أو إعادة
+    /// التشغيل عبر `MatchingEngine::replay`.
+    pub fn tail_after(&self, after_seq: u64) -> Vec<JournalEntry> {
+        self.entries.iter().filter(|e| e.seq > after_seq).cloned().collect()
+    }
+
+    /// يتحقق من سلامة كامل السلسلة المُحتفَظ بها في هذه العملية: كل قيد يجب أن يربط هاشه
+    /// بهاش سابقه مباشرة، بدءاً من `JOURNAL_GENESIS_HASH`.
+    pub fn verify_integrity(&self) -> Result<(), JournalIntegrityError> {
+        Self::verify_entries(&self.entries)
+    }
+
+    /// نفس التحقق، لكن على أي تسلسل قيود مُستقل (مثلاً ذيل وصل عبر الشبكة من عملية أخرى)
+    /// بدلاً من سجل هذه العملية نفسها.
+    pub fn verify_entries(entries: &[JournalEntry]) -> Result<(), JournalIntegrityError> {
+        let mut expected_prev = JOURNAL_GENESIS_HASH.to_string();
+        for entry in entries {
+            let recomputed = Self::hash_of(&expected_prev, &entry.op);
+            if entry.prev_hash != expected_prev || recomputed != entry.entry_hash {
+                return Err(JournalIntegrityError::ChainBroken(entry.seq));
+            }
+            expected_prev = entry.entry_hash.clone();
+        }
+        Ok(())
+    }
+}
@@ -16,6 +16,10 @@ use std::fmt;
 // سنقوم بكتابة هذه الملفات (orderbook.rs, engine.rs) لاحقاً
 pub mod orderbook;
 pub mod engine;
+pub mod inventory_mgr;
+pub mod fee_schedule;
+pub mod wal;
+pub mod journal;
 
 // =================================================================
 // أنواع البيانات الأساسية (Fundamental Data Types)
@@ -42,11 +46,20 @@ impl Side {
 /// نوع الأمر (كيفية التنفيذ).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderType {
-    Limit,          // نفذ بسعر محدد أو أفضل
-    Market,         // نفذ فوراً بأي سعر متاح
-    ImmediateOrCancel, // نفذ ما تستطيع فوراً وألغ الباقي (IOC)
-    FillOrKill,     // نفذ الكل فوراً أو ألغ الكل (FOK)
-    PostOnly,       // لا تأخذ سيولة أبداً (كن صانع سوق فقط)
+    Limit,      // نفذ بسعر محدد أو أفضل
+    Market,     // نفذ فوراً بأي سعر متاح، بتجاوز شرط السعر الحدّي
+    Stop,       // أمر سوق كامن يتفعل (يتحول لـ Market) عند عبور last_price لـ stop_price
+    StopLimit,  // أمر محدد كامن يتفعل (يتحول لـ Limit) عند عبور last_price لـ stop_price
+}
+
+/// مدة صلاحية الأمر (Time In Force) - منفصلة عن `OrderType` لأنها تصف *متى ينسحب*
+/// الأمر، لا *كيف ينفذ*؛ أمر Limit واحد قد يكون GTC أو IOC أو FOK بلا تغيير في نوعه.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    GTC, // Good Till Cancel: يبقى في الدفتر حتى يُلغى أو يمتلئ
+    IOC, // Immediate Or Cancel: نفذ ما تستطيع فوراً وألغ الباقي دون أن يرتاح في الدفتر
+    FOK, // Fill Or Kill: يُرفض بالكامل إن تعذّر تنفيذه بالكامل فوراً
+    GTT, // Good Till Time: يبقى حتى `expires_at_ms`، ثم يُسقَط عبر اجتياح انتهاء الصلاحية
 }
 
 /// الهيكل الرئيسي للأمر (The Atom of the Engine).
@@ -61,16 +74,24 @@ pub struct Order {
     
     pub side: Side,
     pub order_type: OrderType,
-    
-    /// السعر (Decimal لضمان الدقة المالية)
+    pub time_in_force: TimeInForce,
+
+    /// السعر (Decimal لضمان الدقة المالية). يُتجاهَل شرط عبوره للدفتر بالنسبة لأوامر
+    /// `Market`، لكنه يبقى موجوداً ليُستخدم كحد أقصى/أدنى انزلاق سعري لاحقاً إن لزم.
     pub price: Decimal,
-    
+
     /// الكمية المطلوبة
     pub quantity: Decimal,
-    
+
+    /// سعر التفعيل لأوامر `Stop`/`StopLimit` فقط؛ يُفحص عند كل صفقة جديدة في السوق.
+    pub stop_price: Option<Decimal>,
+
+    /// وقت انتهاء الصلاحية (Unix Milliseconds) لأوامر `TimeInForce::GTT` فقط.
+    pub expires_at_ms: Option<u64>,
+
     /// وقت الإنشاء (Unix Nanoseconds) - للأولوية الزمنية والتحقيق الجنائي
     pub timestamp: u64,
-    
+
     /// المصدر (Strategy ID / User ID)
     pub owner_id: String,
 }
@@ -80,6 +101,20 @@ impl Order {
     pub fn validate(&self) -> bool {
         self.quantity > Decimal::ZERO && self.price >= Decimal::ZERO
     }
+
+    /// هل هذا أمر كامن (Stop/StopLimit) لم يتفعّل بعد؟
+    pub fn is_pending_trigger(&self) -> bool {
+        matches!(self.order_type, OrderType::Stop | OrderType::StopLimit)
+    }
+
+    /// هل تفعّل أمر كامن عند هذا السعر الأخير في السوق؟
+    pub fn should_trigger(&self, last_price: Decimal) -> bool {
+        let Some(stop_price) = self.stop_price else { return false; };
+        match self.side {
+            Side::Bid => last_price >= stop_price,
+            Side::Ask => last_price <= stop_price,
+        }
+    }
 }
 
 // =================================================================
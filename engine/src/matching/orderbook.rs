@@ -0,0 +1,1244 @@
+// L3 Order Book (Price-Time Priority)
+
+/*
+ * ALPHA SOVEREIGN - L3 ORDER BOOK (PRODUCTION MATCHING CORE)
+ * =================================================================
+ * Component Name: engine/src/matching/orderbook.rs
+ * Core Responsibility: مطابقة الأوامر بدقة "السعر-الزمن" على نموذج الأمر الموحد (Performance Pillar).
+ * Design Pattern: BTreeMap price levels + FIFO queue + O(1) cancel index
+ * Forensic Impact: نقطة الحقيقة الوحيدة لحالة السوق. أي خلل هنا يعني تنفيذاً خاطئاً أو سرقة سبريد.
+ * =================================================================
+ */
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use rust_decimal::Decimal;
+use tracing::warn;
+use crate::error::{AlphaError, AlphaResult};
+use crate::models::order::{Order, OrderSide, OrderType, OrderStatus, SelfTradePolicy, TimeInForce};
+use super::fee_schedule::FeeSchedule;
+
+/// صفقة منفذة بين آخذ (Taker) وصانع (Maker)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub taker_side: OrderSide,
+    pub executed_at: u64,
+    /// رسوم الصانع المستحقة على هذه الصفقة (قد تكون سالبة = خصم/Rebate)
+    pub maker_fee: Decimal,
+    /// رسوم الآخذ المستحقة على هذه الصفقة
+    pub taker_fee: Decimal,
+}
+
+/// حدث منع تداول مع النفس: كمية أُلغيت أو خُفضت دون تنفيذ صفقة عليها.
+/// تُستخدمه الطبقة الأعلى (settlement) لفك حجز الأموال عبر `InventoryManager::unlock_funds`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StpEvent {
+    pub resting_order_id: u64,
+    pub incoming_order_id: u64,
+    pub cancelled_qty: Decimal,
+    pub policy: SelfTradePolicy,
+}
+
+/// نتيجة وضع أمر `SendTake` (مطابقة فورية على غرار `process_send_take` في OpenBook):
+/// يعبر الدفتر مباشرة، يحسب رسوم الآخذ ضمن مرور المطابقة، ولا يترك أي بقية مقيمة.
+#[derive(Debug, Clone)]
+pub struct SendTakeResult {
+    pub trades: Vec<Trade>,
+    /// صافي العائد بعد خصم رسوم الآخذ (للبائع: العائد النقدي، للمشتري: التكلفة الصافية سالبة الإشارة منطقياً عبر الاستدعاء)
+    pub net_proceeds: Decimal,
+}
+
+/// لقطة للدفتر لأغراض العرض والاختبار، مرتبة حسب أولوية السعر-الزمن. `Serialize`/`Deserialize`
+/// مطلوبان لأن `wal::OrderBookWal` يكتب هذه اللقطة دورياً إلى السجل الدائم (انظر `wal.rs`).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BookSnapshot {
+    /// من الأفضل سعراً (الأعلى) إلى الأدنى
+    pub bids: Vec<Order>,
+    /// من الأفضل سعراً (الأدنى) إلى الأعلى
+    pub asks: Vec<Order>,
+}
+
+/// نتيجة تطبيق سياسة STP على تداخل واحد بين الآخذ والمُقيم
+enum StpOutcome {
+    /// تابع المطابقة على نفس مستوى السعر (قد تبقى سيولة أخرى غير ذاتية فيه)
+    ContinueLevel,
+    /// تخطَّ هذا المستوى بالكامل وانتقل للمستوى التالي
+    SkipLevel,
+    /// الأمر الوارد استُنفد بالكامل (تخفيضاً أو تنفيذاً)، أوقف المطابقة
+    TakerExhausted,
+}
+
+/// موقع الأمر داخل الدفتر (للإلغاء بسرعة O(1))
+struct OrderLocation {
+    price: Decimal,
+    side: OrderSide,
+}
+
+/// حالة مطابقة معلّقة (Optimistic Match): قرار دفتر داخلي (من طابق مَن وبأي سعر) صدر
+/// بالفعل وظهر ضمن `Vec<Trade>` المُعاد من `process_order`، لكنه لم يُسوَّ بعد عبر طبقة
+/// التنفيذ/التسوية الخارجية.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchState {
+    /// وُلّدت للتو؛ الكمية محجوزة من الصانع لكن لم تُفقد نهائياً من الدفتر بعد
+    Pending,
+    /// أُكِّدت التسوية الخارجية؛ الصفقة نهائية الآن ولا رجوع عنها
+    Executed,
+    /// فشلت التسوية الخارجية أو لم تصل أبداً؛ استُرجعت الكمية المحجوزة لصاحب الصانع
+    Failed,
+}
+
+/// مطابقة واحدة بين آخذ وصانع، فصلناها عمداً عن `Trade` النهائية: تفصل قرار الدفتر عن لحظة
+/// التسوية الفعلية، بما يسمح للمحرك بإصدار مطابقات متفائلة للتسوية قبل أن تؤكَّد، مع ضمان
+/// إمكانية التراجع عنها بدقة إن لم يصل تأكيد التنفيذ أبداً (انظر `rollback_match`).
+#[derive(Debug, Clone)]
+pub struct ExecutableMatch {
+    pub match_id: u64,
+    pub taker_order_id: u64,
+    pub maker_order_id: u64,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub matched_at: u64,
+    pub state: MatchState,
+}
+
+/// ما يلزم لاسترجاع أمر صانع (Maker) إلى حالته قبل مطابقة معلّقة واحدة تحديداً. نحتفظ بلقطة
+/// الصانع *كاملةً* كما كانت قبل تطبيق هذا الملء تحديداً (`executed_qty`/`avg_fill_price`/
+/// `status`)، لا فقط الكمية، لأن هذا الدفتر يراكم متوسط سعر تنفيذ مرجَّحاً لا يصح حسابه
+/// تجميعياً. ملاحظة: إن شارك نفس الصانع في عدة مطابقات معلّقة مستقلة ضمن نداء واحد، فالتراجع
+/// عنها بترتيب غير عكسي زمنياً قد لا يُعيد متوسط السعر بدقة.
+struct ReservedMaker {
+    price_level: Decimal,
+    side: OrderSide,
+    snapshot_before_match: Order,
+}
+
+pub struct OrderBook {
+    pub symbol: String,
+
+    // المشترون: نريد الأعلى سعراً أولاً، لذا نكرر بعكس ترتيب BTreeMap الصاعد
+    bids: BTreeMap<Decimal, VecDeque<Order>>,
+    // البائعون: نريد الأرخص سعراً أولاً، وهو الترتيب الطبيعي لـ BTreeMap
+    asks: BTreeMap<Decimal, VecDeque<Order>>,
+
+    order_index: HashMap<u64, OrderLocation>,
+
+    /// السياسة الافتراضية لمنع التداول مع النفس لهذا الدفتر (قابلة للتجاوز لكل أمر)
+    stp_policy: SelfTradePolicy,
+
+    /// أحداث STP الناتجة عن آخر استدعاء لـ `add_order` فقط (تُمسح في بداية كل نداء)
+    last_stp_events: Vec<StpEvent>,
+
+    /// جدول رسوم الصانع/الآخذ لهذا الدفتر
+    fee_schedule: FeeSchedule,
+
+    /// الحجم الاسمي التراكمي (Rolling Notional) لكل `strategy_id`، يُستخدم لتحديد مستوى الرسوم
+    volume_tracker: HashMap<String, Decimal>,
+
+    /// الأوامر المحتجزة ضمن نافذة تجميع المزاد (Batch Auction)، بانتظار `run_auction()`.
+    /// قناة منفصلة تماماً عن الدفتر المستمر (`bids`/`asks`)؛ الأوامر هنا لا تُطابَق فوراً.
+    batch_queue: Vec<Order>,
+
+    /// مطابقات معلّقة بانتظار تأكيد التسوية الخارجية، مفتاحة بـ `match_id` لا بـ `order_id`
+    /// لأن أمراً آخذاً واحداً قد يولّد عدة مطابقات معلّقة مستقلة ضد عدة صانعين في نداء واحد.
+    pending_matches: HashMap<u64, ExecutableMatch>,
+
+    /// ما يلزم لاسترجاع كل مطابقة معلّقة، مفتاحة بنفس `match_id`.
+    reserved_makers: HashMap<u64, ReservedMaker>,
+
+    /// عدّاد تسلسلي لتوليد معرفات المطابقات المعلّقة.
+    next_match_id: u64,
+}
+
+impl OrderBook {
+    pub fn new(symbol: String) -> Self {
+        Self {
+            symbol,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            order_index: HashMap::new(),
+            stp_policy: SelfTradePolicy::CancelNewest,
+            last_stp_events: Vec::new(),
+            fee_schedule: FeeSchedule::default(),
+            volume_tracker: HashMap::new(),
+            batch_queue: Vec::new(),
+            pending_matches: HashMap::new(),
+            reserved_makers: HashMap::new(),
+            next_match_id: 1,
+        }
+    }
+
+    /// إنشاء دفتر بسياسة STP مخصصة (بدلاً من الافتراضي CancelNewest)
+    pub fn with_stp_policy(mut self, policy: SelfTradePolicy) -> Self {
+        self.stp_policy = policy;
+        self
+    }
+
+    /// إنشاء دفتر بجدول رسوم مخصص (بدلاً من الافتراضي في `FeeSchedule::default`)
+    pub fn with_fee_schedule(mut self, schedule: FeeSchedule) -> Self {
+        self.fee_schedule = schedule;
+        self
+    }
+
+    /// الحجم الاسمي التراكمي المسجَّل حتى الآن لاستراتيجية معيّنة (لتحديد مستوى رسومها)
+    fn rolling_volume(&self, strategy_id: &str) -> Decimal {
+        self.volume_tracker.get(strategy_id).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    /// أحداث منع التداول مع النفس الناتجة عن آخر أمر تمت معالجته
+    pub fn last_stp_events(&self) -> &[StpEvent] {
+        &self.last_stp_events
+    }
+
+    /// المحرك الرئيسي: استقبال أمر جديد ومحاولة مطابقته. كل مطابقة ضد صانع تُسجَّل كـ
+    /// `ExecutableMatch` معلّقة (انظر `commit_match`/`rollback_match`) بالإضافة إلى ظهورها
+    /// ضمن `Vec<Trade>` المُعاد كقرار دفتر فوري. `OrderStatus` الثالثة هي نتيجة معالجة
+    /// شروط الصلاحية (IOC/FOK/PostOnly/GTD) بدقة - `Filled` إن لم تبقَ كمية، `New`/`PartiallyFilled`
+    /// إن ارتاحت بقية الكمية، أو `Canceled` إن أُسقطت بصمت (IOC/FOK لم تُطابَق بالكامل، أو Market بلا سيولة
+    /// كافية) - كي يستطيع المستدعي (والسجل الجنائي WAL) تسجيل مصير الأمر بدقة دون تخمينه من
+    /// مجرد عدد الصفقات.
+    pub fn process_order(&mut self, mut order: Order) -> AlphaResult<(Vec<Trade>, Vec<ExecutableMatch>, OrderStatus)> {
+        if order.original_qty <= Decimal::ZERO {
+            return Err(AlphaError::ValidationFailed("Order quantity must be positive".into()));
+        }
+
+        // PostOnly: نرفض فوراً دون أي تعديل على الدفتر إن كان الأمر سيعبر السوق.
+        if order.time_in_force == TimeInForce::PostOnly && self.would_cross(&order) {
+            return Err(AlphaError::ExchangeRejection(format!(
+                "PostOnly order {} would cross the book immediately", order.id
+            )));
+        }
+
+        // FOK: نتحقق مسبقاً (دون أي تعديل) أن كامل الكمية قابلة للتنفيذ فوراً، وإلا نرفض الأمر بالكامل.
+        if order.time_in_force == TimeInForce::FOK {
+            let fillable = self.fillable_quantity(&order);
+            if fillable < order.original_qty {
+                return Err(AlphaError::ExchangeRejection(format!(
+                    "FOK order {} cannot be fully filled ({} of {} available)",
+                    order.id, fillable, order.original_qty
+                )));
+            }
+        }
+
+        self.last_stp_events.clear();
+
+        let mut trades = Vec::new();
+        let mut pending = Vec::new();
+        match order.side {
+            OrderSide::Buy => self.match_incoming(&mut order, &mut trades, &mut pending, Self::crosses_ask),
+            OrderSide::Sell => self.match_incoming(&mut order, &mut trades, &mut pending, Self::crosses_bid),
+        }
+
+        let remaining = order.original_qty - order.executed_qty;
+
+        // فقط أوامر Limit (غير IOC/FOK) ترتاح في الدفتر كصانع سيولة.
+        // IOC وFOK لا يرتاحان أبداً: أي بقية تُسقط بصمت بدلاً من الارتياح.
+        let may_rest = order.order_type == OrderType::Limit
+            && !matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK);
+
+        let status = if remaining > Decimal::ZERO && may_rest {
+            let status = if trades.is_empty() { OrderStatus::New } else { OrderStatus::PartiallyFilled };
+            order.status = status;
+            self.insert_resting(order);
+            status
+        } else if remaining > Decimal::ZERO {
+            // بقية لم ترتح (IOC/FOK أُسقطت، أو Market استنفد السيولة المتاحة): أُلغيت بصمت.
+            OrderStatus::Canceled
+        } else {
+            OrderStatus::Filled
+        };
+
+        Ok((trades, pending, status))
+    }
+
+    /// الغلاف المتوافق خلفياً: ينفّذ `process_order` ثم يُثبِّت كل مطابقاتها المعلّقة فوراً
+    /// (نفس سلوك هذه الدالة قبل فصل مرحلتي القرار والتسوية). يناسب المستدعين الذين لا
+    /// يحتاجون التحكم اليدوي بالتأكيد/التراجع، كـ `send_take` واختبارات هذا الملف.
+    pub fn add_order(&mut self, order: Order) -> AlphaResult<Vec<Trade>> {
+        let (trades, pending, _status) = self.process_order(order)?;
+        for exec in pending {
+            self.commit_match(exec.match_id)?;
+        }
+        Ok(trades)
+    }
+
+    /// يكتسح كلا الجانبين بحثاً عن أوامر `TimeInForce::GTD` مُقيمة تجاوزت `expire_at`، ينقل
+    /// كل واحد منها إلى `OrderStatus::Expired` عبر `Order::check_expiry`، يزيله من طابوره
+    /// وفهرسه (`order_index`)، وينظّف أي مستوى سعري أصبح فارغاً بعد الإزالة. يعيد نسخة من
+    /// كل أمر انتهت صلاحيته تواً، كي يُصاغ لحدث `Expired` من طرف المستدعي.
+    pub fn sweep_expired(&mut self, now_ms: u64) -> Vec<Order> {
+        let mut expired = Vec::new();
+
+        for book in [&mut self.bids, &mut self.asks] {
+            book.retain(|_, queue| {
+                queue.retain_mut(|order| {
+                    if order.check_expiry(now_ms) {
+                        expired.push(order.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+                !queue.is_empty()
+            });
+        }
+
+        for order in &expired {
+            self.order_index.remove(&order.id);
+        }
+
+        expired
+    }
+
+    /// يُثبِّت مطابقة معلّقة نهائياً: تتحول حالتها إلى `MatchState::Executed` والكمية
+    /// المحجوزة من الصانع تُعتبر مفقودة نهائياً (لم تعد قابلة للاسترجاع).
+    pub fn commit_match(&mut self, match_id: u64) -> AlphaResult<ExecutableMatch> {
+        self.reserved_makers.remove(&match_id);
+        let mut exec = self.pending_matches.remove(&match_id).ok_or_else(|| {
+            AlphaError::ValidationFailed(format!("No pending match with id {}", match_id))
+        })?;
+        exec.state = MatchState::Executed;
+        Ok(exec)
+    }
+
+    /// يسترجع مطابقة معلّقة لم تصل تأكيد تسويتها الخارجية أبداً: يعيد الصانع إلى حالته قبل
+    /// هذه المطابقة تحديداً (إما بتحديث نسخته في الطابور، أو بإعادة إدراج لقطته كاملةً في
+    /// مقدمة طابوره الأصلي إن كان قد أُزيل منه بالكامل لامتلائه وقت المطابقة)، ثم يضع حالتها
+    /// على `MatchState::Failed`. لا يملك هذا الدفتر طريقة لاسترجاع الأمر الآخذ نفسه إن كان قد
+    /// امتلأ بالكامل وغادر الدفتر في نفس الاستدعاء؛ هذا يبقى مسؤولية طبقة التسوية التي تحتفظ
+    /// بالأمر الأصلي (راجع `Vec<Trade>` المُعاد من `process_order`).
+    pub fn rollback_match(&mut self, match_id: u64) -> AlphaResult<ExecutableMatch> {
+        let reserved = self.reserved_makers.remove(&match_id).ok_or_else(|| {
+            AlphaError::ValidationFailed(format!("No reserved maker for match {}", match_id))
+        })?;
+        let mut exec = self.pending_matches.remove(&match_id).ok_or_else(|| {
+            AlphaError::ValidationFailed(format!("No pending match with id {}", match_id))
+        })?;
+
+        self.restore_maker(reserved);
+        exec.state = MatchState::Failed;
+        Ok(exec)
+    }
+
+    /// يعيد أمراً صانعاً إلى حالته قبل مطابقة معلّقة واحدة تحديداً، من لقطته المحفوظة.
+    fn restore_maker(&mut self, reserved: ReservedMaker) {
+        let queue = self.side_mut(reserved.side)
+            .entry(reserved.price_level)
+            .or_insert_with(VecDeque::new);
+
+        if let Some(existing) = queue.iter_mut().find(|o| o.id == reserved.snapshot_before_match.id) {
+            existing.executed_qty = reserved.snapshot_before_match.executed_qty;
+            existing.avg_fill_price = reserved.snapshot_before_match.avg_fill_price;
+            existing.status = reserved.snapshot_before_match.status;
+        } else {
+            self.order_index.insert(reserved.snapshot_before_match.id, OrderLocation {
+                price: reserved.price_level,
+                side: reserved.side,
+            });
+            queue.push_front(reserved.snapshot_before_match);
+        }
+    }
+
+    /// وضع أمر "SendTake" على غرار `process_send_take` في OpenBook: يعبر الدفتر فوراً
+    /// (يُفرض عليه IOC ضمنياً بغض النظر عن القيمة الممررة)، يحسب رسوم الآخذ ضمن مرور
+    /// المطابقة نفسه، ويعيد الصفقات مع صافي العائد دون أي بقية مقيمة أو قفل معلّق.
+    pub fn send_take(&mut self, mut order: Order, taker_fee_bps: Decimal) -> AlphaResult<SendTakeResult> {
+        order.time_in_force = TimeInForce::IOC;
+        let trades = self.add_order(order)?;
+
+        let gross_notional: Decimal = trades.iter().map(|t| t.price * t.quantity).sum();
+        let fee = gross_notional * taker_fee_bps / Decimal::from(10_000);
+
+        Ok(SendTakeResult { trades, net_proceeds: gross_notional - fee })
+    }
+
+    // ----------------------------------------------------------------
+    // وضع المزاد الدُفعي (Batch Auction / Uniform Clearing Price)
+    // على غرار مزادات CoW Protocol: الأوامر تُحتجز دون مطابقة فورية خلال نافذة
+    // التجميع، ثم تُنفَّذ كلها دفعة واحدة بسعر تصفية موحّد عند `run_auction()`.
+    // ----------------------------------------------------------------
+
+    /// يضيف أمراً لنافذة تجميع المزاد بدلاً من مطابقته فوراً. وضع اختياري (Opt-in)
+    /// مستقل تماماً عن `add_order`/المطابقة المستمرة.
+    pub fn submit_to_auction(&mut self, order: Order) -> AlphaResult<()> {
+        if order.original_qty <= Decimal::ZERO {
+            return Err(AlphaError::ValidationFailed("Order quantity must be positive".into()));
+        }
+        self.batch_queue.push(order);
+        Ok(())
+    }
+
+    /// عدد الأوامر المنتظرة حالياً في نافذة تجميع المزاد
+    pub fn pending_auction_count(&self) -> usize {
+        self.batch_queue.len()
+    }
+
+    /// ينفّذ المزاد على كل الأوامر المحتجزة حالياً: يحسب سعر التصفية الموحّد الذي يعظّم
+    /// الحجم المُنفَّذ عبر تقاطع منحنيي الطلب والعرض التراكميين، ثم ينفّذ كل الأوامر العابرة
+    /// بهذا السعر الواحد، موزّعاً المستوى الحدّي تناسبياً (Pro-Rata) إن لم تتوازن الكميات تماماً.
+    /// البقية غير المنفَّذة ترتاح في الدفتر المستمر (أوامر Limit/GTC) أو تُلغى (IOC/FOK) بنفس
+    /// منطق `add_order`. التسوية عبر `InventoryManager` تتم خارجياً كالمعتاد من أحداث `Trade` الناتجة.
+    pub fn run_auction(&mut self) -> AlphaResult<Vec<Trade>> {
+        let batch = std::mem::take(&mut self.batch_queue);
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (mut bids, mut asks): (Vec<Order>, Vec<Order>) =
+            batch.into_iter().partition(|o| o.side == OrderSide::Buy);
+
+        // ترتيب حسب أولوية السعر ثم الزمن. أوامر السوق (بلا سعر) أكثر عدوانية من أي سعر محدد.
+        bids.sort_by(Self::auction_priority(true));
+        asks.sort_by(Self::auction_priority(false));
+
+        let clearing_price = Self::find_clearing_price(&bids, &asks);
+
+        let mut trades = Vec::new();
+        if let Some(price) = clearing_price {
+            let matched = Self::allocate_auction_quantities(&mut bids, &mut asks, price);
+            if matched > Decimal::ZERO {
+                trades = Self::build_auction_trades(&mut bids, &mut asks, price);
+            }
+        }
+
+        for order in bids.into_iter().chain(asks.into_iter()) {
+            self.settle_auction_residual(order);
+        }
+
+        Ok(trades)
+    }
+
+    /// ترتيب أولوية جانب واحد من أوامر المزاد: أفضل سعر أولاً (الأعلى للشراء، الأدنى للبيع)،
+    /// ثم الأقدم زمنياً عند تساوي السعر. أوامر السوق (None) تُعامَل كالأعدوانية دوماً.
+    fn auction_priority(is_bid: bool) -> impl Fn(&Order, &Order) -> std::cmp::Ordering {
+        move |a: &Order, b: &Order| match (a.price, b.price) {
+            (None, None) => a.timestamp.cmp(&b.timestamp),
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (Some(pa), Some(pb)) => {
+                let price_order = if is_bid { pb.cmp(&pa) } else { pa.cmp(&pb) };
+                price_order.then(a.timestamp.cmp(&b.timestamp))
+            }
+        }
+    }
+
+    /// يحسب سعر التصفية الذي يعظّم الحجم المُنفَّذ عبر تقاطع منحنيي الطلب والعرض التراكميين.
+    /// يعيد `None` إن لم يتقاطع العرض والطلب على الإطلاق (لا تنفيذ في هذه الدفعة).
+    fn find_clearing_price(bids: &[Order], asks: &[Order]) -> Option<Decimal> {
+        let mut candidates: Vec<Decimal> = bids.iter().chain(asks.iter())
+            .filter_map(|o| o.price)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let demand_at = |p: Decimal| -> Decimal {
+            bids.iter()
+                .filter(|b| b.price.map_or(true, |bp| bp >= p))
+                .map(|b| b.original_qty)
+                .sum()
+        };
+        let supply_at = |p: Decimal| -> Decimal {
+            asks.iter()
+                .filter(|a| a.price.map_or(true, |ap| ap <= p))
+                .map(|a| a.original_qty)
+                .sum()
+        };
+
+        let mut best: Option<(Decimal, Decimal, Decimal)> = None; // (السعر، الحجم، عدم التوازن)
+        for p in candidates {
+            let d = demand_at(p);
+            let s = supply_at(p);
+            let volume = d.min(s);
+            if volume <= Decimal::ZERO {
+                continue;
+            }
+            let imbalance = (d - s).abs();
+            let is_better = match best {
+                None => true,
+                Some((_, best_volume, best_imbalance)) => {
+                    volume > best_volume || (volume == best_volume && imbalance < best_imbalance)
+                }
+            };
+            if is_better {
+                best = Some((p, volume, imbalance));
+            }
+        }
+        best.map(|(p, _, _)| p)
+    }
+
+    /// يحدد كمية التنفيذ النهائية لكل أمر في الدفعة عند سعر التصفية (عبر `Order::update_execution`):
+    /// الأوامر الأفضل من السعر الحدّي تُنفَّذ بالكامل، وأوامر المستوى الحدّي (المساوي لسعر التصفية)
+    /// تُوزَّع تناسبياً (Pro-Rata) بما يكفي فقط لتغطية الحجم المتبقي. يعيد إجمالي الحجم المُنفَّذ.
+    fn allocate_auction_quantities(bids: &mut [Order], asks: &mut [Order], clearing_price: Decimal) -> Decimal {
+        let demand_total: Decimal = bids.iter()
+            .filter(|b| b.price.map_or(true, |p| p >= clearing_price))
+            .map(|b| b.original_qty)
+            .sum();
+        let supply_total: Decimal = asks.iter()
+            .filter(|a| a.price.map_or(true, |p| p <= clearing_price))
+            .map(|a| a.original_qty)
+            .sum();
+        let matched = demand_total.min(supply_total);
+
+        Self::allocate_side(bids, clearing_price, matched, true);
+        Self::allocate_side(asks, clearing_price, matched, false);
+
+        matched
+    }
+
+    /// يوزّع `matched` وحدة على جانب واحد من المزاد ضمن `allocate_auction_quantities`
+    fn allocate_side(orders: &mut [Order], clearing_price: Decimal, matched: Decimal, is_bid: bool) {
+        let is_strict = |o: &Order| match o.price {
+            None => true,
+            Some(p) => if is_bid { p > clearing_price } else { p < clearing_price },
+        };
+
+        let strict_total: Decimal = orders.iter().filter(|o| is_strict(o)).map(|o| o.original_qty).sum();
+        let marginal_total: Decimal = orders.iter()
+            .filter(|o| o.price == Some(clearing_price))
+            .map(|o| o.original_qty)
+            .sum();
+
+        let needed_from_marginal = (matched - strict_total).max(Decimal::ZERO);
+        let pro_rata_ratio = if marginal_total > Decimal::ZERO {
+            (needed_from_marginal / marginal_total).min(Decimal::ONE)
+        } else {
+            Decimal::ZERO
+        };
+
+        for order in orders.iter_mut() {
+            let exec_qty = if is_strict(order) {
+                order.original_qty
+            } else if order.price == Some(clearing_price) {
+                order.original_qty * pro_rata_ratio
+            } else {
+                Decimal::ZERO
+            };
+            if exec_qty > Decimal::ZERO {
+                order.update_execution(exec_qty, clearing_price);
+            }
+        }
+    }
+
+    /// يولّد أحداث `Trade` بمزج طرفي المزاد حسب الأولوية عند الكميات المُنفَّذة المحسوبة مسبقاً.
+    /// بما أن كل أوامر الدفعة متزامنة منطقياً، نعتبر اصطلاحاً البائع صانعاً والمشتري آخذاً لهذا
+    /// الحقل فقط، دون أي دلالة زمنية حقيقية (لا يوجد "أول" في مزاد دُفعي).
+    fn build_auction_trades(bids: &mut [Order], asks: &mut [Order], clearing_price: Decimal) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let mut bid_idx = 0;
+        let mut ask_idx = 0;
+        let mut bid_remaining = bids.first().map(|o| o.executed_qty).unwrap_or(Decimal::ZERO);
+        let mut ask_remaining = asks.first().map(|o| o.executed_qty).unwrap_or(Decimal::ZERO);
+
+        while bid_idx < bids.len() && ask_idx < asks.len() {
+            if bid_remaining <= Decimal::ZERO {
+                bid_idx += 1;
+                bid_remaining = bids.get(bid_idx).map(|o| o.executed_qty).unwrap_or(Decimal::ZERO);
+                continue;
+            }
+            if ask_remaining <= Decimal::ZERO {
+                ask_idx += 1;
+                ask_remaining = asks.get(ask_idx).map(|o| o.executed_qty).unwrap_or(Decimal::ZERO);
+                continue;
+            }
+
+            let matched_qty = bid_remaining.min(ask_remaining);
+            trades.push(Trade {
+                maker_order_id: asks[ask_idx].id,
+                taker_order_id: bids[bid_idx].id,
+                price: clearing_price,
+                quantity: matched_qty,
+                taker_side: OrderSide::Buy,
+                executed_at: chrono::Utc::now().timestamp_nanos() as u64,
+                maker_fee: Decimal::ZERO,
+                taker_fee: Decimal::ZERO,
+            });
+
+            bid_remaining -= matched_qty;
+            ask_remaining -= matched_qty;
+        }
+
+        trades
+    }
+
+    /// يعيد البقية غير المُنفَّذة من أمر مزاد دُفعي للدفتر المستمر (Limit/GTC)، أو يُسقطها (IOC/FOK/Market)،
+    /// بنفس منطق `add_order` بالضبط بعد نهاية المطابقة.
+    fn settle_auction_residual(&mut self, order: Order) {
+        let remaining = order.original_qty - order.executed_qty;
+        let may_rest = order.order_type == OrderType::Limit
+            && !matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK);
+
+        if remaining > Decimal::ZERO && may_rest {
+            self.insert_resting(order);
+        }
+    }
+
+    /// إلغاء أمر موجود في الدفتر
+    pub fn cancel_order(&mut self, order_id: u64) -> AlphaResult<bool> {
+        let loc = self.order_index.remove(&order_id)
+            .ok_or_else(|| AlphaError::ValidationFailed(format!("Order {} not found in book", order_id)))?;
+
+        let book_side = self.side_mut(loc.side);
+        if let Some(queue) = book_side.get_mut(&loc.price) {
+            if let Some(idx) = queue.iter().position(|o| o.id == order_id) {
+                queue.remove(idx);
+                if queue.is_empty() {
+                    book_side.remove(&loc.price);
+                }
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// لقطة كاملة للدفتر، بترتيب أولوية السعر-الزمن
+    pub fn get_snapshot(&self) -> BookSnapshot {
+        BookSnapshot {
+            bids: self.bids.iter().rev().flat_map(|(_, q)| q.iter().cloned()).collect(),
+            asks: self.asks.iter().flat_map(|(_, q)| q.iter().cloned()).collect(),
+        }
+    }
+
+    /// يعيد بناء الدفتر من لقطة مسبقة (`wal::OrderBookWal::recover`) بإدراج كل أمر كما هو
+    /// مباشرة في مستوى سعره دون إعادة مطابقته - اللقطة بحكم تعريفها لا تحوي أوامر متقاطعة.
+    /// يجب استدعاؤها على دفتر فارغ فقط (فور `OrderBook::new`).
+    pub(crate) fn restore_from_snapshot(&mut self, snapshot: BookSnapshot) {
+        for order in snapshot.bids.into_iter().chain(snapshot.asks) {
+            self.insert_resting(order);
+        }
+    }
+
+    // ----------------------------------------------------------------
+    // المنطق الداخلي للمطابقة (Matching Logic)
+    // ----------------------------------------------------------------
+
+    fn side_mut(&mut self, side: OrderSide) -> &mut BTreeMap<Decimal, VecDeque<Order>> {
+        match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        }
+    }
+
+    fn crosses_ask(order: &Order, ask_price: Decimal) -> bool {
+        match order.price {
+            Some(limit) => ask_price <= limit,
+            None => true, // أمر سوق: يقبل أي سعر
+        }
+    }
+
+    fn crosses_bid(order: &Order, bid_price: Decimal) -> bool {
+        match order.price {
+            Some(limit) => bid_price >= limit,
+            None => true,
+        }
+    }
+
+    /// هل سيعبر هذا الأمر أفضل سعر مقابل في الدفتر حالياً، دون أي تعديل (لفحص PostOnly)؟
+    fn would_cross(&self, order: &Order) -> bool {
+        let (opposite_book, crosses): (&BTreeMap<Decimal, VecDeque<Order>>, fn(&Order, Decimal) -> bool) = match order.side {
+            OrderSide::Buy => (&self.asks, Self::crosses_ask),
+            OrderSide::Sell => (&self.bids, Self::crosses_bid),
+        };
+
+        let best = match order.side {
+            OrderSide::Buy => opposite_book.keys().next().copied(),
+            OrderSide::Sell => opposite_book.keys().next_back().copied(),
+        };
+
+        best.is_some_and(|price| crosses(order, price))
+    }
+
+    /// يحسب أقصى كمية قابلة للمطابقة فوراً ضد الأمر الوارد دون تعديل الدفتر (لفحص FOK مسبقاً).
+    /// يتجاهل السيولة التي تشكّل تداولاً مع النفس، لأن STP سيتخطاها أثناء المطابقة الفعلية أيضاً.
+    fn fillable_quantity(&self, order: &Order) -> Decimal {
+        let opposite_side = match order.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+        let book = match opposite_side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        let crosses: fn(&Order, Decimal) -> bool = match order.side {
+            OrderSide::Buy => Self::crosses_ask,
+            OrderSide::Sell => Self::crosses_bid,
+        };
+
+        let levels: Box<dyn Iterator<Item = (&Decimal, &VecDeque<Order>)>> = match opposite_side {
+            OrderSide::Buy => Box::new(book.iter().rev()),
+            OrderSide::Sell => Box::new(book.iter()),
+        };
+
+        let mut available = Decimal::ZERO;
+        for (price, queue) in levels {
+            if !crosses(order, *price) {
+                break;
+            }
+            for resting in queue {
+                if resting.strategy_id == order.strategy_id {
+                    continue;
+                }
+                available += resting.original_qty - resting.executed_qty;
+                if available >= order.original_qty {
+                    return available;
+                }
+            }
+        }
+        available
+    }
+
+    /// منطق مطابقة موحّد للجانبين؛ `crosses` يحدد هل يسمح سعر المستوى المقابل بالتنفيذ.
+    /// كل ملء ضد صانع يُسجَّل فوراً كـ `ExecutableMatch` معلّقة في `pending` بالإضافة لظهوره
+    /// في `trades`، لحين تثبيته أو استرجاعه عبر `commit_match`/`rollback_match`.
+    fn match_incoming(
+        &mut self,
+        order: &mut Order,
+        trades: &mut Vec<Trade>,
+        pending: &mut Vec<ExecutableMatch>,
+        crosses: fn(&Order, Decimal) -> bool,
+    ) {
+        let opposite_side = match order.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+
+        'levels: loop {
+            if order.executed_qty >= order.original_qty {
+                break;
+            }
+
+            let opposite_book = match opposite_side {
+                OrderSide::Buy => &mut self.bids,
+                OrderSide::Sell => &mut self.asks,
+            };
+
+            let best_level = match opposite_side {
+                // للبائع: أفضل مشترٍ هو الأعلى سعراً (آخر مفتاح)
+                OrderSide::Buy => opposite_book.keys().next_back().copied(),
+                // للمشتري: أفضل بائع هو الأرخص سعراً (أول مفتاح)
+                OrderSide::Sell => opposite_book.keys().next().copied(),
+            };
+
+            let level_price = match best_level {
+                Some(p) if crosses(order, p) => p,
+                _ => break, // لا توجد سيولة، أو السعر لا يطابق حد الأمر
+            };
+
+            loop {
+                if order.executed_qty >= order.original_qty {
+                    break 'levels;
+                }
+
+                let queue = match self.side_mut(opposite_side).get_mut(&level_price) {
+                    Some(q) if !q.is_empty() => q,
+                    _ => break, // المستوى فرغ، ننتقل للمستوى التالي
+                };
+
+                let maker_id = queue.front().unwrap().id;
+                let is_self_trade = queue.front().unwrap().strategy_id == order.strategy_id;
+
+                if is_self_trade {
+                    let policy = order.stp_policy.unwrap_or(self.stp_policy);
+                    match self.apply_stp(opposite_side, level_price, order, policy) {
+                        StpOutcome::ContinueLevel => continue,
+                        StpOutcome::SkipLevel => break,
+                        StpOutcome::TakerExhausted => break 'levels,
+                    }
+                }
+
+                let maker_strategy_id = queue.front().unwrap().strategy_id.clone();
+                let maker_rolling_volume = self.rolling_volume(&maker_strategy_id);
+                let taker_rolling_volume = self.rolling_volume(&order.strategy_id);
+
+                let queue = self.side_mut(opposite_side).get_mut(&level_price).unwrap();
+                let maker = queue.front_mut().unwrap();
+
+                let taker_remaining = order.original_qty - order.executed_qty;
+                let maker_remaining = maker.original_qty - maker.executed_qty;
+                let matched_qty = taker_remaining.min(maker_remaining);
+                let snapshot_before_match = maker.clone();
+
+                maker.update_execution(matched_qty, level_price);
+                order.update_execution(matched_qty, level_price);
+
+                let notional = level_price * matched_qty;
+                let maker_fee = self.fee_schedule.maker_fee(notional, maker_rolling_volume);
+                let taker_fee = self.fee_schedule.taker_fee(notional, taker_rolling_volume);
+                let matched_at = chrono::Utc::now().timestamp_nanos() as u64;
+
+                trades.push(Trade {
+                    maker_order_id: maker_id,
+                    taker_order_id: order.id,
+                    price: level_price,
+                    quantity: matched_qty,
+                    taker_side: order.side,
+                    executed_at: matched_at,
+                    maker_fee,
+                    taker_fee,
+                });
+
+                let match_id = self.next_match_id;
+                self.next_match_id += 1;
+                self.reserved_makers.insert(match_id, ReservedMaker {
+                    price_level: level_price,
+                    side: opposite_side,
+                    snapshot_before_match,
+                });
+                let exec = ExecutableMatch {
+                    match_id,
+                    taker_order_id: order.id,
+                    maker_order_id: maker_id,
+                    price: level_price,
+                    quantity: matched_qty,
+                    matched_at,
+                    state: MatchState::Pending,
+                };
+                self.pending_matches.insert(match_id, exec.clone());
+                pending.push(exec);
+
+                if maker.executed_qty >= maker.original_qty {
+                    let removed = queue.pop_front().unwrap();
+                    self.order_index.remove(&removed.id);
+                }
+
+                if queue.is_empty() {
+                    self.side_mut(opposite_side).remove(&level_price);
+                }
+
+                *self.volume_tracker.entry(maker_strategy_id).or_insert(Decimal::ZERO) += notional;
+                *self.volume_tracker.entry(order.strategy_id.clone()).or_insert(Decimal::ZERO) += notional;
+            }
+        }
+    }
+
+    /// يطبّق سياسة STP على أفضل أمر مقيم عند `price` مقابل الأمر الوارد `order`.
+    fn apply_stp(&mut self, resting_side: OrderSide, price: Decimal, order: &mut Order, policy: SelfTradePolicy) -> StpOutcome {
+        match policy {
+            SelfTradePolicy::CancelNewest => {
+                // لا نلغي المُقيم؛ الآخذ الوارد (الأحدث) هو من يُلغى. لا يكفي "تخطي" هذا
+                // المستوى فقط: طالما المُقيم لم يُمس فهو يبقى أفضل سعر في الدفتر، فتُعاد
+                // قراءته كأفضل مستوى في الدورة التالية من الحلقة الخارجية ويتكرر نفس فرع
+                // التداخل الذاتي إلى ما لا نهاية. لذا نُنهي الآخذ فعلياً (كما تفعل
+                // `engine.rs::apply_self_trade`'s `cancel_taker`) بدلاً من إرجاع نتيجة بلا أثر.
+                let resting_id = self.side_mut(resting_side).get(&price).and_then(|q| q.front()).map(|o| o.id);
+                if let Some(resting_id) = resting_id {
+                    let cancelled_qty = order.original_qty - order.executed_qty;
+                    warn!(
+                        "STP: self-trade avoided (CancelNewest) — cancelling incoming order {} ({} remaining) against resting order {} at {}",
+                        order.id, cancelled_qty, resting_id, price
+                    );
+                    self.last_stp_events.push(StpEvent {
+                        resting_order_id: resting_id,
+                        incoming_order_id: order.id,
+                        cancelled_qty,
+                        policy,
+                    });
+                    order.original_qty = order.executed_qty;
+                }
+                StpOutcome::TakerExhausted
+            }
+            SelfTradePolicy::CancelOldest => {
+                let queue = self.side_mut(resting_side).get_mut(&price).unwrap();
+                let resting = queue.pop_front().unwrap();
+                self.order_index.remove(&resting.id);
+                if queue.is_empty() {
+                    self.side_mut(resting_side).remove(&price);
+                }
+
+                let cancelled_qty = resting.original_qty - resting.executed_qty;
+                warn!(
+                    "STP: self-trade avoided (CancelOldest) — cancelled resting order {} ({} remaining) for incoming order {}",
+                    resting.id, cancelled_qty, order.id
+                );
+                self.last_stp_events.push(StpEvent {
+                    resting_order_id: resting.id,
+                    incoming_order_id: order.id,
+                    cancelled_qty,
+                    policy,
+                });
+                StpOutcome::ContinueLevel
+            }
+            SelfTradePolicy::DecrementCancel => {
+                let taker_remaining = order.original_qty - order.executed_qty;
+
+                let queue = self.side_mut(resting_side).get_mut(&price).unwrap();
+                let resting = queue.front_mut().unwrap();
+                let resting_id = resting.id;
+                let resting_remaining = resting.original_qty - resting.executed_qty;
+
+                let decrement = taker_remaining.min(resting_remaining);
+                resting.original_qty -= decrement;
+                order.original_qty -= decrement;
+
+                let resting_done = resting.original_qty <= resting.executed_qty;
+                if resting_done {
+                    let removed = queue.pop_front().unwrap();
+                    self.order_index.remove(&removed.id);
+                    if queue.is_empty() {
+                        self.side_mut(resting_side).remove(&price);
+                    }
+                }
+
+                warn!(
+                    "STP: self-trade avoided (DecrementCancel) — decremented order {} and resting order {} by {}",
+                    order.id, resting_id, decrement
+                );
+                self.last_stp_events.push(StpEvent {
+                    resting_order_id: resting_id,
+                    incoming_order_id: order.id,
+                    cancelled_qty: decrement,
+                    policy,
+                });
+
+                if order.original_qty <= order.executed_qty {
+                    StpOutcome::TakerExhausted
+                } else {
+                    StpOutcome::ContinueLevel
+                }
+            }
+            SelfTradePolicy::CancelBoth => {
+                let queue = self.side_mut(resting_side).get_mut(&price).unwrap();
+                let resting = queue.pop_front().unwrap();
+                self.order_index.remove(&resting.id);
+                if queue.is_empty() {
+                    self.side_mut(resting_side).remove(&price);
+                }
+
+                let resting_cancelled_qty = resting.original_qty - resting.executed_qty;
+                let taker_cancelled_qty = order.original_qty - order.executed_qty;
+                order.original_qty = order.executed_qty;
+
+                warn!(
+                    "STP: self-trade avoided (CancelBoth) — cancelled both resting order {} ({} remaining) and incoming order {} ({} remaining)",
+                    resting.id, resting_cancelled_qty, order.id, taker_cancelled_qty
+                );
+                self.last_stp_events.push(StpEvent {
+                    resting_order_id: resting.id,
+                    incoming_order_id: order.id,
+                    cancelled_qty: resting_cancelled_qty + taker_cancelled_qty,
+                    policy,
+                });
+
+                StpOutcome::TakerExhausted
+            }
+        }
+    }
+
+    fn insert_resting(&mut self, order: Order) {
+        let price = order.price.expect("Limit orders must carry a price");
+
+        self.order_index.insert(order.id, OrderLocation { price, side: order.side });
+
+        self.side_mut(order.side)
+            .entry(price)
+            .or_insert_with(VecDeque::new)
+            .push_back(order);
+    }
+}
+
+// =================================================================
+// اختبارات: منع التداول مع النفس + أنماط تنفيذ الأوامر (TIF / SendTake)
+// =================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn limit(strategy: &str, side: OrderSide, price: f64, qty: f64) -> Order {
+        Order::new(
+            rand_id(), strategy.into(), "BTCUSDT".into(), "BINANCE".into(),
+            side, OrderType::Limit, dec!(qty), Some(Decimal::try_from(price).unwrap()),
+        )
+    }
+
+    // مولّد معرفات بسيط ومتسلسل للاختبارات فقط (لا علاقة له بـ utils::id الحقيقي)
+    fn rand_id() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_stp_cancel_newest_cancels_the_incoming_taker() {
+        let mut book = OrderBook::new("BTCUSDT".into()).with_stp_policy(SelfTradePolicy::CancelNewest);
+
+        let resting = limit("ALPHA", OrderSide::Sell, 100.0, 1.0);
+        book.add_order(resting).unwrap();
+
+        // أمر من نفس الاستراتيجية يحاول عبور نفس المستوى
+        let taker = limit("ALPHA", OrderSide::Buy, 100.0, 1.0);
+        let trades = book.add_order(taker).unwrap();
+
+        assert_eq!(trades.len(), 0, "CancelNewest must not execute a self-trade");
+        // المُقيم (الأقدم) يبقى في الدفتر كما هو، والآخذ (الأحدث) يُلغى بالكامل ولا يرتاح أبداً:
+        // لو ارتاح لبقي هو نفسه أفضل سعر مقابل ولتكرر فرع التداخل الذاتي إلى ما لا نهاية.
+        let snapshot = book.get_snapshot();
+        assert_eq!(snapshot.asks.len(), 1, "Resting maker must remain untouched");
+        assert!(snapshot.bids.is_empty(), "Cancelled taker must not rest in the book");
+
+        let events = book.last_stp_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].policy, SelfTradePolicy::CancelNewest);
+        assert_eq!(events[0].cancelled_qty, dec!(1.0));
+    }
+
+    #[test]
+    fn test_stp_cancel_newest_terminates_with_multiple_orders_at_best_level() {
+        // اختبار الانحدار: قبل الإصلاح كانت `CancelNewest` تُرجع `SkipLevel` بلا أي أثر على
+        // الدفتر، فتُعاد قراءة نفس أفضل سعر في كل دورة من الحلقة الخارجية وتدخل حلقة لا نهائية.
+        let mut book = OrderBook::new("BTCUSDT".into()).with_stp_policy(SelfTradePolicy::CancelNewest);
+
+        book.add_order(limit("ALPHA", OrderSide::Sell, 100.0, 1.0)).unwrap();
+        book.add_order(limit("BETA", OrderSide::Sell, 100.0, 1.0)).unwrap();
+
+        let taker = limit("ALPHA", OrderSide::Buy, 100.0, 2.0);
+        let trades = book.add_order(taker).unwrap();
+
+        assert!(trades.is_empty(), "Self-trade against the front-of-queue maker must not execute");
+        let snapshot = book.get_snapshot();
+        assert_eq!(snapshot.asks.len(), 2, "Both resting makers (ALPHA and BETA) remain untouched");
+        assert!(snapshot.bids.is_empty(), "Cancelled taker must not rest in the book");
+    }
+
+    #[test]
+    fn test_stp_cancel_oldest_removes_resting_and_continues() {
+        let mut book = OrderBook::new("BTCUSDT".into()).with_stp_policy(SelfTradePolicy::CancelOldest);
+
+        book.add_order(limit("ALPHA", OrderSide::Sell, 100.0, 1.0)).unwrap();
+        book.add_order(limit("BETA", OrderSide::Sell, 100.0, 1.0)).unwrap();
+
+        let taker = limit("ALPHA", OrderSide::Buy, 100.0, 1.0);
+        let trades = book.add_order(taker).unwrap();
+
+        // المُقيم الأول (ALPHA) يُلغى بدون تنفيذ، ثم يُنفذ ضد الثاني (BETA)
+        assert_eq!(trades.len(), 1, "Should trade against the non-self maker after cancelling the self maker");
+        assert_eq!(trades[0].quantity, dec!(1.0));
+
+        let snapshot = book.get_snapshot();
+        assert!(snapshot.asks.is_empty());
+        assert!(snapshot.bids.is_empty());
+
+        let events = book.last_stp_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].policy, SelfTradePolicy::CancelOldest);
+        assert_eq!(events[0].cancelled_qty, dec!(1.0));
+    }
+
+    #[test]
+    fn test_stp_decrement_cancel_reduces_both_sides() {
+        let mut book = OrderBook::new("BTCUSDT".into()).with_stp_policy(SelfTradePolicy::DecrementCancel);
+
+        book.add_order(limit("ALPHA", OrderSide::Sell, 100.0, 3.0)).unwrap();
+
+        let taker = limit("ALPHA", OrderSide::Buy, 100.0, 1.0);
+        let trades = book.add_order(taker).unwrap();
+
+        // لا صفقة تُسجّل على التداخل الذاتي
+        assert_eq!(trades.len(), 0);
+
+        // الآخذ (1.0) استُنفد بالكامل، والمُقيم تقلص إلى 2.0 وبقي في الدفتر
+        let snapshot = book.get_snapshot();
+        assert_eq!(snapshot.bids.len(), 0, "Taker fully decremented, nothing rests on the bid side");
+        assert_eq!(snapshot.asks.len(), 1);
+        assert_eq!(snapshot.asks[0].original_qty, dec!(2.0));
+
+        let events = book.last_stp_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cancelled_qty, dec!(1.0));
+    }
+
+    #[test]
+    fn test_non_self_trade_still_matches_normally() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+
+        book.add_order(limit("ALPHA", OrderSide::Sell, 100.0, 1.0)).unwrap();
+        let trades = book.add_order(limit("BETA", OrderSide::Buy, 100.0, 1.0)).unwrap();
+
+        assert_eq!(trades.len(), 1, "Different strategies must trade normally");
+        assert!(book.last_stp_events().is_empty());
+    }
+
+    #[test]
+    fn test_ioc_drops_unfilled_remainder_instead_of_resting() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 1.0)).unwrap();
+
+        let mut taker = limit("TAKER", OrderSide::Buy, 100.0, 5.0);
+        taker.time_in_force = TimeInForce::IOC;
+        let trades = book.add_order(taker).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, dec!(1.0));
+        assert!(book.get_snapshot().bids.is_empty(), "IOC must never rest its unfilled remainder");
+    }
+
+    #[test]
+    fn test_fok_executes_nothing_when_not_fully_fillable() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 1.0)).unwrap();
+
+        let mut taker = limit("TAKER", OrderSide::Buy, 100.0, 5.0);
+        taker.time_in_force = TimeInForce::FOK;
+        let res = book.add_order(taker);
+
+        assert!(res.is_err(), "FOK must reject when full quantity cannot be filled");
+        let snapshot = book.get_snapshot();
+        assert_eq!(snapshot.asks.len(), 1, "Maker liquidity must be untouched by a rejected FOK");
+        assert_eq!(snapshot.asks[0].executed_qty, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_fok_fills_completely_when_liquidity_sufficient() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 3.0)).unwrap();
+
+        let mut taker = limit("TAKER", OrderSide::Buy, 100.0, 3.0);
+        taker.time_in_force = TimeInForce::FOK;
+        let trades = book.add_order(taker).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, dec!(3.0));
+        assert!(book.get_snapshot().asks.is_empty());
+    }
+
+    #[test]
+    fn test_post_only_rejected_when_it_would_cross() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 1.0)).unwrap();
+
+        let mut taker = limit("TAKER", OrderSide::Buy, 100.0, 1.0);
+        taker.time_in_force = TimeInForce::PostOnly;
+        let res = book.add_order(taker);
+
+        assert!(res.is_err(), "PostOnly must reject an order that would take liquidity");
+        assert_eq!(book.get_snapshot().asks.len(), 1, "Resting maker untouched by the rejection");
+    }
+
+    #[test]
+    fn test_post_only_rests_when_it_would_not_cross() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+
+        let mut maker = limit("MAKER", OrderSide::Buy, 99.0, 1.0);
+        maker.time_in_force = TimeInForce::PostOnly;
+        let trades = book.add_order(maker).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(book.get_snapshot().bids.len(), 1, "Non-crossing PostOnly order rests as a maker");
+    }
+
+    #[test]
+    fn test_send_take_crosses_book_and_computes_net_proceeds() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 2.0)).unwrap();
+
+        let taker = limit("TAKER", OrderSide::Buy, 100.0, 2.0);
+        let result = book.send_take(taker, dec!(10)).unwrap(); // 10 bps taker fee
+
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].quantity, dec!(2.0));
+        // إجمالي: 200.0، الرسوم: 200.0 * 10 / 10000 = 0.2
+        assert_eq!(result.net_proceeds, dec!(199.8));
+        assert!(book.get_snapshot().bids.is_empty(), "SendTake never leaves a resting remainder");
+    }
+
+    #[test]
+    fn test_maker_rebate_vs_taker_charge_on_match() {
+        // على غرار سيناريو الـ 50,000 BTC في اختبار التكامل: صانع يعرض عند 50,000، آخذ محدد يعبر عند نفس السعر
+        let schedule = crate::matching::fee_schedule::FeeSchedule::flat(dec!(-1), dec!(5));
+        let mut book = OrderBook::new("BTCUSDT".into()).with_fee_schedule(schedule);
+
+        book.add_order(limit("MAKER", OrderSide::Sell, 50000.0, 1.0)).unwrap();
+        let taker = limit("TAKER", OrderSide::Buy, 50000.0, 0.5);
+        let trades = book.add_order(taker).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        // الإسمي: 50000 * 0.5 = 25000؛ الصانع يتقاضى خصماً (-1 bps) والآخذ يدفع رسوماً (5 bps)
+        assert_eq!(trades[0].maker_fee, dec!(-2.5));
+        assert_eq!(trades[0].taker_fee, dec!(12.5));
+    }
+
+    #[test]
+    fn test_tier_crossing_lowers_taker_fee_after_rolling_volume_threshold() {
+        let schedule = crate::matching::fee_schedule::FeeSchedule::flat(dec!(0), dec!(10))
+            .with_tier(dec!(1000), dec!(0), dec!(2));
+        let mut book = OrderBook::new("BTCUSDT".into()).with_fee_schedule(schedule);
+
+        book.add_order(limit("MAKER", OrderSide::Sell, 100.0, 100.0)).unwrap();
+
+        // أول صفقة للآخذ: حجمه التراكمي صفر بعد، لذا يدفع المعدل الأساسي (10 bps)
+        let trades1 = book.add_order(limit("TAKER", OrderSide::Buy, 100.0, 5.0)).unwrap();
+        assert_eq!(trades1[0].taker_fee, dec!(0.5), "5 * 100 * 10 / 10000 = 0.5");
+
+        // بعد هذه الصفقة، حجم TAKER التراكمي أصبح 500، لا يزال دون الحد 1000
+        let trades2 = book.add_order(limit("TAKER", OrderSide::Buy, 100.0, 5.0)).unwrap();
+        assert_eq!(trades2[0].taker_fee, dec!(0.5), "still below the 1000 threshold");
+
+        // بعد هذه الصفقة تجاوز الحجم التراكمي 1000: المستوى المخفّض يُطبَّق على الصفقة التالية
+        let trades3 = book.add_order(limit("TAKER", OrderSide::Buy, 100.0, 5.0)).unwrap();
+        assert_eq!(trades3[0].taker_fee, dec!(0.1), "5 * 100 * 2 / 10000 = 0.1 once tier crossed");
+    }
+
+    #[test]
+    fn test_batch_auction_clears_at_volume_maximizing_price() {
+        let mut book = OrderBook::new("ETHUSDT".into());
+
+        // الطلب (تنازلياً): 5 @ 101، 5 @ 100، 5 @ 99 — العرض (تصاعدياً): 5 @ 99، 5 @ 100، 5 @ 101
+        book.submit_to_auction(limit("B1", OrderSide::Buy, 101.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("B2", OrderSide::Buy, 100.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("B3", OrderSide::Buy, 99.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("A1", OrderSide::Sell, 99.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("A2", OrderSide::Sell, 100.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("A3", OrderSide::Sell, 101.0, 5.0)).unwrap();
+
+        assert_eq!(book.pending_auction_count(), 6);
+
+        let trades = book.run_auction().unwrap();
+
+        // الحجم التنفيذي الأقصى = 10 عند السعر الموحّد 100 (min(D,S) يبلغ ذروته هناك)
+        let total_qty: Decimal = trades.iter().map(|t| t.quantity).sum();
+        assert_eq!(total_qty, dec!(10.0));
+        for trade in &trades {
+            assert_eq!(trade.price, dec!(100.0), "Every fill in the batch shares the single uniform clearing price");
+        }
+
+        assert_eq!(book.pending_auction_count(), 0, "Auction window is drained after run_auction");
+
+        // B3 وA3 كانا خارج سعر التصفية ولم يتقاطعا إطلاقاً؛ يرتاحان في الدفتر المستمر كأوامر Limit عادية
+        let snap = book.get_snapshot();
+        assert_eq!(snap.bids.len(), 1);
+        assert_eq!(snap.bids[0].price, Some(dec!(99.0)));
+        assert_eq!(snap.asks.len(), 1);
+        assert_eq!(snap.asks[0].price, Some(dec!(101.0)));
+    }
+
+    #[test]
+    fn test_batch_auction_pro_rata_splits_the_marginal_level() {
+        let mut book = OrderBook::new("ETHUSDT".into());
+
+        // الطلب: 5 @ 102 (أفضل من السعر الحدّي) + 10 @ 100 (حدّي) — العرض: 5 @ 98 + 6 @ 100 (كلاهما ممتلئ بالكامل)
+        book.submit_to_auction(limit("B1", OrderSide::Buy, 102.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("B2", OrderSide::Buy, 100.0, 10.0)).unwrap();
+        book.submit_to_auction(limit("A1", OrderSide::Sell, 98.0, 5.0)).unwrap();
+        book.submit_to_auction(limit("A2", OrderSide::Sell, 100.0, 6.0)).unwrap();
+
+        let trades = book.run_auction().unwrap();
+
+        let total_qty: Decimal = trades.iter().map(|t| t.quantity).sum();
+        // سعر التصفية 100: الطلب الكلي (>=100) = 15، العرض الكلي (<=100) = 11 -> الحجم المطابق = 11
+        assert_eq!(total_qty, dec!(11.0));
+        for trade in &trades {
+            assert_eq!(trade.price, dec!(100.0));
+        }
+
+        // لا يبقى شيء في الدفتر: كلا الأمرين من جانب العرض امتلآ بالكامل، والمستوى الحدّي للطلب
+        // (B2) وُزِّع تناسبياً بما يكفي فقط لتغطية البقية (6 من أصل 10)، والباقي (4) يرتاح كصانع سيولة.
+        let snap = book.get_snapshot();
+        assert!(snap.asks.is_empty(), "Both asks fully consumed by the matched volume");
+        assert_eq!(snap.bids.len(), 1, "Only B2's pro-rated remainder rests");
+        assert_eq!(snap.bids[0].price, Some(dec!(100.0)));
+        assert_eq!(snap.bids[0].original_qty - snap.bids[0].executed_qty, dec!(4.0), "B2 filled 6 of 10 pro-rata (ratio 0.6)");
+    }
+}
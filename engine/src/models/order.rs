@@ -32,10 +32,11 @@ pub enum OrderType {
 /// مدة صلاحية الأمر (Time In Force)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TimeInForce {
-    GTC, // Good Till Cancel (يبقى حتى نلغيه)
-    IOC, // Immediate Or Cancel (نفذ ما تستطيع فوراً وألغ الباقي)
-    FOK, // Fill Or Kill (الكل أو لا شيء)
-    GTD, // Good Till Date (حتى تاريخ معين)
+    GTC,      // Good Till Cancel (يبقى حتى نلغيه)
+    IOC,      // Immediate Or Cancel (نفذ ما تستطيع فوراً وألغ الباقي)
+    FOK,      // Fill Or Kill (الكل أو لا شيء، أو لا تنفيذ إطلاقاً)
+    GTD,      // Good Till Date (حتى تاريخ معين)
+    PostOnly, // ارتياح كصانع سيولة فقط؛ يُرفض إن كان سيعبر الدفتر فوراً
 }
 
 /// حالة الأمر الحالية (دورة الحياة)
@@ -49,6 +50,27 @@ pub enum OrderStatus {
     Canceled,       // تم الإلغاء يدوياً
     Rejected,       // تم الرفض من البورصة (خطأ)
     Expired,        // انتهت صلاحيته
+
+    /// طابقنا الأمر محلياً بتفاؤل (Optimistic Match) لكن لم يصل تأكيد تنفيذ خارجي بعد؛
+    /// قد يتحول هذا إما لـ `PartiallyFilled`/`Filled` عند التأكيد أو يُسترجع بالكامل
+    /// (انظر `MatchingEngine::rollback_match` في `matching::engine`) إن وصل رفض بدلاً من ذلك.
+    PendingCancel,
+}
+
+/// سبب وصول الأمر لحالته النهائية (Terminal State)، منفصل عن `OrderStatus` نفسها كي
+/// يستطيع السجل الجنائي التمييز لاحقاً بين إغلاق بطلب المستخدم وإغلاق آلي بحت.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderReason {
+    /// إغلاق بطلب صريح من المستخدم/الاستراتيجية (الإلغاء الاعتيادي)
+    Manual,
+    /// انتهت صلاحية الأمر (`TimeInForce::GTD`) قبل أن يُنفَّذ أو يُلغى يدوياً
+    Expired,
+    /// أُغلق آلياً بقرار من طبقة المخاطر (مثلاً قاطع دائرة أو انتهاك هامش)
+    RiskStop,
+    /// أُلغي (الآخذ أو المُقيم أو كلاهما) لتجنّب تداول الحساب مع نفسه (انظر `SelfTradePolicy`)
+    SelfTradePrevention,
+    /// أمر سوق لم يجد أي سيولة مقابلة على الإطلاق فأُلغي فوراً دون أي تنفيذ جزئي
+    NoLiquidity,
 }
 
 /// جانب الأمر (الشراء/البيع)
@@ -58,6 +80,21 @@ pub enum OrderSide {
     Sell,
 }
 
+/// سياسة منع التداول مع النفس (Self-Trade Prevention)، مطابقة لما تقدمه
+/// منصات مثل OpenBook/Mango عندما يلتقي أمران من نفس `strategy_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelfTradePolicy {
+    /// إلغاء كامل البقية غير المنفذة من الأمر الوارد (الأحدث) وترك المُقيم كما هو؛ لا تتابع
+    /// مطابقة الوارد عند مستويات السعر التالية
+    CancelNewest,
+    /// إلغاء الأمر المقيم (الأقدم) من الدفتر دون تنفيذه، ثم متابعة مطابقة الآخذ مع ما تبقى
+    CancelOldest,
+    /// تخفيض كلا الأمرين بأقل الكميتين وإلغاء من يصل للصفر، دون تسجيل أي صفقة على ذلك التداخل
+    DecrementCancel,
+    /// إلغاء كل من الأمر الوارد والأمر المُقيم بالكامل، دون أي تخفيض أو تنفيذ جزئي بينهما
+    CancelBoth,
+}
+
 // =================================================================
 // الهيكل الرئيسي للأمر (The Order Entity)
 // =================================================================
@@ -105,9 +142,31 @@ pub struct Order {
 
     pub status: OrderStatus,
 
+    /// وقت انتهاء الصلاحية (Unix Millis) لأوامر `TimeInForce::GTD` فقط؛ `None` لبقية الأنواع
+    pub expire_at: Option<u64>,
+
+    /// سبب الوصول للحالة النهائية الحالية؛ `Manual` افتراضياً حتى يُغيَّر صراحة (انظر `check_expiry`)
+    pub order_reason: OrderReason,
+
     /// طوابع زمنية (Timestamps)
     pub created_at: u64,
     pub updated_at: u64,
+
+    /// تجاوز اختياري لسياسة منع التداول مع النفس على مستوى الدفتر.
+    /// None يعني استخدام السياسة الافتراضية لدفتر الأوامر.
+    pub stp_policy: Option<SelfTradePolicy>,
+
+    /// معرف أمر الدخول الأب، إن كان هذا الأمر طفل قوس (Bracket) أُنشئ عبر `Bracket::new`.
+    /// `None` لأي أمر مستقل عادي.
+    pub parent_id: Option<u64>,
+
+    /// معرف مجموعة One-Cancels-Other المشتركة بين هذا الأمر وشقيقه؛ امتلاء أي منهما
+    /// (جزئياً أو كلياً) يُلغي الآخر تلقائياً (انظر `MatchingEngine::apply_oco_fill`).
+    pub oco_group: Option<Uuid>,
+
+    /// المسافة الثابتة (مطلقة، بنفس وحدة السعر) بين السعر الحالي و`stop_price` لأمر
+    /// `OrderType::TrailingStop`؛ `None` لأي أمر آخر. انظر `update_trailing_stop`.
+    pub trail_offset: Option<Decimal>,
 }
 
 impl Order {
@@ -143,8 +202,14 @@ impl Order {
             stop_price: None,
             avg_fill_price: None,
             status: OrderStatus::Created,
+            expire_at: None,
+            order_reason: OrderReason::Manual,
             created_at: now,
             updated_at: now,
+            stp_policy: None,
+            parent_id: None,
+            oco_group: None,
+            trail_offset: None,
         }
     }
 
@@ -161,6 +226,66 @@ impl Order {
         !self.is_active() && self.status != OrderStatus::Created
     }
 
+    /// يفحص هل تجاوز هذا الأمر (من نوع `TimeInForce::GTD` فقط) وقت `expire_at` دون أن
+    /// يُنفَّذ أو يُلغى يدوياً، وإن كان كذلك ينقله إلى `OrderStatus::Expired` مع
+    /// `order_reason = Expired`. يعيد `true` إن انتهت صلاحيته الآن فعلاً، `false` خلاف ذلك
+    /// (بما في ذلك الأوامر غير النشطة أو غير الخاضعة لـ GTD أصلاً). يُستدعى دورياً من
+    /// حلقة الجني (Reaper) التي تكتسح مجموعة الأوامر النشطة (انظر `matching::engine::OrderBook::sweep_expired`).
+    pub fn check_expiry(&mut self, now: u64) -> bool {
+        if !self.is_active() || self.time_in_force != TimeInForce::GTD {
+            return false;
+        }
+        match self.expire_at {
+            Some(expire_at) if now >= expire_at => {
+                self.status = OrderStatus::Expired;
+                self.order_reason = OrderReason::Expired;
+                self.updated_at = now;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// يُستدعى عند كل تحديث سعر جديد للرمز (Tick/Trade): يحرّك `stop_price` باتجاه
+    /// السوق فقط أبداً (علامة المياه العالية - High-Water-Mark)، فيرتفع لأمر بيع (يتبع
+    /// السوق الصاعد) عبر `max(stop_price, last_price - trail_offset)` ولا ينخفض أبداً،
+    /// وينخفض لأمر شراء (يتبع السوق الهابط) عبر `min(stop_price, last_price + trail_offset)`
+    /// ولا يرتفع أبداً. يعيد `true` ويحوّل `order_type` إلى `Market` إن كان `last_price`
+    /// قد تجاوز `stop_price` الحالي تواً (جاهز الآن للإصدار الفعلي كأمر سوق)، `false` خلاف
+    /// ذلك (بما في ذلك الأوامر غير `TrailingStop`/غير النشطة/بلا `trail_offset` مضبوط).
+    pub fn update_trailing_stop(&mut self, last_price: Decimal) -> bool {
+        if self.order_type != OrderType::TrailingStop || !self.is_active() {
+            return false;
+        }
+        let Some(trail_offset) = self.trail_offset else { return false; };
+
+        match self.side {
+            // بيع: نحمي مركزاً طويلاً، فنرفع stop_price فقط كلما صعد السوق (يشد الوقف خلفه)
+            OrderSide::Sell => {
+                let candidate = last_price - trail_offset;
+                self.stop_price = Some(self.stop_price.map_or(candidate, |sp| sp.max(candidate)));
+            }
+            // شراء: نحمي مركزاً قصيراً، فنخفض stop_price فقط كلما هبط السوق
+            OrderSide::Buy => {
+                let candidate = last_price + trail_offset;
+                self.stop_price = Some(self.stop_price.map_or(candidate, |sp| sp.min(candidate)));
+            }
+        }
+
+        let triggered = match self.side {
+            OrderSide::Sell => self.stop_price.map_or(false, |sp| last_price <= sp),
+            OrderSide::Buy => self.stop_price.map_or(false, |sp| last_price >= sp),
+        };
+
+        if triggered {
+            // تحوّل لأمر سوق فعلي جاهز للإصدار؛ لم يعد "وقفاً متحركاً" من الآن فصاعداً
+            self.order_type = OrderType::Market;
+            self.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        }
+
+        triggered
+    }
+
     /// حساب النسبة المئوية للتنفيذ
     pub fn fill_percentage(&self) -> Decimal {
         if self.original_qty.is_zero() {
@@ -190,6 +315,70 @@ impl Order {
         
         self.updated_at = chrono::Utc::now().timestamp_millis() as u64;
     }
+
+    /// يعيد بناء `executed_qty`/`avg_fill_price`/`status` من سجل الصفقات الخام
+    /// (`crate::matching::Trade`) بدلاً من الوثوق بالعدادات التراكمية في الذاكرة: يصفّي
+    /// الصفقات التي طرفها الآخذ أو الصانع هو هذا الأمر، يجمع كمياتها لـ `executed_qty`،
+    /// يحسب متوسط سعرها المرجَّح بالكمية لـ `avg_fill_price`، ثم يشتق `status` من النسبة
+    /// إلى `original_qty`. يُستخدم عند الاسترجاع بعد عطل أو للتدقيق الجنائي، حيث يكون
+    /// سجل الصفقات الثابت هو مصدر الحقيقة الوحيد وليست العدادات المتغيرة في الذاكرة.
+    pub fn reconcile_from_trades(&mut self, trades: &[crate::matching::Trade]) {
+        let related: Vec<&crate::matching::Trade> = trades
+            .iter()
+            .filter(|t| t.maker_order_id == self.id || t.taker_order_id == self.id)
+            .collect();
+
+        if related.is_empty() {
+            return;
+        }
+
+        let executed_qty: Decimal = related.iter().map(|t| t.quantity).sum();
+        let weighted_sum: Decimal = related.iter().map(|t| t.price * t.quantity).sum();
+
+        self.executed_qty = executed_qty;
+        self.avg_fill_price = if executed_qty.is_zero() {
+            None
+        } else {
+            Some(weighted_sum / executed_qty)
+        };
+
+        self.status = if self.executed_qty >= self.original_qty {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+
+        self.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+    }
+}
+
+/// أمر قوسي (Bracket / OCO): أمر دخول مع طفلين متنافيين (جني أرباح ووقف خسارة) يُنشَّطان
+/// فقط بعد امتلاء الدخول ولو جزئياً، ويُلغي أحدهما الآخر تلقائياً فور امتلاء أي منهما
+/// (انظر `MatchingEngine::place_bracket`/`activate_bracket_children`/`apply_oco_fill`
+/// في `matching::engine`).
+pub struct Bracket {
+    pub entry: Order,
+    pub take_profit: Order,
+    pub stop_loss: Order,
+}
+
+impl Bracket {
+    /// يبني قوساً كاملاً: يربط الطفلين بأمر الدخول عبر `parent_id`، يضعهما في نفس
+    /// `oco_group` العشوائي، ويعيد توليد `client_order_id` لكل منهما (بلاحقة `-TP`/`-SL`
+    /// على معرف الدخول) ليعكس صلتهما به بدل المعرف العشوائي الذي ولّده `Order::new`.
+    pub fn new(entry: Order, mut take_profit: Order, mut stop_loss: Order) -> Self {
+        let oco_group = Uuid::new_v4();
+
+        take_profit.parent_id = Some(entry.id);
+        take_profit.oco_group = Some(oco_group);
+        take_profit.client_order_id = format!("{}-TP", entry.client_order_id);
+
+        stop_loss.parent_id = Some(entry.id);
+        stop_loss.oco_group = Some(oco_group);
+        stop_loss.client_order_id = format!("{}-SL", entry.client_order_id);
+
+        Self { entry, take_profit, stop_loss }
+    }
 }
 
 // لتسهيل الطباعة والتصحيح (Debugging)
@@ -11,13 +11,14 @@
  */
 
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use parking_lot::Mutex; // أسرع من std::sync::Mutex
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
 use tracing::{error, warn, info};
 use crate::error::{AlphaError, AlphaResult};
-use super::{trigger_emergency_stop, RiskLevel, RiskReport};
+use super::{trigger_emergency_stop, RiskCheck, RiskContext, RiskLevel, RiskReport};
 
 /// حالات القاطع
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -177,7 +178,45 @@ impl CircuitBreaker {
         
         // إعادة فتح البوابة الذرية
         self.is_tripped.store(false, Ordering::SeqCst);
-        
+
+        Ok(())
+    }
+}
+
+// =================================================================
+// تكامل خط أنابيب RiskCheck (Pipeline Integration)
+// =================================================================
+
+/// يكيّف `CircuitBreaker` ليتدفق ضمن خط أنابيب `RiskCheck`: يكتفي بالفحص السريع
+/// (`ensure_closed`) بلا أي اطلاع على الأمر نفسه أو سياقه - تحديث العدادات
+/// (`record_pnl`/`record_error`) يبقى مسؤولية المتصل خارج السلسلة، كما كان قبل هذا الغلاف.
+pub struct CircuitBreakerCheck {
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerCheck {
+    pub fn new(breaker: Arc<CircuitBreaker>) -> Self {
+        Self { breaker }
+    }
+}
+
+impl RiskCheck for CircuitBreakerCheck {
+    fn name(&self) -> &'static str {
+        "CIRCUIT_BREAKER"
+    }
+
+    fn check(&self, _order: &crate::matching::Order, _context: &RiskContext) -> Result<(), RiskReport> {
+        if let Err(AlphaError::RiskViolation { rule, limit, actual }) = self.breaker.ensure_closed() {
+            return Err(RiskReport {
+                check_name: self.name().to_string(),
+                level: RiskLevel::Critical,
+                threshold: limit.parse().unwrap_or(Decimal::ZERO),
+                attempted: actual.parse().unwrap_or(Decimal::ZERO),
+                message: format!("circuit breaker is open (rule: {})", rule),
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                signature: Vec::new(),
+            });
+        }
         Ok(())
     }
 }
\ No newline at end of file
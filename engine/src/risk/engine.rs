@@ -3,8 +3,10 @@ use crate::models::order::Order;
 use crate::utils::logger::log_risk_reject;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::Zero;
+use std::collections::HashSet;
 use std::sync::Arc;
 use parking_lot::RwLock;
+use super::{trigger_emergency_stop, RiskCheck, RiskContext, RiskLevel, RiskReport};
 
 #[derive(Debug, Clone)]
 pub struct RiskConfig {
@@ -24,13 +26,90 @@ impl Default for RiskConfig {
 pub struct RiskEngine {
     config: RiskConfig,
     current_loss: Arc<RwLock<Decimal>>,
+    /// خط أنابيب فحوص `RiskCheck` القابلة للتوصيل (انظر `evaluate`) - منفصل تماماً عن
+    /// `check_order` أدناه، إذ يعمل على عائلة `crate::matching::Order` الخفيفة بدل هذه
+    /// العائلة الغنية (`crate::models::order::Order`).
+    checks: RwLock<Vec<Box<dyn RiskCheck>>>,
+    /// أسماء الفحوص المُعطَّلة يدوياً عبر `disable` (انظر `RiskCheck::name`)
+    disabled_checks: RwLock<HashSet<String>>,
 }
 
 impl RiskEngine {
     pub fn new(config: Option<RiskConfig>) -> Self {
-        Self { 
+        Self {
             config: config.unwrap_or_default(),
             current_loss: Arc::new(RwLock::new(Decimal::ZERO)),
+            checks: RwLock::new(Vec::new()),
+            disabled_checks: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// يُسجِّل فحصاً جديداً في نهاية خط الأنابيب - يُشغَّل بترتيب التسجيل ضمن `evaluate`
+    pub fn register(&self, check: Box<dyn RiskCheck>) {
+        self.checks.write().push(check);
+    }
+
+    /// يُعيد تفعيل فحص مُعطَّل مسبقاً بالاسم
+    pub fn enable(&self, name: &str) {
+        self.disabled_checks.write().remove(name);
+    }
+
+    /// يُعطِّل فحصاً بالاسم دون إزالته من خط الأنابيب - `evaluate` يتجاوزه حتى يُعاد تفعيله
+    pub fn disable(&self, name: &str) {
+        self.disabled_checks.write().insert(name.to_string());
+    }
+
+    /// ترتيب صارم لمستويات الخطورة، يُستخدَم لمقارنة "الأسوأ" بين أكثر من فشل Rejection
+    /// ضمن نفس التشغيلة (Critical/Fatal يوقفان السلسلة فوراً فلا حاجة لمقارنتهما هنا).
+    fn level_rank(level: RiskLevel) -> u8 {
+        match level {
+            RiskLevel::Warning => 0,
+            RiskLevel::Rejection => 1,
+            RiskLevel::Critical => 2,
+            RiskLevel::Fatal => 3,
+        }
+    }
+
+    /// يشغّل كل الفحوص المُسجَّلة والمُفعَّلة بترتيب التسجيل على أمر واحد. يتوقف فوراً عند
+    /// أول فشل Critical أو Fatal (ويُفعِّل الإيقاف الطارئ العالمي عند Fatal عبر
+    /// `trigger_emergency_stop`)، لكنه يواصل عبر فشل Rejection ليُرجع أسوأها فقط بدل أولها
+    /// (عند تساوي الرتبة يُرجَع آخرها). تتراكم تحذيرات Warning عبر السلسلة كاملة وتُعاد في
+    /// `Ok` حين لا يقع أي فشل حاجب؛ أما عند `Err` فيُعاد تقرير الفشل وحده دون التحذيرات.
+    pub fn evaluate(&self, order: &crate::matching::Order, context: &RiskContext) -> Result<Vec<RiskReport>, RiskReport> {
+        let disabled = self.disabled_checks.read();
+        let checks = self.checks.read();
+
+        let mut warnings = Vec::new();
+        let mut worst_blocking: Option<RiskReport> = None;
+
+        for check in checks.iter() {
+            if !check.is_enabled() || disabled.contains(check.name()) {
+                continue;
+            }
+
+            if let Err(report) = check.check(order, context) {
+                match report.level {
+                    RiskLevel::Warning => warnings.push(report),
+                    RiskLevel::Fatal => {
+                        trigger_emergency_stop();
+                        return Err(report);
+                    }
+                    RiskLevel::Critical => return Err(report),
+                    RiskLevel::Rejection => {
+                        let is_worse = worst_blocking.as_ref().map_or(true, |current| {
+                            Self::level_rank(report.level) >= Self::level_rank(current.level)
+                        });
+                        if is_worse {
+                            worst_blocking = Some(report);
+                        }
+                    }
+                }
+            }
+        }
+
+        match worst_blocking {
+            Some(report) => Err(report),
+            None => Ok(warnings),
         }
     }
 
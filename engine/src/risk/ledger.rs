@@ -0,0 +1,174 @@
+/*
+ * ALPHA SOVEREIGN - FORENSIC RISK LEDGER
+ * =================================================================
+ * Component Name: engine/src/risk/ledger.rs
+ * Core Responsibility: سجل سلسلة-هاش غير قابل للتلاعب لكل `RiskReport` وحدث إيقاف طارئ (Risk Management Pillar).
+ * Design Pattern: Append-Only Hash Chain / WORM
+ * Forensic Impact: يجعل سجل المخاطر "غير قابل للإنكار" - أي تعديل على تقرير سابق (أو حذفه) يُبطل هاش كل قيد بعده.
+ * =================================================================
+ */
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use crate::error::{AlphaError, AlphaResult};
+use super::RiskReport;
+
+/// حدث واحد قابل للتسجيل في السجل الجنائي: إما تقرير فحص مخاطر كامل، أو تفعيل الإيقاف
+/// الطارئ نفسه (قد يُستدعى مباشرة من خارج فحص مُقنَّن واحد، فلا يترافق بالضرورة مع `RiskReport`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LedgerEvent {
+    Report(RiskReport),
+    EmergencyStop { reason: String, timestamp: u64 },
+}
+
+/// قيد واحد في السجل: الحدث المسجَّل، وهاشه المتسلسل من سابقه مباشرة -
+/// `entry_hash = H(prev_hash || serialized_event)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub event: LedgerEvent,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+/// سجل جنائي غير قابل للتلاعب (Tamper-Evident) لكل تقرير مخاطر وكل تفعيل إيقاف طارئ:
+/// كل قيد مربوط بسابقه عبر هاش SHA-256 متسلسل، ومُلحَق فوراً على القرص (Append-Only) كي
+/// تنجو السلسلة من إعادة التشغيل. أي تعديل أو حذف لقيد تاريخي يُبطل هاش كل ما بعده، فيكشفه
+/// `verify()` فوراً عند الإقلاع التالي.
+pub struct ForensicLedger {
+    path: PathBuf,
+    genesis_hash: String,
+    entries: Mutex<Vec<LedgerEntry>>,
+    last_hash: Mutex<String>,
+}
+
+impl ForensicLedger {
+    /// يفتح سجلاً موجوداً على `path` (ويُعيد تحميل كل قيوده) أو يبدأ واحداً جديداً فارغاً
+    /// إن لم يكن الملف موجوداً بعد، مبتدئاً السلسلة من `genesis_seed` في الحالتين. يتحقق
+    /// السجل من سلامة سلسلته بنفسه فور التحميل، ويُفعِّل الإيقاف الطارئ إن وجدها مكسورة -
+    /// سجل جنائي تالف هو بحد ذاته حدث Fatal.
+    pub fn new(path: impl AsRef<Path>, genesis_seed: &str) -> AlphaResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let genesis_hash = Self::hash_bytes(genesis_seed.as_bytes());
+
+        let mut entries = Vec::new();
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| AlphaError::ConfigMissing(format!("Cannot open forensic ledger {}: {}", path.display(), e)))?;
+            for line in BufReader::new(file).lines() {
+                let line = line
+                    .map_err(|e| AlphaError::ConfigMissing(format!("Cannot read forensic ledger {}: {}", path.display(), e)))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: LedgerEntry = serde_json::from_str(&line)
+                    .map_err(|e| AlphaError::ValidationFailed(format!("Malformed forensic ledger entry: {}", e)))?;
+                entries.push(entry);
+            }
+        }
+
+        let last_hash = entries.last().map(|e| e.entry_hash.clone()).unwrap_or_else(|| genesis_hash.clone());
+
+        let ledger = Self {
+            path,
+            genesis_hash,
+            entries: Mutex::new(entries),
+            last_hash: Mutex::new(last_hash),
+        };
+
+        if let Err(bad_index) = ledger.verify() {
+            tracing::error!(
+                "FORENSIC_LEDGER: chain verification failed at entry {} on boot - a corrupted audit trail is itself a Fatal breach",
+                bad_index
+            );
+            super::trigger_emergency_stop();
+        }
+
+        Ok(ledger)
+    }
+
+    fn hash_bytes(bytes: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(bytes);
+        hex::encode(hasher.finalize())
+    }
+
+    /// يُلحق حدثاً جديداً بالسلسلة، يكتبه فوراً للملف (سطر JSON واحد لكل قيد)، ويُرجع هاشه
+    /// الجديد (`entry_hash`) - ما يصبح `prev_hash` للقيد التالي.
+    pub fn append(&self, event: LedgerEvent) -> AlphaResult<String> {
+        let serialized = serde_json::to_string(&event)
+            .map_err(|e| AlphaError::ValidationFailed(format!("Cannot serialize ledger event: {}", e)))?;
+
+        let mut last_hash = self.last_hash.lock();
+        let mut hasher = Sha256::new();
+        hasher.update(last_hash.as_bytes());
+        hasher.update(serialized.as_bytes());
+        let entry_hash = hex::encode(hasher.finalize());
+
+        let entry = LedgerEntry {
+            event,
+            prev_hash: last_hash.clone(),
+            entry_hash: entry_hash.clone(),
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)
+            .map_err(|e| AlphaError::ConfigMissing(format!("Cannot open forensic ledger {}: {}", self.path.display(), e)))?;
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap_or_default())
+            .map_err(|e| AlphaError::ConfigMissing(format!("Cannot write forensic ledger entry: {}", e)))?;
+
+        self.entries.lock().push(entry);
+        *last_hash = entry_hash.clone();
+        Ok(entry_hash)
+    }
+
+    /// يُسجِّل تقرير فحص مخاطر واحداً (يُستدعى عادة من نفس الموقع الذي يُرجِع `Err(RiskReport)`
+    /// من `RiskCheck::check`).
+    pub fn append_report(&self, report: RiskReport) -> AlphaResult<String> {
+        self.append(LedgerEvent::Report(report))
+    }
+
+    /// يُسجِّل حدث تفعيل الإيقاف الطارئ نفسه. ملاحظة: `trigger_emergency_stop()` الحرة في
+    /// `risk::mod` حالة عامة ثابتة (`AtomicBool`) لا تملك مرجعاً لأي سجل - من يملك سجلاً
+    /// ويُفعِّل الإيقاف الطارئ يدوياً عليه استدعاء هذه الدالة صراحة بجانبه ليبقى الحدث موثَّقاً.
+    pub fn append_emergency_stop(&self, reason: impl Into<String>, timestamp: u64) -> AlphaResult<String> {
+        self.append(LedgerEvent::EmergencyStop { reason: reason.into(), timestamp })
+    }
+
+    /// يعيد حساب السلسلة كاملة من الجذر (`genesis_seed`)، ويُرجع فهرس أول قيد وُجد متلاعَباً
+    /// به (هاشه أو `prev_hash` لا يطابق ما هو متوقَّع) إن وُجد.
+    pub fn verify(&self) -> Result<(), usize> {
+        let entries = self.entries.lock();
+        let mut expected_prev = self.genesis_hash.clone();
+
+        for (index, entry) in entries.iter().enumerate() {
+            if entry.prev_hash != expected_prev {
+                return Err(index);
+            }
+
+            let serialized = serde_json::to_string(&entry.event).unwrap_or_default();
+            let mut hasher = Sha256::new();
+            hasher.update(expected_prev.as_bytes());
+            hasher.update(serialized.as_bytes());
+            let recomputed = hex::encode(hasher.finalize());
+
+            if recomputed != entry.entry_hash {
+                return Err(index);
+            }
+            expected_prev = entry.entry_hash.clone();
+        }
+
+        Ok(())
+    }
+
+    /// عدد القيود المُسجَّلة حالياً في السجل.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -10,33 +10,105 @@
  * =================================================================
  */
 
+use std::collections::HashMap;
+use std::sync::Arc;
+use parking_lot::Mutex;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal_macros::dec;
 use tracing::{warn, error, info};
 use crate::error::{AlphaError, AlphaResult};
+use super::{RiskCheck, RiskContext, RiskLevel, RiskReport};
+
+/// آخر `PortfolioHealth` محسوبة بنجاح عبر `evaluate_health`، بصرف النظر عن أي `MarginGuard`
+/// أصدرها - يقرأها أمر `HEALTH` في `transport::tcp_server` كي يعرض المشغّل حالة المحفظة
+/// اللحظية دون الحاجة لمرجع مباشر لكائن `MarginGuard` (على غرار `GLOBAL_EMERGENCY_STOP`).
+static LAST_HEALTH: Mutex<Option<PortfolioHealth>> = Mutex::new(None);
+
+/// آخر لقطة صحة محفظة محسوبة، إن كانت هناك واحدة منذ الإقلاع.
+pub fn last_portfolio_health() -> Option<PortfolioHealth> {
+    LAST_HEALTH.lock().clone()
+}
+
+/// شريحة واحدة من جدول هامش الصيانة المتدرج: تغطي كل القيم الإسمية حتى `notional_ceiling`
+/// (شاملاً السقف نفسه، والشرائح مرتبة تصاعدياً)، وتحدد معدل هامش الصيانة ومبلغ الخصم الثابت الخاصين
+/// بها - بالضبط كما تتدرج أنظمة الهامش المتقاطع الحقيقية مع حجم المركز.
+#[derive(Debug, Clone)]
+pub struct MaintenanceMarginBracket {
+    pub notional_ceiling: Decimal,
+    pub maintenance_margin_rate: Decimal,
+    pub maintenance_amount: Decimal,
+}
+
+/// جدول الشرائح الخاص برمز واحد. الشرائح يجب أن تكون مرتبة تصاعدياً بحسب `notional_ceiling`؛
+/// إن تجاوزت القيمة الإسمية آخر شريحة معرَّفة، تُستخدم تلك الأخيرة (الأعلى MMR) كإجراء أمان.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceMarginTable {
+    pub brackets: Vec<MaintenanceMarginBracket>,
+}
+
+impl MaintenanceMarginTable {
+    pub fn bracket_for(&self, notional: Decimal) -> Option<&MaintenanceMarginBracket> {
+        self.brackets
+            .iter()
+            .find(|b| notional <= b.notional_ceiling)
+            .or_else(|| self.brackets.last())
+    }
+}
 
 /// إعدادات حارس الهامش
 #[derive(Debug, Clone)]
 pub struct MarginConfig {
     /// الرافعة المالية القصوى المسموحة عالمياً للنظام (مثلاً 10x)
     pub max_global_leverage: Decimal,
-    
+
     /// نسبة التحذير من التسييل (مثلاً 0.80)
     /// يعني: إذا وصلنا لـ 80% من المسافة نحو التسييل، ابدأ في تقليل المراكز.
     pub liquidation_safety_buffer: Decimal,
-    
-    /// الحد الأدنى للهامش المقبول (Maintenance Margin Rate)
-    /// يختلف حسب العملة (مثلاً BTC تحتاج 0.5%، بينما ALTCOIN تحتاج 2%)
+
+    /// الحد الأدنى للهامش المقبول (Maintenance Margin Rate) - يُستخدم فقط كشريحة احتياطية
+    /// لرمز لا يملك جدولاً متدرجاً في `maintenance_margin_tables`
     pub default_maintenance_margin: Decimal,
+
+    /// جداول هامش الصيانة المتدرجة لكل رمز (Symbol -> Table). رمز غائب يسقط للقيمة
+    /// الافتراضية المسطّحة `default_maintenance_margin` عبر شريحة وحيدة ضمنية.
+    pub maintenance_margin_tables: HashMap<String, MaintenanceMarginTable>,
+}
+
+/// الإجراء الموصى به استجابةً لوضع هامش حالي - استبدال لتحذير نصي وحيد بقرار متدرّج
+/// (Graduated Response) يُشتق من عمق اختراق حاجز الأمان (`liquidation_safety_buffer`) نحو
+/// خط التسييل الفعلي (`margin_ratio == 1.0`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RiskAction {
+    /// دون حاجز الأمان - لا إجراء مطلوب
+    None,
+    /// اخترق الحاجز بعمق كافٍ ليبرر تقليص المركز: الكمية الاسمية الموصى بتخفيضها عبر أمر
+    /// Reduce-Only لإعادة `effective_leverage` إلى ما دون `max_global_leverage`
+    ReduceBy(Decimal),
+    /// اخترق حاجز الأمان لكن بعمق ضحل بعد: امنع أي أمر جديد يزيد التعرض، دون تقليص قسري
+    HaltNewOrders,
+    /// بلغ أو تجاوز خط التسييل الفعلي (`margin_ratio >= 1.0`): أغلق المركز بالكامل فوراً
+    CloseAll,
 }
 
 /// لقطة لوضع المحفظة (تستخدم في الحسابات)
+#[derive(Debug, Clone)]
 pub struct PortfolioHealth {
     pub total_equity: Decimal,      // الرصيد + الربح غير المحقق
     pub total_notional: Decimal,    // القيمة الإسمية لكل المراكز المفتوحة
     pub used_margin: Decimal,       // الهامش المحجوز حالياً
     pub margin_ratio: Decimal,      // نسبة الهامش (الخطر)
     pub effective_leverage: Decimal,// الرافعة الحقيقية المستخدمة
+
+    /// سعر التسييل الآمن (Maintenance Liquidation Price) - السعر الذي يجب أن يخرج عنده
+    /// الحارس قبل أن تتدخل البورصة، محسوباً بمعدل هامش الصيانة الفعلي للشريحة الحالية.
+    pub maintenance_liquidation_price: Decimal,
+
+    /// سعر الإفلاس (Bankruptcy Price) - السعر الذي تصبح عنده قيمة الحقوق صفراً تماماً
+    /// (MMR = 0%)؛ هذا خط النهاية المطلق الذي تتجاوزه البورصة نفسها نحو الخسارة الصافية.
+    pub bankruptcy_price: Decimal,
+
+    /// الإجراء الموصى به الآن استناداً إلى `margin_ratio` أعلاه - انظر `RiskAction`
+    pub recommended_action: RiskAction,
 }
 
 pub struct MarginGuard {
@@ -82,34 +154,53 @@ impl MarginGuard {
         Ok(())
     }
 
+    /// يبحث عن شريحة هامش الصيانة المناسبة لرمز وقيمة إسمية معينين. رمز غائب من
+    /// `maintenance_margin_tables` يسقط لشريحة وحيدة ضمنية تستخدم `default_maintenance_margin`
+    /// بلا مبلغ خصم، حفاظاً على التوافق مع الإعدادات المسطّحة القديمة.
+    fn bracket_for(&self, symbol: &str, notional: Decimal) -> MaintenanceMarginBracket {
+        self.config
+            .maintenance_margin_tables
+            .get(symbol)
+            .and_then(|table| table.bracket_for(notional))
+            .cloned()
+            .unwrap_or(MaintenanceMarginBracket {
+                notional_ceiling: Decimal::MAX,
+                maintenance_margin_rate: self.config.default_maintenance_margin,
+                maintenance_amount: Decimal::ZERO,
+            })
+    }
+
     /// 2. تقييم الصحة العامة (Real-time Health Check)
-    /// يتم استدعاؤه مع كل تحديث للسعر (Tick)
+    /// يتم استدعاؤه مع كل تحديث للسعر (Tick). يشتق متطلب هامش الصيانة من شريحة الرمز
+    /// المتدرجة (`MaintenanceMarginBracket`) بدل معدل مسطّح واحد، ويُرفق سعري التسييل
+    /// الآمن والإفلاس لتمييز "خط الخروج الآمن" عن "نقطة الإعسار الفعلية".
     pub fn evaluate_health(
         &self,
+        symbol: &str,
         equity: Decimal,
-        maintenance_margin_required: Decimal,
-        total_notional: Decimal,
+        entry_price: Decimal,
+        quantity: Decimal,
+        is_long: bool,
     ) -> AlphaResult<PortfolioHealth> {
-        
+
         if equity <= Decimal::ZERO {
             // حالة إفلاس (Bankruptcy)
             error!("MARGIN_CRITICAL: Negative Equity detected! Immediate liquidation protocol required.");
             return Err(AlphaError::Fatal("NEGATIVE_EQUITY".into()));
         }
 
+        let total_notional = entry_price * quantity;
+        let bracket = self.bracket_for(symbol, total_notional);
+
+        // متطلب هامش الصيانة = القيمة الإسمية × MMR الشريحة - مبلغ الخصم الثابت لها
+        let maintenance_margin_required =
+            (total_notional * bracket.maintenance_margin_rate - bracket.maintenance_amount)
+                .max(Decimal::ZERO);
+
         // نسبة الهامش = الهامش المطلوب للصيانة / الرصيد الحالي
         // إذا وصلت 100% (1.0)، فالبورصة ستقوم بالتسييل.
-        let margin_ratio = if equity.is_zero() {
-            Decimal::MAX
-        } else {
-            maintenance_margin_required / equity
-        };
-
-        let effective_leverage = if equity.is_zero() {
-            Decimal::ZERO 
-        } else {
-            total_notional / equity
-        };
+        let margin_ratio = maintenance_margin_required / equity;
+        let effective_leverage = total_notional / equity;
 
         // التحقق من المناطق الخطرة
         if margin_ratio > self.config.liquidation_safety_buffer {
@@ -118,43 +209,235 @@ impl MarginGuard {
                 (margin_ratio * Decimal::from(100)).round_dp(2),
                 (self.config.liquidation_safety_buffer * Decimal::from(100)).round_dp(2)
             );
-            
-            // هنا يمكن إرسال إشارة لتقليل المراكز (De-leveraging Signal)
-            // في النسخة الكاملة، سنعيد Enum يطلب Action معين
         }
 
-        Ok(PortfolioHealth {
+        let (maintenance_liquidation_price, bankruptcy_price) =
+            self.calculate_liquidation_prices(symbol, entry_price, quantity, equity, is_long);
+
+        let recommended_action = self.recommend_action(margin_ratio, effective_leverage, total_notional, entry_price, equity);
+
+        let health = PortfolioHealth {
             total_equity: equity,
             total_notional,
             used_margin: maintenance_margin_required,
             margin_ratio,
             effective_leverage,
-        })
+            maintenance_liquidation_price,
+            bankruptcy_price,
+            recommended_action,
+        };
+
+        *LAST_HEALTH.lock() = Some(health.clone());
+        Ok(health)
     }
 
-    /// 3. حساب سعر التسييل الداخلي (للتخطيط)
-    /// يعيد السعر الذي يجب أن نخرج عنده قبل البورصة
-    pub fn calculate_internal_liquidation_price(
+    /// يشتق الإجراء الموصى به من عمق اختراق حاجز الأمان نحو خط التسييل الفعلي
+    /// (`margin_ratio == 1.0`). دون الحاجز: لا إجراء. تجاوز الحاجز بعمق ضحل (أقل من نصف
+    /// المسافة المتبقية حتى التسييل): امنع أوامر جديدة فقط. تجاوزه بعمق كبير ورافعة فعلية
+    /// تتجاوز `max_global_leverage`: أصدر حجم تخفيض (Reduce-Only) يُعيد الرافعة تماماً إلى
+    /// `max_global_leverage` (`notional - max_global_leverage * equity`, مُحوَّلاً لكمية
+    /// بقسمته على `entry_price`). بلوغ خط التسييل نفسه أو تجاوزه: أغلق المركز بالكامل.
+    fn recommend_action(
         &self,
+        margin_ratio: Decimal,
+        effective_leverage: Decimal,
+        total_notional: Decimal,
         entry_price: Decimal,
-        leverage: Decimal,
+        equity: Decimal,
+    ) -> RiskAction {
+        if margin_ratio >= Decimal::ONE {
+            return RiskAction::CloseAll;
+        }
+        if margin_ratio <= self.config.liquidation_safety_buffer {
+            return RiskAction::None;
+        }
+
+        let breach_depth = (margin_ratio - self.config.liquidation_safety_buffer)
+            / (Decimal::ONE - self.config.liquidation_safety_buffer);
+
+        if breach_depth >= dec!(0.5) && effective_leverage > self.config.max_global_leverage && !entry_price.is_zero() {
+            let target_notional = self.config.max_global_leverage * equity;
+            let reduce_notional = (total_notional - target_notional).max(Decimal::ZERO);
+            RiskAction::ReduceBy(reduce_notional / entry_price)
+        } else {
+            RiskAction::HaltNewOrders
+        }
+    }
+
+    /// 3. حساب سعري التسييل الآمن والإفلاس الحقيقيين، باستخدام شريحة هامش الصيانة
+    /// المتدرجة الخاصة بالرمز بدل معدل مسطّح وحشو أمان تقريبي. `maintenance_liquidation_price`
+    /// يستخدم MMR الشريحة الفعلي (خط الخروج الآمن)، بينما `bankruptcy_price` يفترض MMR = 0%
+    /// (السعر الذي تصبح عنده قيمة الحقوق صفراً تماماً - نقطة الإعسار المطلقة).
+    pub fn calculate_liquidation_prices(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        quantity: Decimal,
+        equity: Decimal,
         is_long: bool,
-    ) -> Decimal {
-        // معادلة تقريبية للتسييل:
-        // Long: Entry * (1 - (1/Leverage) + MMR)
-        // MMR = Maintenance Margin Rate (e.g., 0.5%)
-        
-        let mmr = self.config.default_maintenance_margin;
-        let safety_pad = Decimal::from_f64(0.02).unwrap(); // نزيد 2% أمان إضافي
+    ) -> (Decimal, Decimal) {
+        let notional = entry_price * quantity;
+        let bracket = self.bracket_for(symbol, notional);
+        let mmr = bracket.maintenance_margin_rate;
+        let maintenance_amount = bracket.maintenance_amount;
 
         if is_long {
-            // Entry * (1 - 1/Lev + MMR + Pad)
-            let risk_factor = (Decimal::ONE / leverage) - mmr - safety_pad;
-            entry_price * (Decimal::ONE - risk_factor)
+            let liquidation_price =
+                (notional - (equity - maintenance_amount)) / (quantity * (Decimal::ONE - mmr));
+            let bankruptcy_price = (notional - equity) / quantity;
+            (liquidation_price, bankruptcy_price)
         } else {
-            // Short: Entry * (1 + 1/Lev - MMR - Pad)
-            let risk_factor = (Decimal::ONE / leverage) - mmr - safety_pad;
-            entry_price * (Decimal::ONE + risk_factor)
+            let liquidation_price =
+                (notional + (equity - maintenance_amount)) / (quantity * (Decimal::ONE + mmr));
+            let bankruptcy_price = (notional + equity) / quantity;
+            (liquidation_price, bankruptcy_price)
         }
     }
+}
+
+// =================================================================
+// تكامل خط أنابيب RiskCheck (Pipeline Integration)
+// =================================================================
+
+/// يكيّف `MarginGuard` ليتدفق ضمن خط أنابيب `RiskCheck`: يشتق القيمة الإسمية للأمر الجديد
+/// من `price * quantity`، ويأخذ الرصيد الحالي والقيمة الإسمية للمراكز المفتوحة مسبقاً من
+/// `RiskContext` - `MarginGuard` نفسه لا يعرف شيئاً عن تمثيل الأمر المستخدَم في طبقة المطابقة.
+pub struct MarginGuardCheck {
+    guard: Arc<MarginGuard>,
+}
+
+impl MarginGuardCheck {
+    pub fn new(guard: Arc<MarginGuard>) -> Self {
+        Self { guard }
+    }
+}
+
+impl RiskCheck for MarginGuardCheck {
+    fn name(&self) -> &'static str {
+        "MARGIN_GUARD"
+    }
+
+    fn check(&self, order: &crate::matching::Order, context: &RiskContext) -> Result<(), RiskReport> {
+        let new_order_notional = order.price * order.quantity;
+
+        match self.guard.check_new_order(
+            context.portfolio_value,
+            context.current_position_notional,
+            new_order_notional,
+        ) {
+            Ok(()) => Ok(()),
+            Err(AlphaError::RiskViolation { rule, limit, actual }) => Err(RiskReport {
+                check_name: self.name().to_string(),
+                level: RiskLevel::Rejection,
+                threshold: limit.parse().unwrap_or(Decimal::ZERO),
+                attempted: actual.parse().unwrap_or(Decimal::ZERO),
+                message: format!("{} would breach leverage limits", rule),
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                signature: Vec::new(),
+            }),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiered_guard() -> MarginGuard {
+        let mut tables = HashMap::new();
+        tables.insert("BTCUSDT".to_string(), MaintenanceMarginTable {
+            brackets: vec![
+                MaintenanceMarginBracket {
+                    notional_ceiling: dec!(50_000),
+                    maintenance_margin_rate: dec!(0.01),
+                    maintenance_amount: Decimal::ZERO,
+                },
+                MaintenanceMarginBracket {
+                    notional_ceiling: dec!(250_000),
+                    maintenance_margin_rate: dec!(0.025),
+                    maintenance_amount: dec!(250),
+                },
+            ],
+        });
+
+        MarginGuard::new(MarginConfig {
+            max_global_leverage: dec!(10),
+            liquidation_safety_buffer: dec!(0.8),
+            default_maintenance_margin: dec!(0.05),
+            maintenance_margin_tables: tables,
+        })
+    }
+
+    #[test]
+    fn test_bracket_for_selects_tier_by_notional() {
+        let guard = tiered_guard();
+
+        // ضمن الشريحة الأولى (<= 50,000)
+        let small = guard.bracket_for("BTCUSDT", dec!(10_000));
+        assert_eq!(small.maintenance_margin_rate, dec!(0.01));
+
+        // يتجاوز الشريحة الأولى، يقع في الثانية (<= 250,000)
+        let large = guard.bracket_for("BTCUSDT", dec!(100_000));
+        assert_eq!(large.maintenance_margin_rate, dec!(0.025));
+        assert_eq!(large.maintenance_amount, dec!(250));
+    }
+
+    #[test]
+    fn test_bracket_for_unknown_symbol_falls_back_to_default_flat_rate() {
+        let guard = tiered_guard();
+        let bracket = guard.bracket_for("ETHUSDT", dec!(10_000));
+        assert_eq!(bracket.maintenance_margin_rate, dec!(0.05));
+        assert_eq!(bracket.maintenance_amount, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_calculate_liquidation_prices_long_matches_formula() {
+        let guard = tiered_guard();
+        // القيمة الإسمية هنا 100,000 -> الشريحة الثانية (mmr=0.025, maintenance_amount=250)
+        let entry_price = dec!(50_000);
+        let quantity = dec!(2);
+        let equity = dec!(20_000);
+
+        let (liq, bankruptcy) = guard.calculate_liquidation_prices("BTCUSDT", entry_price, quantity, equity, true);
+
+        let notional = entry_price * quantity;
+        let expected_liq = (notional - (equity - dec!(250))) / (quantity * (Decimal::ONE - dec!(0.025)));
+        let expected_bankruptcy = (notional - equity) / quantity;
+
+        assert_eq!(liq, expected_liq);
+        assert_eq!(bankruptcy, expected_bankruptcy);
+        // سعر التسييل الآمن يجب أن يبقى فوق سعر الإفلاس لمركز طويل (خط خروج أبكر)
+        assert!(liq > bankruptcy);
+    }
+
+    #[test]
+    fn test_calculate_liquidation_prices_short_matches_formula() {
+        let guard = tiered_guard();
+        let entry_price = dec!(50_000);
+        let quantity = dec!(2);
+        let equity = dec!(20_000);
+
+        let (liq, bankruptcy) = guard.calculate_liquidation_prices("BTCUSDT", entry_price, quantity, equity, false);
+
+        let notional = entry_price * quantity;
+        let expected_liq = (notional + (equity - dec!(250))) / (quantity * (Decimal::ONE + dec!(0.025)));
+        let expected_bankruptcy = (notional + equity) / quantity;
+
+        assert_eq!(liq, expected_liq);
+        assert_eq!(bankruptcy, expected_bankruptcy);
+        // سعر التسييل الآمن يجب أن يبقى دون سعر الإفلاس لمركز قصير (خط خروج أبكر للأعلى)
+        assert!(liq < bankruptcy);
+    }
+
+    #[test]
+    fn test_evaluate_health_uses_tier_selected_requirement_not_flat_rate() {
+        let guard = tiered_guard();
+        // قيمة إسمية 100,000 تقع في الشريحة الثانية (mmr=0.025, amount=250)
+        let health = guard.evaluate_health("BTCUSDT", dec!(20_000), dec!(50_000), dec!(2), true).unwrap();
+
+        let expected_margin_required = dec!(100_000) * dec!(0.025) - dec!(250);
+        assert_eq!(health.used_margin, expected_margin_required);
+        assert_eq!(health.margin_ratio, expected_margin_required / dec!(20_000));
+    }
 }
\ No newline at end of file
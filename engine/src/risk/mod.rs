@@ -9,12 +9,19 @@
  */
 
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use parking_lot::Mutex;
 use serde::{Serialize, Deserialize};
 use rust_decimal::Decimal;
 
 // تصدير الوحدات الفرعية (سنكتبها لاحقاً)
 pub mod engine;      // المحرك الرئيسي للمخاطر
 pub mod limits;      // قواعد الحدود (Limits)
+pub mod ledger;      // السجل الجنائي غير القابل للتلاعب (Hash Chain)
+pub mod signing;     // توقيع/تحقق Ed25519 منفصل للتقارير وأحداث الإيقاف
+pub mod margin_guard; // حارس الهامش والرافعة (Pre-Trade + Real-time Health)
+pub mod pre_trade_check; // سلسلة فحوص نزاهة الأمر (Sanity/Notional/Oracle Band/Self-Trade)
+pub mod circuit_breaker; // القاطع السريع لسلوك كارثي (Drawdown/Error Storm)
 
 // =================================================================
 // مفتاح الطوارئ العالمي (The Physical Kill Switch)
@@ -24,10 +31,78 @@ pub mod limits;      // قواعد الحدود (Limits)
 // إذا أصبح true، يتوقف المحرك فوراً عن قبول أي أوامر جديدة.
 pub static GLOBAL_EMERGENCY_STOP: AtomicBool = AtomicBool::new(false);
 
-/// تفعيل الإيقاف الطارئ (يستخدم عند اكتشاف اختراق أو انهيار سوقي)
+/// مفتاح توقيع العقدة، يُسجَّل مرة واحدة عند الإقلاع عبر `set_node_signing_key` (انظر
+/// `main.rs`). يبقى `None` في العمليات التي لا تهتم بالتوقيع الجنائي (اختبارات، أدوات محلية)،
+/// وعندها يُصدر `trigger_emergency_stop` تحذيراً بدل توقيع الحدث.
+static NODE_SIGNING_KEY: OnceLock<signing::SigningKey> = OnceLock::new();
+
+/// آخر شهادة إيقاف طارئ (Halt Attestation) موقَّعة صدرت عن هذه العقدة، إن وُجدت.
+static LAST_HALT_ATTESTATION: Mutex<Option<HaltAttestation>> = Mutex::new(None);
+
+/// شهادة موقَّعة (Detached Signature) على تفعيل الإيقاف الطارئ: تثبت بدليل تشفيري قابل
+/// للتحقق خارجياً (لا مجرد سطر سجل نصي) أن هذه العقدة تحديداً هي من أوقفت التداول ومتى ولماذا.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaltAttestation {
+    pub reason: String,
+    pub timestamp: u64,
+    pub signature: Vec<u8>,
+}
+
+impl HaltAttestation {
+    /// الحقول القانونية التي يُحسَب عليها التوقيع - كل شيء ماعدا `signature` نفسه.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        let mut unsigned = self.clone();
+        unsigned.signature = Vec::new();
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    /// يتحقق من أن التوقيع صادر فعلاً عن صاحب `pubkey` لهذه الشهادة تحديداً.
+    pub fn verify_signature(&self, pubkey: &[u8; 32]) -> bool {
+        if self.signature.is_empty() {
+            return false;
+        }
+        signing::verify_signature(pubkey, &self.canonical_bytes(), &self.signature)
+    }
+}
+
+/// يُسجَّل مرة واحدة عند الإقلاع (`main.rs`): مفتاح التوقيع الخاص بهذه العقدة، يُستخدم
+/// لاحقاً لتوقيع أي `HaltAttestation` يُصدرها `trigger_emergency_stop`. الاستدعاءات اللاحقة
+/// بعد أول تسجيل ناجح تُتجاهَل بصمت (سلوك `OnceLock` الطبيعي) - لا يصح تبديل مفتاح العقدة
+/// أثناء التشغيل.
+pub fn set_node_signing_key(key: signing::SigningKey) {
+    let _ = NODE_SIGNING_KEY.set(key);
+}
+
+/// آخر شهادة إيقاف طارئ موقَّعة أصدرتها هذه العقدة، إن فعّلت الإيقاف مطلقاً منذ الإقلاع.
+pub fn last_halt_attestation() -> Option<HaltAttestation> {
+    LAST_HALT_ATTESTATION.lock().clone()
+}
+
+/// تفعيل الإيقاف الطارئ (يستخدم عند اكتشاف اختراق أو انهيار سوقي). إن كان مفتاح توقيع
+/// العقدة مُسجَّلاً، يُصدر أيضاً `HaltAttestation` موقَّعة تثبت هوية العقدة ولحظة التفعيل،
+/// متاحة لاحقاً عبر `last_halt_attestation`.
 pub fn trigger_emergency_stop() {
     GLOBAL_EMERGENCY_STOP.store(true, Ordering::SeqCst);
     tracing::error!("RISK_ALERT: GLOBAL KILL SWITCH ACTIVATED! All trading halted.");
+
+    match NODE_SIGNING_KEY.get() {
+        Some(key) => {
+            let mut attestation = HaltAttestation {
+                reason: "GLOBAL_EMERGENCY_STOP".to_string(),
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                signature: Vec::new(),
+            };
+            attestation.signature = key.sign(&attestation.canonical_bytes());
+            tracing::error!(
+                "RISK_ALERT: HALT attestation signed by node key (pubkey {})",
+                hex::encode(key.verifying_key())
+            );
+            *LAST_HALT_ATTESTATION.lock() = Some(attestation);
+        }
+        None => {
+            tracing::warn!("RISK_ALERT: no node signing key registered - HALT attestation NOT signed");
+        }
+    }
 }
 
 /// التحقق مما إذا كان النظام في حالة إيقاف
@@ -58,6 +133,33 @@ pub struct RiskReport {
     pub attempted: Decimal,     // القيمة التي حاول الأمر تنفيذها
     pub message: String,        // رسالة بشرية
     pub timestamp: u64,
+    /// توقيع Ed25519 منفصل (Detached) على كل الحقول أعلاه، مُولَّد عبر `RiskReport::sign`.
+    /// فارغ (`Vec::new()`) لتقرير لم يُوقَّع بعد.
+    pub signature: Vec<u8>,
+}
+
+impl RiskReport {
+    /// الحقول القانونية التي يُحسَب عليها التوقيع - كل شيء ماعدا `signature` نفسه.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        let mut unsigned = self.clone();
+        unsigned.signature = Vec::new();
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    /// يوقِّع التقرير بمفتاح توقيع العقدة، ويضع التوقيع داخل `self.signature`.
+    pub fn sign(&mut self, key: &signing::SigningKey) {
+        self.signature = key.sign(&self.canonical_bytes());
+    }
+
+    /// يتحقق من أن التوقيع الحالي على هذا التقرير صادر فعلاً عن صاحب `pubkey` - يسمح
+    /// لمستهلكي التقرير (الدماغ، مدقّقون خارجيون) بالتأكد من أنه صدر عن هذا المحرك فعلاً
+    /// ولم يُزوَّر أو يُعدَّل بعد التوقيع.
+    pub fn verify_signature(&self, pubkey: &[u8; 32]) -> bool {
+        if self.signature.is_empty() {
+            return false;
+        }
+        signing::verify_signature(pubkey, &self.canonical_bytes(), &self.signature)
+    }
 }
 
 /// السمة (Trait) التي يجب أن يطبقها أي فحص مخاطر جديد.
@@ -80,4 +182,7 @@ pub struct RiskContext {
     pub open_orders_count: usize,
     pub daily_loss: Decimal,
     pub volatility_index: Decimal,
+    /// القيمة الإسمية لكل المراكز المفتوحة حالياً قبل هذا الأمر - يحتاجها `MarginGuardCheck`
+    /// لاشتقاق الرافعة المتوقعة دون أن يعرف `MarginGuard` نفسه شيئاً عن تمثيل الأمر.
+    pub current_position_notional: Decimal,
 }
\ No newline at end of file
@@ -10,11 +10,18 @@
  * =================================================================
  */
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+use parking_lot::RwLock;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use tracing::{warn, error, info};
 use crate::error::{AlphaError, AlphaResult};
 use crate::matching::{Order, Side, OrderType};
+use super::{RiskCheck, RiskContext, RiskLevel, RiskReport};
+
+/// عدد الأسعار المحفوظة في نافذة التقلب المتدحرجة لتوسيع النطاق تلقائياً
+const VOLATILITY_WINDOW: usize = 20;
 
 /// قيود التداول (يتم تحميلها لكل زوج عملات)
 #[derive(Debug, Clone)]
@@ -26,93 +33,564 @@ pub struct TradeConstraints {
     pub min_notional: Decimal,     // أقل قيمة للصفقة (Dust Limit)
     pub max_notional: Decimal,     // أقصى قيمة للصفقة (Fat Finger Limit)
     pub max_price_deviation: Decimal, // نسبة الانحراف المسموحة عن سعر السوق (e.g., 0.10 for 10%)
+    pub band_up: Decimal,   // اتساع نطاق الأوراكل للأعلى (e.g. 0.05 = 5% فوق سعر الأوراكل)
+    pub band_down: Decimal, // اتساع نطاق الأوراكل للأسفل (e.g. 0.05 = 5% تحت سعر الأوراكل)
 }
 
-pub struct PreTradeCheck {
-    constraints: TradeConstraints,
+/// الحالة الداخلية المشتركة لنطاق الأوراكل: آخر سعر معروف، ونافذة متدحرجة من الأسعار
+/// الأخيرة تُستخدم لتقدير التقلب وتوسيع النطاق تلقائياً عند اضطراب السوق.
+struct OracleState {
+    price: Decimal,
+    recent_prices: VecDeque<Decimal>,
 }
 
-impl PreTradeCheck {
-    pub fn new(constraints: TradeConstraints) -> Self {
-        Self { constraints }
+/// نطاق سعري مرتكز على الأوراكل (Oracle-Anchored Price Band)، على غرار فحص Mango/OpenBook
+/// لأوامر الشراء والبيع الجديدة. قابل للمشاركة (Clone رخيص عبر Arc) بين `PreTradeCheck`
+/// وطبقة المطابقة، بحيث يرفض الطرفان نفس الأوامر المنحرفة دون تكرار المنطق.
+#[derive(Clone)]
+pub struct OraclePriceBand {
+    state: Arc<RwLock<OracleState>>,
+    band_up: Decimal,
+    band_down: Decimal,
+}
+
+impl OraclePriceBand {
+    pub fn new(initial_oracle_price: Decimal, band_up: Decimal, band_down: Decimal) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(OracleState {
+                price: initial_oracle_price,
+                recent_prices: VecDeque::with_capacity(VOLATILITY_WINDOW),
+            })),
+            band_up,
+            band_down,
+        }
+    }
+
+    /// تحديث سعر الأوراكل/العلامة الحالي، بشكل مستقل عن أي أمر معين (يُستدعى من مغذّي الأسعار)
+    pub fn update_oracle_price(&self, price: Decimal) {
+        let mut state = self.state.write();
+        state.price = price;
+        state.recent_prices.push_back(price);
+        if state.recent_prices.len() > VOLATILITY_WINDOW {
+            state.recent_prices.pop_front();
+        }
+    }
+
+    /// آخر سعر أوراكل معروف
+    pub fn oracle_price(&self) -> Decimal {
+        self.state.read().price
+    }
+
+    /// نسبة التقلب الحالية: مدى آخر الأسعار (أعلى - أدنى) نسبة إلى متوسطها، 0 إذا لم تتوفر عينات كافية
+    fn volatility_ratio(&self) -> Decimal {
+        let state = self.state.read();
+        if state.recent_prices.len() < 2 {
+            return Decimal::ZERO;
+        }
+        let max = state.recent_prices.iter().copied().fold(Decimal::MIN, Decimal::max);
+        let min = state.recent_prices.iter().copied().fold(Decimal::MAX, Decimal::min);
+        let mean = state.recent_prices.iter().copied().sum::<Decimal>() / Decimal::from(state.recent_prices.len());
+        if mean <= Decimal::ZERO { Decimal::ZERO } else { (max - min) / mean }
+    }
+
+    /// حدود النطاق الحالية (أدنى سعر مقبول للبيع، أعلى سعر مقبول للشراء) حول `anchor`،
+    /// مع اتساع تلقائي للنطاقين يتناسب مع تقلب آخر الأسعار
+    pub fn bounds_around(&self, anchor: Decimal) -> (Decimal, Decimal) {
+        let widening = Decimal::ONE + self.volatility_ratio();
+        let lower = anchor * (Decimal::ONE - self.band_down * widening);
+        let upper = anchor * (Decimal::ONE + self.band_up * widening);
+        (lower, upper)
     }
 
-    /// التحقق الشامل من الأمر
-    /// reference_price: سعر السوق الحالي (Last Trade Price or Mid Price)
-    pub fn validate(&self, order: &Order, reference_price: Option<Decimal>) -> AlphaResult<()> {
-        
-        // 1. الفحص الأساسي (Sanity Check)
-        // لا يمكن أن يكون السعر أو الكمية صفر أو سالب (إلا في أوامر السوق قد يكون السعر 0)
+    /// حدود النطاق الحالية حول آخر سعر أوراكل معروف
+    pub fn bounds(&self) -> (Decimal, Decimal) {
+        self.bounds_around(self.oracle_price())
+    }
+
+    /// يرفض أمر شراء إن تجاوز سعره الحد الأعلى للنطاق، أو بيع إن نزل سعره عن الحد الأدنى
+    pub fn check(&self, side: Side, limit_price: Decimal, anchor: Decimal) -> AlphaResult<()> {
+        let (lower, upper) = self.bounds_around(anchor);
+
+        let (breached, bound) = match side {
+            Side::Bid => (limit_price > upper, upper),
+            Side::Ask => (limit_price < lower, lower),
+        };
+
+        if breached {
+            warn!(
+                "PRICE_BAND_VIOLATION: {:?} order at {} breaches oracle band [{}, {}] (anchor: {})",
+                side, limit_price, lower, upper, anchor
+            );
+            return Err(AlphaError::RiskViolation {
+                rule: "PRICE_BAND".into(),
+                limit: bound.to_string(),
+                actual: limit_price.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// =================================================================
+// سلسلة المسؤولية القابلة للإعداد (Chain of Responsibility)
+// =================================================================
+
+/// نتيجة قاعدة واحدة فشلت أو حذّرت: تحمل اسم القاعدة وحدّها وقيمتها الفعلية، كي يعكس
+/// السجل الجنائي بدقة أي قاعدة أطلقت الرفض/التحذير وبأي أرقام بالضبط.
+#[derive(Debug, Clone)]
+pub struct RuleFinding {
+    pub rule: String,
+    pub limit: String,
+    pub actual: String,
+}
+
+/// نتيجة فحص قاعدة واحدة ضمن السلسلة.
+pub enum ValidatorOutcome {
+    /// القاعدة راضية، تابع للقاعدة التالية
+    Pass,
+    /// تحذير لين: لا يوقف السلسلة لكنه يُسجَّل في `ValidationReport::warnings`
+    Warn(RuleFinding),
+    /// رفض قاطع: يوقف السلسلة فوراً عند أول حدوث
+    Reject(RuleFinding),
+}
+
+/// كل ما تحتاجه قاعدة واحدة لاتخاذ قرارها، دون أن تحمل نسخة خاصة بها من الإعداد المشترك.
+pub struct ValidationContext<'a> {
+    pub reference_price: Option<Decimal>,
+    pub constraints: &'a TradeConstraints,
+    pub oracle_band: &'a OraclePriceBand,
+    /// الرصيد المتاح لمالك الأمر، إن كان معروفاً لدى المتصل - يحتاجه `FixedCostValidator`
+    /// في وضع السيلو للتحقق من تغطية `notional + fixed_cost`.
+    pub available_balance: Option<Decimal>,
+}
+
+/// قاعدة واحدة مستقلة ضمن سلسلة `PreTradeCheck`. قابلة للتفعيل/التعطيل أو إعادة الترتيب
+/// من الإعداد عبر `PreTradeCheck::with_validators`، ويمكن إضافة قواعد جديدة دون لمس البقية.
+pub trait Validator: Send + Sync {
+    /// اسم القاعدة كما يظهر في بادئة رسائل السجل
+    fn name(&self) -> &'static str;
+    fn check(&self, order: &Order, ctx: &ValidationContext) -> ValidatorOutcome;
+}
+
+struct SanityValidator;
+impl Validator for SanityValidator {
+    fn name(&self) -> &'static str { "SANITY_CHECK" }
+
+    fn check(&self, order: &Order, _ctx: &ValidationContext) -> ValidatorOutcome {
+        // لا يمكن أن تكون الكمية صفراً أو سالبة، ولا سعر أمر Limit (أوامر السوق قد يكون سعرها 0)
         if order.quantity <= Decimal::ZERO {
-            return Err(self.reject("Zero or Negative Quantity", order.quantity));
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "ZERO_OR_NEGATIVE_QUANTITY".into(),
+                limit: "> 0".into(),
+                actual: order.quantity.to_string(),
+            });
         }
-        
         if order.order_type == OrderType::Limit && order.price <= Decimal::ZERO {
-            return Err(self.reject("Zero or Negative Price for Limit Order", order.price));
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "ZERO_OR_NEGATIVE_PRICE".into(),
+                limit: "> 0".into(),
+                actual: order.price.to_string(),
+            });
         }
+        ValidatorOutcome::Pass
+    }
+}
 
-        // 2. فحص الحدود الكمية (Quantity Limits)
-        if order.quantity < self.constraints.min_quantity {
-            return Err(self.reject("Quantity below minimum allowed", order.quantity));
+struct QuantityBoundsValidator;
+impl Validator for QuantityBoundsValidator {
+    fn name(&self) -> &'static str { "QUANTITY_BOUNDS" }
+
+    fn check(&self, order: &Order, ctx: &ValidationContext) -> ValidatorOutcome {
+        if order.quantity < ctx.constraints.min_quantity {
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "QUANTITY_BELOW_MINIMUM".into(),
+                limit: ctx.constraints.min_quantity.to_string(),
+                actual: order.quantity.to_string(),
+            });
         }
-        if order.quantity > self.constraints.max_quantity {
-            return Err(self.reject("Quantity exceeds maximum allowed", order.quantity));
+        if order.quantity > ctx.constraints.max_quantity {
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "QUANTITY_ABOVE_MAXIMUM".into(),
+                limit: ctx.constraints.max_quantity.to_string(),
+                actual: order.quantity.to_string(),
+            });
         }
+        ValidatorOutcome::Pass
+    }
+}
+
+/// فحص القيمة الإسمية (Price * Quantity)، أهم فحص لمنع "Fat Finger".
+struct NotionalValidator;
+impl Validator for NotionalValidator {
+    fn name(&self) -> &'static str { "NOTIONAL_CHECK" }
 
-        // 3. فحص القيمة الإسمية (Notional Value Check)
-        // Notional = Price * Quantity
-        // هذا أهم فحص لمنع "Fat Finger"
-        let estimated_price = if order.price > Decimal::ZERO { 
-            order.price 
-        } else { 
-            reference_price.unwrap_or(Decimal::ONE) // في حالة أمر السوق وعدم وجود مرجع
+    fn check(&self, order: &Order, ctx: &ValidationContext) -> ValidatorOutcome {
+        let estimated_price = if order.price > Decimal::ZERO {
+            order.price
+        } else {
+            ctx.reference_price.unwrap_or(Decimal::ONE) // في حالة أمر السوق وعدم وجود مرجع
         };
-        
+
         let notional = estimated_price * order.quantity;
 
-        if notional < self.constraints.min_notional {
-            return Err(self.reject("Order value too small (Dust)", notional));
+        if notional < ctx.constraints.min_notional {
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "DUST_NOTIONAL".into(),
+                limit: ctx.constraints.min_notional.to_string(),
+                actual: notional.to_string(),
+            });
+        }
+        if notional > ctx.constraints.max_notional {
+            error!("FAT_FINGER_DETECTED: Attempted notional {} exceeds limit {}", notional, ctx.constraints.max_notional);
+            return ValidatorOutcome::Reject(RuleFinding {
+                rule: "FAT_FINGER_PROTECTION".into(),
+                limit: ctx.constraints.max_notional.to_string(),
+                actual: notional.to_string(),
+            });
+        }
+        ValidatorOutcome::Pass
+    }
+}
+
+/// النطاق السعري المرتكز على الأوراكل (Oracle-Anchored Price Band): يمنع الشراء بسعر
+/// أعلى من oracle * (1 + band_up)، أو البيع بسعر أقل من oracle * (1 - band_down). إن مُرِّر
+/// `reference_price` صراحة (كما في أوامر السوق)، يُستخدم كمرساة لهذا الاستدعاء فقط؛ وإلا
+/// يُعتمد على آخر سعر أوراكل محدَّث بشكل مستقل عبر `OraclePriceBand::update_oracle_price`.
+struct PriceBandValidator;
+impl Validator for PriceBandValidator {
+    fn name(&self) -> &'static str { "ORACLE_PRICE_BAND" }
+
+    fn check(&self, order: &Order, ctx: &ValidationContext) -> ValidatorOutcome {
+        if order.order_type != OrderType::Limit {
+            return ValidatorOutcome::Pass;
+        }
+        let anchor = ctx.reference_price.unwrap_or_else(|| ctx.oracle_band.oracle_price());
+        if anchor <= Decimal::ZERO {
+            return ValidatorOutcome::Pass;
         }
+        match ctx.oracle_band.check(order.side, order.price, anchor) {
+            Ok(()) => ValidatorOutcome::Pass,
+            Err(AlphaError::RiskViolation { rule, limit, actual }) => {
+                ValidatorOutcome::Reject(RuleFinding { rule, limit, actual })
+            }
+            Err(_) => ValidatorOutcome::Pass, // لا يُصدر `OraclePriceBand::check` إلا `RiskViolation`
+        }
+    }
+}
+
+/// ما يحتاجه فاحص منع التداول مع النفس من العالم الخارجي: هل يوجد أمر مُقيم على الجانب
+/// المقابل يتقاطع سعرياً مع `(side, price)`، ومن مالكه؟ يُنفَّذ هذا العقد من طرف الدفتر
+/// الفعلي في بيئة الإنتاج؛ فصله هنا يبقي `PreTradeCheck` مستقلة عن أي عائلة أنواع أوامر
+/// بعينها (الأمر الوارد لهذا الفاحص ليس بالضرورة من نفس نوع أوامر الدفتر الذي يستضيفه).
+pub trait CrossingBookView: Send + Sync {
+    /// معرّف مالك (`owner_id`) أفضل أمر مُقيم على `side` يتقاطع مع `price`، إن وُجد.
+    fn crossing_owner(&self, side: Side, price: Decimal) -> Option<String>;
+}
+
+/// الإجراء المطلوب عند اكتشاف تداول مع النفس.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTradeAction {
+    /// رفض الأمر الوارد (الأحدث) بالكامل قبل وصوله للمطابقة
+    RejectIncoming,
+    /// نفس أثر `RejectIncoming` من زاوية هذا الفاحص (لا صلاحية له لإلغاء المُقيم نفسه)،
+    /// محفوظ كخيار صريح في الإعداد لتمييز النية في السجل الجنائي
+    CancelNewest,
+    /// السماح للأمر الوارد بالمتابعة نحو المطابقة، مع رفع تحذير لين يحمل هوية المُقيم
+    /// الذي يجب إلغاؤه؛ محرك المطابقة هو من ينفّذ الإلغاء الفعلي لذلك الأمر المقيم
+    CancelOldest,
+}
+
+struct SelfTradeValidator {
+    book_view: Arc<dyn CrossingBookView>,
+    action: SelfTradeAction,
+}
+
+impl Validator for SelfTradeValidator {
+    fn name(&self) -> &'static str { "SELF_TRADE_PREVENTION" }
+
+    fn check(&self, order: &Order, _ctx: &ValidationContext) -> ValidatorOutcome {
+        if order.order_type != OrderType::Limit {
+            return ValidatorOutcome::Pass; // فحص التقاطع يفترض وجود سعر محدد للمقارنة
+        }
+
+        let opposite = order.side.opposite();
+        let crossing_owner = match self.book_view.crossing_owner(opposite, order.price) {
+            Some(owner) => owner,
+            None => return ValidatorOutcome::Pass,
+        };
 
-        if notional > self.constraints.max_notional {
-            error!("FAT_FINGER_DETECTED: Attempted notional {} exceeds limit {}", notional, self.constraints.max_notional);
-            return Err(self.reject("FAT FINGER PROTECTION: Order value too high", notional));
+        if crossing_owner != order.owner_id {
+            return ValidatorOutcome::Pass;
         }
 
-        // 4. فحص الانحراف السعري (Price Band Check)
-        // يمنع الشراء بسعر أعلى بكثير من السوق، أو البيع بسعر أقل بكثير
-        if let Some(ref_price) = reference_price {
-            if ref_price > Decimal::ZERO && order.order_type == OrderType::Limit {
-                let deviation = (order.price - ref_price).abs() / ref_price;
-                
-                if deviation > self.constraints.max_price_deviation {
-                    let msg = format!(
-                        "Price deviation {:.2}% exceeds limit {:.2}% (Ref: {}, Order: {})",
-                        deviation * Decimal::from(100),
-                        self.constraints.max_price_deviation * Decimal::from(100),
-                        ref_price,
-                        order.price
+        let finding = RuleFinding {
+            rule: "SELF_TRADE_PREVENTION".into(),
+            limit: format!("{:?}", self.action),
+            actual: crossing_owner,
+        };
+
+        match self.action {
+            SelfTradeAction::RejectIncoming | SelfTradeAction::CancelNewest => ValidatorOutcome::Reject(finding),
+            SelfTradeAction::CancelOldest => ValidatorOutcome::Warn(finding),
+        }
+    }
+}
+
+/// إعداد وضع "السيلو" (Silo Mode): تكلفة ثابتة مُعلَنة مسبقاً تُحصَّل على كل صفقة بصرف
+/// النظر عن حجمها (محاسبة غاز-ثابت-لكل-معاملة)، مع سقف ميزانية يومي تراكمي يقارنه المشغّل
+/// بـ `PreTradeCheck::accumulated_fixed_cost` خارجياً ليقرر متى يُفعِّل `CircuitBreaker`.
+#[derive(Debug, Clone)]
+pub struct SiloConfig {
+    pub fixed_cost_per_tx: Decimal,
+    pub daily_cost_budget: Decimal,
+}
+
+/// يُحصِّل `fixed_cost_per_tx` على كل أمر يمر بالسلسلة، بصرف النظر عن حجمه، ويرفض الأمر إن
+/// كان الرصيد المتاح لا يغطي `notional + fixed_cost`. لا علاقة له بـ `daily_cost_budget` -
+/// ذلك سقف يراقبه المشغّل عبر `PreTradeCheck::accumulated_fixed_cost`، لا هذه القاعدة.
+struct FixedCostValidator {
+    fixed_cost_per_tx: Decimal,
+    ledger: Arc<RwLock<Decimal>>,
+}
+
+impl Validator for FixedCostValidator {
+    fn name(&self) -> &'static str { "FIXED_COST" }
+
+    fn check(&self, order: &Order, ctx: &ValidationContext) -> ValidatorOutcome {
+        let estimated_price = if order.price > Decimal::ZERO {
+            order.price
+        } else {
+            ctx.reference_price.unwrap_or(Decimal::ONE)
+        };
+        let required = estimated_price * order.quantity + self.fixed_cost_per_tx;
+
+        if let Some(balance) = ctx.available_balance {
+            if balance < required {
+                return ValidatorOutcome::Reject(RuleFinding {
+                    rule: "FIXED_COST".into(),
+                    limit: required.to_string(),
+                    actual: balance.to_string(),
+                });
+            }
+        }
+
+        *self.ledger.write() += self.fixed_cost_per_tx;
+        ValidatorOutcome::Pass
+    }
+}
+
+/// نتيجة تشغيل السلسلة كاملة على أمر واحد حتى أول رفض قاطع (أو حتى النهاية دون رفض):
+/// التحذيرات اللينة المتراكمة من كل القواعد التي مرّت، لعرضها في لوحة المراقبة أو السجل.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub warnings: Vec<RuleFinding>,
+}
+
+pub struct PreTradeCheck {
+    constraints: TradeConstraints,
+    oracle_band: OraclePriceBand,
+    validators: Vec<Box<dyn Validator>>,
+    /// مجموع تكاليف السيلو الثابتة المُحصَّلة تراكمياً (يبقى صفراً إن لم يُفعَّل `with_silo_mode`)
+    fixed_cost_ledger: Arc<RwLock<Decimal>>,
+}
+
+impl PreTradeCheck {
+    /// السلسلة الافتراضية: الفحوص الثابتة بالترتيب الذي كانت تُشغَّل به يدوياً من قبل.
+    /// بلا منع تداول مع النفس (يتطلب `CrossingBookView` حقيقياً من الدفتر المستضيف،
+    /// أضِفه عبر `with_self_trade_prevention`).
+    fn default_validators() -> Vec<Box<dyn Validator>> {
+        vec![
+            Box::new(SanityValidator),
+            Box::new(QuantityBoundsValidator),
+            Box::new(NotionalValidator),
+            Box::new(PriceBandValidator),
+        ]
+    }
+
+    pub fn new(constraints: TradeConstraints) -> Self {
+        let oracle_band = OraclePriceBand::new(Decimal::ZERO, constraints.band_up, constraints.band_down);
+        Self {
+            constraints,
+            oracle_band,
+            validators: Self::default_validators(),
+            fixed_cost_ledger: Arc::new(RwLock::new(Decimal::ZERO)),
+        }
+    }
+
+    /// إنشاء الفاحص مع مشاركة نطاق أوراكل موجود مسبقاً (بدلاً من إنشاء واحد جديد)، بحيث
+    /// يمكن لطبقة المطابقة استخدام نفس الكائن عبر `oracle_band()` لرفض الأوامر المتقاطعة كذلك
+    pub fn with_oracle_band(constraints: TradeConstraints, oracle_band: OraclePriceBand) -> Self {
+        Self {
+            constraints,
+            oracle_band,
+            validators: Self::default_validators(),
+            fixed_cost_ledger: Arc::new(RwLock::new(Decimal::ZERO)),
+        }
+    }
+
+    /// استبدال السلسلة بالكامل بترتيب/تشكيلة مخصصة من القواعد، محمّلة من الإعداد — يمكن
+    /// تعطيل قاعدة بحذفها، أو إعادة ترتيب الأولويات، أو إقحام قواعد مخصصة غير معرَّفة هنا.
+    pub fn with_validators(mut self, validators: Vec<Box<dyn Validator>>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    /// إضافة فاحص منع التداول مع النفس لنهاية السلسلة الحالية، متصلاً بلقطة حيّة لدفتر
+    /// الأوامر المستضيف عبر `CrossingBookView`.
+    pub fn with_self_trade_prevention(mut self, book_view: Arc<dyn CrossingBookView>, action: SelfTradeAction) -> Self {
+        self.validators.push(Box::new(SelfTradeValidator { book_view, action }));
+        self
+    }
+
+    /// تفعيل وضع السيلو لنهاية السلسلة الحالية: كل أمر لاحق يُحصَّل عنه `fixed_cost_per_tx`
+    /// إضافة لقيمته الإسمية المعتادة (انظر `accumulated_fixed_cost` لمتابعة السقف اليومي).
+    pub fn with_silo_mode(mut self, silo: SiloConfig) -> Self {
+        self.validators.push(Box::new(FixedCostValidator {
+            fixed_cost_per_tx: silo.fixed_cost_per_tx,
+            ledger: self.fixed_cost_ledger.clone(),
+        }));
+        self
+    }
+
+    /// نطاق الأوراكل المستخدم من هذا الفاحص، للمشاركة مع مكونات أخرى (مثل `OrderBook`)
+    pub fn oracle_band(&self) -> OraclePriceBand {
+        self.oracle_band.clone()
+    }
+
+    /// مجموع تكاليف السيلو الثابتة المُحصَّلة تراكمياً منذ الإنشاء (صفر إن لم يُفعَّل
+    /// `with_silo_mode`) - يقارنه المشغّل بـ `SiloConfig::daily_cost_budget` دورياً ليقرر متى
+    /// يُفعِّل `CircuitBreaker` يدوياً؛ هذا الفاحص لا يملك مرجعاً للقاطع نفسه.
+    pub fn accumulated_fixed_cost(&self) -> Decimal {
+        *self.fixed_cost_ledger.read()
+    }
+
+    /// التحقق الشامل من الأمر عبر كامل السلسلة: تتوقف عند أول رفض قاطع، وتتراكم التحذيرات
+    /// اللينة من كل قاعدة مرّت حتى تلك اللحظة في `ValidationReport`.
+    /// reference_price: سعر السوق الحالي (Last Trade Price or Mid Price).
+    /// available_balance: رصيد مالك الأمر المتاح، إن كان معروفاً - يحتاجه وضع السيلو فقط.
+    pub fn validate(
+        &self,
+        order: &Order,
+        reference_price: Option<Decimal>,
+        available_balance: Option<Decimal>,
+    ) -> AlphaResult<ValidationReport> {
+        let ctx = ValidationContext {
+            reference_price,
+            constraints: &self.constraints,
+            oracle_band: &self.oracle_band,
+            available_balance,
+        };
+
+        let mut report = ValidationReport::default();
+
+        for validator in &self.validators {
+            match validator.check(order, &ctx) {
+                ValidatorOutcome::Pass => {}
+                ValidatorOutcome::Warn(finding) => {
+                    warn!(
+                        "PRE_TRADE_WARN[{}]: rule={} limit={} actual={}",
+                        validator.name(), finding.rule, finding.limit, finding.actual
+                    );
+                    report.warnings.push(finding);
+                }
+                ValidatorOutcome::Reject(finding) => {
+                    warn!(
+                        "ORDER_REJECTED[{}]: rule={} limit={} actual={}",
+                        validator.name(), finding.rule, finding.limit, finding.actual
                     );
-                    warn!("PRICE_BAND_VIOLATION: {}", msg);
                     return Err(AlphaError::RiskViolation {
-                        rule: "PRICE_BAND".into(),
-                        limit: self.constraints.max_price_deviation.to_string(),
-                        actual: deviation.to_string(),
+                        rule: finding.rule,
+                        limit: finding.limit,
+                        actual: finding.actual,
                     });
                 }
             }
         }
 
-        Ok(())
+        Ok(report)
+    }
+}
+
+// =================================================================
+// تكامل خط أنابيب RiskCheck (Pipeline Integration)
+// =================================================================
+
+/// `PreTradeCheck` يطابق توقيع `RiskCheck` مباشرة (كلاهما يعمل على عائلة `crate::matching::Order`
+/// الخفيفة)، فلا حاجة لغلاف وسيط - فقط ترجمة `AlphaResult<ValidationReport>` إلى
+/// `Result<(), RiskReport>`: أول تحذير في `ValidationReport::warnings` (إن وُجد) يُرفَع كتقرير
+/// `Warning` واحد، ورفض السلسلة القاطع يُرفَع كتقرير `Rejection`.
+impl RiskCheck for PreTradeCheck {
+    fn name(&self) -> &'static str {
+        "PRE_TRADE_CHECK"
     }
 
-    /// مساعد لإنشاء خطأ الرفض
-    fn reject(&self, reason: &str, value: Decimal) -> AlphaError {
-        warn!("ORDER_REJECTED: {} (Value: {})", reason, value);
-        AlphaError::RiskViolation {
-            rule: "PRE_TRADE_VALIDATION".into(),
-            limit: "See Constraints".into(),
-            actual: value.to_string(),
+    fn check(&self, order: &Order, context: &RiskContext) -> Result<(), RiskReport> {
+        match self.validate(order, None, Some(context.portfolio_value)) {
+            Ok(report) => match report.warnings.first() {
+                // اسم القاعدة تحديداً (لا اسم الفاحص العام) هو check_name، كي يعكس التقرير
+                // الجنائي بدقة أي قاعدة أطلقت التحذير/الرفض (مثلاً "FIXED_COST")
+                Some(finding) => Err(RiskReport {
+                    check_name: finding.rule.clone(),
+                    level: RiskLevel::Warning,
+                    threshold: finding.limit.parse().unwrap_or(Decimal::ZERO),
+                    attempted: finding.actual.parse().unwrap_or(Decimal::ZERO),
+                    message: format!(
+                        "{} (+{} more warning(s))",
+                        finding.rule,
+                        report.warnings.len() - 1
+                    ),
+                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    signature: Vec::new(),
+                }),
+                None => Ok(()),
+            },
+            Err(AlphaError::RiskViolation { rule, limit, actual }) => Err(RiskReport {
+                check_name: rule.clone(),
+                level: RiskLevel::Rejection,
+                threshold: limit.parse().unwrap_or(Decimal::ZERO),
+                attempted: actual.parse().unwrap_or(Decimal::ZERO),
+                message: rule,
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                signature: Vec::new(),
+            }),
+            Err(_) => Ok(()),
         }
     }
+}
+
+// =================================================================
+// سجل القيود متعدد الرموز (Multi-Symbol Constraints Registry)
+// =================================================================
+
+/// سجل قيود التداول لكل رمز: يسمح بتسجيل رمز جديد كـ"مرآة" (Mirror) يرث قيود رمز أساسي
+/// مسجَّل مسبقاً بدل تكرار `TradeConstraints` بالكامل - يسرّع إدراج زوج عملات جديد في بيئات
+/// منشورة مقيَّدة (Silo Deployments) دون لمس إعداد الرمز الأساسي.
+#[derive(Default)]
+pub struct ConstraintsRegistry {
+    base: std::collections::HashMap<String, TradeConstraints>,
+    mirrors: std::collections::HashMap<String, String>,
+}
+
+impl ConstraintsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// يسجّل رمزاً أساسياً بقيوده الكاملة الخاصة به.
+    pub fn register(&mut self, symbol: impl Into<String>, constraints: TradeConstraints) {
+        self.base.insert(symbol.into(), constraints);
+    }
+
+    /// يسجّل `mirror_symbol` ليرث قيود `base_symbol` المسجَّل مسبقاً - لا ينسخ شيئاً، مجرد
+    /// رابط يُتبَع عند الاستعلام عبر `constraints_for`.
+    pub fn register_mirror(&mut self, mirror_symbol: impl Into<String>, base_symbol: impl Into<String>) {
+        self.mirrors.insert(mirror_symbol.into(), base_symbol.into());
+    }
+
+    /// يعيد القيود الفعلية لرمز معين، متتبعاً رابط المرآة إن وُجد.
+    pub fn constraints_for(&self, symbol: &str) -> Option<&TradeConstraints> {
+        let base_symbol = self.mirrors.get(symbol).map(String::as_str).unwrap_or(symbol);
+        self.base.get(base_symbol)
+    }
 }
\ No newline at end of file
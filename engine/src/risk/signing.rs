@@ -0,0 +1,46 @@
+/*
+ * ALPHA SOVEREIGN - NODE SIGNING KEY (FORENSIC NON-REPUDIATION)
+ * =================================================================
+ * Component Name: engine/src/risk/signing.rs
+ * Core Responsibility: توقيع/تحقق Ed25519 منفصل (Detached) لتقارير المخاطر وأحداث الإيقاف الطارئ (Risk Management Pillar).
+ * Design Pattern: Key Wrapper / Pure Functions
+ * Forensic Impact: يثبت هوية العقدة التي أصدرت تقريراً أو فعّلت مفتاح الإيقاف - دليل قابل للتحقق خارجياً، لا مجرد سجل نصي.
+ * =================================================================
+ */
+
+use ed25519_dalek::{Signature, Signer, SigningKey as Ed25519SigningKey, Verifier, VerifyingKey};
+
+/// غلاف حول مفتاح Ed25519 الخاص بهذه العقدة. يُحمَّل مرة واحدة عند الإقلاع (`main.rs`) من
+/// ملف/متغير بيئة، ثم يُسجَّل عالمياً عبر `risk::set_node_signing_key` ليستخدمه
+/// `trigger_emergency_stop` لاحقاً.
+pub struct SigningKey {
+    inner: Ed25519SigningKey,
+}
+
+impl SigningKey {
+    /// يبني مفتاحاً من 32 بايت خام (Seed) - الصيغة المعتادة لتخزين/تمرير مفتاح Ed25519 خاص.
+    pub fn from_bytes(seed: &[u8; 32]) -> Self {
+        Self { inner: Ed25519SigningKey::from_bytes(seed) }
+    }
+
+    /// المفتاح العام المقابل (32 بايت)، يُوزَّع على مستهلكي التقارير (الدماغ، المدقّقون)
+    /// ليتحققوا لاحقاً من أي توقيع عبر `verify_signature`.
+    pub fn verifying_key(&self) -> [u8; 32] {
+        self.inner.verifying_key().to_bytes()
+    }
+
+    /// يوقِّع رسالة خام (عادة الحقول القانونية المُسلسَلة لتقرير أو حدث إيقاف) ويُرجع
+    /// التوقيع كـ 64 بايت خام.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.inner.sign(message).to_bytes().to_vec()
+    }
+}
+
+/// يتحقق من أن `signature` توقيع Ed25519 صالح لـ `message` صادر فعلاً عن صاحب `pubkey`.
+/// يعيد `false` بصمت لأي مفتاح عام أو توقيع مُشوَّه (خطأ تنسيق)، لا يُفزِّع (panic) أبداً.
+pub fn verify_signature(pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(verifying_key) = VerifyingKey::from_bytes(pubkey) else { return false };
+    let Ok(sig_bytes) = <[u8; 64]>::try_from(signature) else { return false };
+    let signature = Signature::from_bytes(&sig_bytes);
+    verifying_key.verify(message, &signature).is_ok()
+}
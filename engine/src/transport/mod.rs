@@ -16,6 +16,11 @@ use crate::matching::{Order, Trade};
 // تعريف الوحدات الفرعية (سنقوم ببنائها لاحقاً)
 pub mod grpc;       // الاتصال الداخلي مع Python Brain
 pub mod exchange;   // الاتصال الخارجي مع البورصات (Binance/Kraken)
+pub mod shared_memory; // الخط السريع (Fast Lane) مع Python عبر حلقة ذاكرة مشتركة
+pub mod shm;        // ناقل Disruptor-style فوق SHM بأداء دون-الميكروثانية (يحل محل محاكاة transport_bench)
+pub mod quic;       // ناقل QUIC عبر الشبكة الواسعة (WAN) للدماغ/الواجهة البعيدة
+pub mod wal;        // السجل الدائم (Write-Ahead Log) للصندوق الأسود مع استعادة وإعادة تشغيل
+pub mod tcp_server; // وحدة تحكم الطوارئ الخام عبر TCP (HEALTH/SEQ/PANIC) - انظر main.rs
 
 // =================================================================
 // الأحداث الموحدة (Unified Transport Events)
@@ -25,13 +30,86 @@ pub mod exchange;   // الاتصال الخارجي مع البورصات (Bina
 /// يمثل أي حدث قادم من العالم الخارجي نحو المحرك.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum IngressEvent {
-    /// تحديث بيانات السوق (سعر، عمق، صفقة)
+    /// تحديث بيانات السوق (سعر، عمق، صفقة) - سعر واحد مبسط (يستخدمه المحاكي الداخلي)
     MarketData {
         symbol: String,
         price: rust_decimal::Decimal,
         timestamp: u64,
     },
-    
+
+    /// صفقة عامة وردت من تدفق `trade`/`aggTrade` الخاص بالبورصة (وليست تنفيذاً داخلياً لأمرنا)
+    Trade {
+        symbol: String,
+        price: rust_decimal::Decimal,
+        quantity: rust_decimal::Decimal,
+        is_buyer_maker: bool,
+        timestamp: u64,
+    },
+
+    /// أفضل عرض وطلب حاليين لرمز معين (`bookTicker`) - نحتفظ بالجانبين معاً بدل افتراض
+    /// العرض كسعر حالي وحيد
+    BookTicker {
+        symbol: String,
+        bid_price: rust_decimal::Decimal,
+        bid_qty: rust_decimal::Decimal,
+        ask_price: rust_decimal::Decimal,
+        ask_qty: rust_decimal::Decimal,
+        timestamp: u64,
+    },
+
+    /// لقطة عمق جزئية للسوق (`depth{levels}`)
+    DepthSnapshot {
+        symbol: String,
+        bids: Vec<(rust_decimal::Decimal, rust_decimal::Decimal)>,
+        asks: Vec<(rust_decimal::Decimal, rust_decimal::Decimal)>,
+        timestamp: u64,
+    },
+
+    /// إغلاق شمعة زمنية (`kline_{interval}`) لفاصل زمني معين
+    CandleClose {
+        symbol: String,
+        interval: String,
+        open: rust_decimal::Decimal,
+        high: rust_decimal::Decimal,
+        low: rust_decimal::Decimal,
+        close: rust_decimal::Decimal,
+        volume: rust_decimal::Decimal,
+        timestamp: u64,
+    },
+
+    /// إحصائية متجددة لآخر 24 ساعة (`ticker`) - تغيّر السعر والحجم
+    Ticker24h {
+        symbol: String,
+        last_price: rust_decimal::Decimal,
+        price_change_percent: rust_decimal::Decimal,
+        volume: rust_decimal::Decimal,
+        timestamp: u64,
+    },
+
+    /// تحديث دورة حياة أمر من تدفق بيانات المستخدم الخاص بالبورصة (`ORDER_TRADE_UPDATE`)
+    OrderUpdate {
+        exchange_order_id: u64,
+        client_order_id: String,
+        symbol: String,
+        /// نص حالة البورصة كما ورد (`NEW`, `PARTIALLY_FILLED`, `FILLED`, ...) - يُترجَم لاحقاً
+        /// عبر `Order::apply_exchange_fill`
+        exchange_status: String,
+        filled_quantity: rust_decimal::Decimal,
+        average_fill_price: rust_decimal::Decimal,
+        commission_paid: rust_decimal::Decimal,
+        commission_asset: String,
+        timestamp: u64,
+    },
+
+    /// تحديث رصيد/مركز من تدفق بيانات المستخدم الخاص بالبورصة (`ACCOUNT_UPDATE`)
+    AccountUpdate {
+        symbol: String,
+        quantity: rust_decimal::Decimal,
+        entry_price: rust_decimal::Decimal,
+        unrealized_pnl: rust_decimal::Decimal,
+        timestamp: u64,
+    },
+
     /// أمر تداول جديد قادم من الدماغ
     NewOrderRequest(Order),
     
@@ -0,0 +1,241 @@
+// QUIC/WebTransport WAN Bridge
+
+/*
+ * ALPHA SOVEREIGN - QUIC WAN TRANSPORTER
+ * =================================================================
+ * Component Name: engine/src/transport/quic.rs
+ * Core Responsibility: بثّ أحداث الدخول/الخروج إلى دماغ بايثون أو واجهة Flutter عبر شبكة حقيقية
+ *                       (WAN)، بخلاف `ZmqBridge` المقيّد بالشبكة المحلية (Integration Pillar).
+ * Design Pattern: Pub/Sub-over-QUIC (تيار موثوق لكل "موضوع" + بيانات غير موثوقة (Datagrams)
+ *                  لأحدث لقطات السوق، على غرار WebTransport)
+ * Forensic Impact: فقدان الاتصال هنا يعني أن الدماغ/الواجهة البعيدة تعمل على بيانات قديمة دون علم؛
+ *                   لذا يجب الإبلاغ فوراً بـ `AlphaError::NetworkError` بدل الفشل الصامت.
+ * =================================================================
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use quinn::{Connection, Endpoint, RecvStream, SendStream, ServerConfig};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::error::{AlphaError, AlphaResult};
+use crate::transport::{EgressEvent, IngressEvent, Transporter};
+
+/// المواضيع المنطقية المتفاوض عليها عند فتح الاتصال. كل موضوع يحصل على تيار ثنائي الاتجاه
+/// مستقل، فتجنّب أوامر متأخرة خلف تتبُّع تليمتري بطيء (Head-of-Line Blocking) مستحيل أصلاً
+/// لأنها تيارات QUIC منفصلة.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topic {
+    Orders,
+    Fills,
+    Telemetry,
+}
+
+impl Topic {
+    fn all() -> [Topic; 3] {
+        [Topic::Orders, Topic::Fills, Topic::Telemetry]
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Topic::Orders => "orders",
+            Topic::Fills => "fills",
+            Topic::Telemetry => "telemetry",
+        }
+    }
+}
+
+/// رسالة المصافحة الأولى (SETUP) المرسلة فور فتح كل تيار ثنائي الاتجاه: تعلن الطرف الآخر
+/// أي موضوع يحمله هذا التيار، فلا حاجة لأي توجيه (Dispatch) لكل رسالة لاحقة — التوجيه يحدث
+/// مرة واحدة فقط عند الفتح.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetupHandshake {
+    topic: Topic,
+}
+
+/// ناقل QUIC: يستقبل اتصالات من دماغ بايثون أو واجهة Flutter بعيدة عبر شبكة حقيقية،
+/// ويحتفظ بجلسة كل موضوع لكل اتصال نشط كي ترسل `send()` على التيار الصحيح مباشرة.
+pub struct QuicTransporter {
+    bind_address: String,
+    cert_chain_path: String,
+    private_key_path: String,
+
+    /// جلسات الإرسال المفتوحة حالياً، مفهرسة حسب الموضوع (قد يتصل أكثر من طرف بعيد بنفس الموضوع،
+    /// فنحتفظ بآخر تيار فُتح له كل موضوع — يكفي لبث الدماغ/الواجهة الحالية)
+    topic_senders: Arc<RwLock<HashMap<Topic, SendStream>>>,
+}
+
+impl QuicTransporter {
+    pub fn new(bind_address: impl Into<String>, cert_chain_path: impl Into<String>, private_key_path: impl Into<String>) -> Self {
+        Self {
+            bind_address: bind_address.into(),
+            cert_chain_path: cert_chain_path.into(),
+            private_key_path: private_key_path.into(),
+            topic_senders: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn build_server_config(&self) -> AlphaResult<ServerConfig> {
+        let cert_chain = std::fs::read(&self.cert_chain_path)
+            .map_err(|e| AlphaError::BootstrapError(format!("QUIC Cert Read Error: {}", e)))?;
+        let private_key = std::fs::read(&self.private_key_path)
+            .map_err(|e| AlphaError::BootstrapError(format!("QUIC Key Read Error: {}", e)))?;
+
+        let cert = rustls::Certificate(cert_chain);
+        let key = rustls::PrivateKey(private_key);
+
+        ServerConfig::with_single_cert(vec![cert], key)
+            .map_err(|e| AlphaError::BootstrapError(format!("QUIC TLS Config Error: {}", e)))
+    }
+
+    /// يقبل اتصالاً واحداً، يفتح تيّاراته الثنائية الواحد تلو الآخر، يقرأ مصافحة SETUP من كل
+    /// تيار ليعرف موضوعه، ثم يحوّل كل رسالة تصل عليه إلى `IngressEvent` نحو المحرك. كما يسجّل
+    /// جانب الإرسال (`SendStream`) لكل موضوع كي تستخدمه `send()` لاحقاً.
+    async fn handle_connection(
+        connection: Connection,
+        sender: mpsc::Sender<IngressEvent>,
+        topic_senders: Arc<RwLock<HashMap<Topic, SendStream>>>,
+    ) {
+        info!("QUIC: Peer connected from {}", connection.remote_address());
+
+        loop {
+            match connection.accept_bi().await {
+                Ok((send, recv)) => {
+                    let sender = sender.clone();
+                    let topic_senders = topic_senders.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = Self::handle_topic_stream(send, recv, sender, topic_senders).await {
+                            warn!("QUIC: Topic stream ended: {}", e);
+                        }
+                    });
+                }
+                Err(quinn::ConnectionError::ApplicationClosed(_)) | Err(quinn::ConnectionError::ConnectionClosed(_)) => {
+                    info!("QUIC: Peer {} disconnected", connection.remote_address());
+                    break;
+                }
+                Err(e) => {
+                    error!("QUIC: Connection error from {}: {}", connection.remote_address(), e);
+                    break;
+                }
+            }
+        }
+
+        // الاتصال فُقد: أي موضوع كان هذا الطرف يُرسل عليه لم يعد صالحاً. هذا وحده لا يكفي
+        // لإسقاط موضوع تستخدمه جهة بعيدة أخرى، لذا نعتمد على استبدال الإدخال عند إعادة الاتصال.
+    }
+
+    /// يقرأ مصافحة SETUP أولاً لمعرفة موضوع هذا التيار، يسجّل نصفه المُرسِل للاستخدام من
+    /// `send()`، ثم يتابع قراءة رسائل `IngressEvent` المتتالية عليه.
+    async fn handle_topic_stream(
+        send: SendStream,
+        mut recv: RecvStream,
+        sender: mpsc::Sender<IngressEvent>,
+        topic_senders: Arc<RwLock<HashMap<Topic, SendStream>>>,
+    ) -> AlphaResult<()> {
+        let setup_frame = recv
+            .read_chunk(4096, true)
+            .await
+            .map_err(|e| AlphaError::NetworkError(format!("QUIC Setup Read Error: {}", e)))?
+            .ok_or_else(|| AlphaError::NetworkError("QUIC Setup Stream Closed Before Handshake".into()))?;
+
+        let setup: SetupHandshake = serde_json::from_slice(&setup_frame.bytes)
+            .map_err(|e| AlphaError::ValidationFailed(format!("QUIC Malformed Setup Handshake: {}", e)))?;
+
+        info!("QUIC: Stream negotiated for topic '{}'", setup.topic.as_str());
+        topic_senders.write().insert(setup.topic, send);
+
+        loop {
+            match recv.read_chunk(64 * 1024, true).await {
+                Ok(Some(chunk)) => match serde_json::from_slice::<IngressEvent>(&chunk.bytes) {
+                    Ok(event) => {
+                        if sender.send(event).await.is_err() {
+                            warn!("QUIC: Engine channel closed, stopping topic '{}' reader", setup.topic.as_str());
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => warn!("QUIC: Malformed frame on topic '{}': {}", setup.topic.as_str(), e),
+                },
+                Ok(None) => return Ok(()),
+                Err(e) => return Err(AlphaError::NetworkError(format!("QUIC Read Error: {}", e))),
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transporter for QuicTransporter {
+    fn name(&self) -> &str {
+        "QUIC WAN Bridge"
+    }
+
+    /// يفتح نقطة نهاية QUIC ويقبل الاتصالات الواردة إلى ما لا نهاية، كل اتصال في مهمة Tokio
+    /// مستقلة كي لا يحجب اتصال بطيء بقية الأطراف البعيدة.
+    async fn start(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
+        let server_config = self.build_server_config()?;
+        let socket_addr = self
+            .bind_address
+            .parse()
+            .map_err(|e| AlphaError::ConfigMissing(format!("Invalid QUIC Bind Address: {}", e)))?;
+
+        let endpoint = Endpoint::server(server_config, socket_addr)
+            .map_err(|e| AlphaError::BootstrapError(format!("QUIC Endpoint Bind Error: {}", e)))?;
+
+        info!("QUIC_BRIDGE: Listening on {}", self.bind_address);
+
+        let topic_senders = self.topic_senders.clone();
+        tokio::spawn(async move {
+            while let Some(incoming) = endpoint.accept().await {
+                let sender = sender.clone();
+                let topic_senders = topic_senders.clone();
+                tokio::spawn(async move {
+                    match incoming.await {
+                        Ok(connection) => Self::handle_connection(connection, sender, topic_senders).await,
+                        Err(e) => error!("QUIC: Handshake failed: {}", e),
+                    }
+                });
+            }
+        });
+
+        Ok(())
+    }
+
+    /// يبثّ حدث خروج على تيار الموضوع الذي يطابقه. أحداث تنفيذ الصفقات وتحديثات الحالة تُرسل
+    /// على تيارات موثوقة (Orders/Fills)؛ تنبيهات المخاطر تُرسل عبر موضوع التليمتري. غياب أي
+    /// طرف بعيد مشترك في الموضوع المطلوب يُبلَّغ كفقدان اتصال وليس فشلاً صامتاً.
+    async fn send(&self, event: EgressEvent) -> AlphaResult<()> {
+        let topic = match &event {
+            EgressEvent::OrderExecution(_) => Topic::Fills,
+            EgressEvent::OrderStatusUpdate { .. } => Topic::Orders,
+            EgressEvent::RiskAlert { .. } => Topic::Telemetry,
+        };
+
+        let payload = serde_json::to_vec(&event)
+            .map_err(|e| AlphaError::ExecutionFailed(format!("QUIC JSON Serialize Error: {}", e)))?;
+
+        let mut senders = self.topic_senders.write();
+        let stream = senders
+            .get_mut(&topic)
+            .ok_or_else(|| AlphaError::NetworkError(format!("No QUIC peer subscribed to topic '{}'", topic.as_str())))?;
+
+        stream
+            .write_all(&payload)
+            .await
+            .map_err(|e| AlphaError::NetworkError(format!("QUIC Write Error: {}", e)))
+    }
+}
+
+/// بث لقطة سوق واحدة عبر Datagram غير موثوق: لتفادي تراكم لقطات قديمة خلف الشبكة، تفضيل
+/// إسقاط إطار متأخر على تخزينه، تماماً كما تصف WebTransport استخدام Datagrams للبيانات
+/// عالية التردد القابلة للإسقاط.
+pub async fn send_market_snapshot_datagram(connection: &Connection, event: &IngressEvent) -> AlphaResult<()> {
+    let payload = serde_json::to_vec(event)
+        .map_err(|e| AlphaError::ExecutionFailed(format!("QUIC Datagram Serialize Error: {}", e)))?;
+
+    connection
+        .send_datagram(payload.into())
+        .map_err(|e| AlphaError::NetworkError(format!("QUIC Datagram Send Error: {}", e)))
+}
@@ -1,168 +1,438 @@
-// Ring Buffer Logic
-
-/*
- * ALPHA SOVEREIGN - ZERO-COPY SHARED MEMORY TRANSPORT
- * =================================================================
- * Component Name: engine/src/transport/shared_memory.rs
- * Core Responsibility: نقل كتل البيانات الضخمة (L3 Market Data) بسرعة الضوء (Performance Pillar).
- * Design Pattern: Ring Buffer over Mapped File
- * Forensic Impact: لا يترك أثراً في سجلات الشبكة. التحقيق يتطلب تحليل تفريغ الذاكرة (Memory Dump).
- * =================================================================
- */
-
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::slice;
-use std::mem::size_of;
-use tracing::{info, error};
-use crate::error::{AlphaResult, AlphaError};
-
-// تعريف الثوابت
-const SHM_PATH: &str = "/dev/shm/alpha_fast_lane";
-const BUFFER_CAPACITY: usize = 1024 * 1024 * 10; // 10 MB Buffer
-const MAGIC_HEADER: u32 = 0xA1BDA1BD; // Magic Number (Alpha)
-
-/// رأس الذاكرة المشتركة (Metadata Header)
-/// يجب أن يتطابق هذا الهيكل بتاتاً (Bit-perfect) مع كود Python.
-#[repr(C)]
-pub struct ShmHeader {
-    pub magic: u32,            // للتحقق من أننا نقرأ الملف الصحيح
-    pub capacity: usize,       // حجم المخزن المؤقت
-    pub write_cursor: AtomicUsize, // مؤشر الكتابة (يتحكم فيه Rust)
-    pub read_cursor: AtomicUsize,  // مؤشر القراءة (يتحكم فيه Python)
-    pub sequence: AtomicUsize,     // رقم تسلسلي للكشف عن فقدان الحزم
-}
-
-/// هيكل الرسالة داخل المخزن (Slot)
-#[repr(C)]
-struct DataSlot {
-    length: u32,
-    payload: [u8; 1024], // حجم ثابت لكل رسالة (Fixed Size Slot) للسرعة
-}
-
-pub struct SharedMemoryTransport {
-    // مؤشر خام لمنطقة الذاكرة (Raw Pointer)
-    header: *mut ShmHeader,
-    data_ptr: *mut u8,
-    
-    // الاحتفاظ بمقبض الملف لمنع إغلاقه
-    _mmap: memmap2::MmapMut,
-}
-
-// تنفيذ Send للتأكد من إمكانية نقل الكائن بين الخيوط (نحن نتحمل مسؤولية الأمان)
-unsafe impl Send for SharedMemoryTransport {}
-unsafe impl Sync for SharedMemoryTransport {}
-
-impl SharedMemoryTransport {
-    /// إنشاء أو فتح منطقة الذاكرة المشتركة
-    pub fn new(create_new: bool) -> AlphaResult<Self> {
-        use std::fs::OpenOptions;
-        use memmap2::MmapMut;
-
-        // 1. فتح الملف (الذي يمثل الذاكرة)
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(create_new)
-            .open(SHM_PATH)
-            .map_err(|e| AlphaError::BootstrapError(format!("SHM File Open Error: {}", e)))?;
-
-        // تعيين الحجم إذا كنا نحن من ينشئه
-        if create_new {
-            file.set_len((size_of::<ShmHeader>() + BUFFER_CAPACITY) as u64)
-                .map_err(|e| AlphaError::BootstrapError(format!("SHM Truncate Error: {}", e)))?;
-        }
-
-        // 2. تعيين الذاكرة (Memory Mapping)
-        let mut mmap = unsafe { 
-            MmapMut::map_mut(&file)
-                .map_err(|e| AlphaError::BootstrapError(format!("Mmap Error: {}", e)))? 
-        };
-
-        // 3. حساب المؤشرات
-        let base_ptr = mmap.as_mut_ptr();
-        let header_ptr = base_ptr as *mut ShmHeader;
-        
-        // مؤشر البيانات يبدأ بعد الـ Header مباشرة
-        let data_offset = size_of::<ShmHeader>();
-        let data_ptr = unsafe { base_ptr.add(data_offset) };
-
-        // 4. تهيئة الرأس (إذا كان جديداً)
-        if create_new {
-            unsafe {
-                (*header_ptr).magic = MAGIC_HEADER;
-                (*header_ptr).capacity = BUFFER_CAPACITY;
-                (*header_ptr).write_cursor = AtomicUsize::new(0);
-                (*header_ptr).read_cursor = AtomicUsize::new(0);
-                (*header_ptr).sequence = AtomicUsize::new(0);
-            }
-            info!("SHM: Initialized new shared memory region at {}", SHM_PATH);
-        } else {
-            // التحقق من الـ Magic
-            unsafe {
-                if (*header_ptr).magic != MAGIC_HEADER {
-                    return Err(AlphaError::Fatal("SHM Magic Mismatch! Possible memory corruption.".into()));
-                }
-            }
-        }
-
-        Ok(Self {
-            header: header_ptr,
-            data_ptr,
-            _mmap: mmap,
-        })
-    }
-
-    /// كتابة بيانات (Zero-Copy-ish)
-    /// نحن نكتب مباشرة في الـ RAM المخصصة لـ Python.
-    pub fn write_bytes(&self, data: &[u8]) -> AlphaResult<()> {
-        if data.len() > 1024 {
-            return Err(AlphaError::Internal("Data too large for SHM slot".into()));
-        }
-
-        unsafe {
-            let header = &*self.header;
-            
-            // 1. حساب الموقع في الحلقة (Ring Buffer Logic)
-            let current_write = header.write_cursor.load(Ordering::Acquire);
-            let next_write = (current_write + 1) % (BUFFER_CAPACITY / size_of::<DataSlot>());
-            
-            // التحقق من الامتلاء (هل الكتابة ستتجاوز القراءة؟)
-            let current_read = header.read_cursor.load(Ordering::Acquire);
-            if next_write == current_read {
-                // Buffer Full - Drop Strategy or Spin Wait?
-                // في HFT، نفضل إسقاط القديم (Drop Oldest) أو التحذير، الانتظار يعني الموت.
-                // هنا سنقوم برمي خطأ للتبسيط.
-                return Err(AlphaError::Internal("SHM Ring Buffer Full! Python is too slow.".into()));
-            }
-
-            // 2. الوصول للموقع في الذاكرة
-            let slot_ptr = (self.data_ptr as *mut DataSlot).add(current_write);
-            
-            // 3. كتابة البيانات (Memcpy سريع جداً داخل الـ CPU Cache)
-            (*slot_ptr).length = data.len() as u32;
-            std::ptr::copy_nonoverlapping(data.as_ptr(), (*slot_ptr).payload.as_mut_ptr(), data.len());
-
-            // 4. تحديث المؤشرات (Commit)
-            // نستخدم Release لضمان أن البيانات كتبت فعلاً قبل تحديث المؤشر
-            header.sequence.fetch_add(1, Ordering::Release);
-            header.write_cursor.store(next_write, Ordering::Release);
-        }
-
-        Ok(())
-    }
-
-    /// قراءة الإحصائيات (للمراقبة)
-    pub fn get_stats(&self) -> String {
-        unsafe {
-            let header = &*self.header;
-            format!(
-                "SHM Stats [Seq: {}, Write: {}, Read: {}, Lag: {}]", 
-                header.sequence.load(Ordering::Relaxed),
-                header.write_cursor.load(Ordering::Relaxed),
-                header.read_cursor.load(Ordering::Relaxed),
-                // حساب الفرق (Lag)
-                (header.write_cursor.load(Ordering::Relaxed) + 1000 - header.read_cursor.load(Ordering::Relaxed)) % 1000 // تقريبي
-            )
-        }
-    }
-}
\ No newline at end of file
+// Ring Buffer Logic
+
+/*
+ * ALPHA SOVEREIGN - ZERO-COPY SHARED MEMORY TRANSPORT
+ * =================================================================
+ * Component Name: engine/src/transport/shared_memory.rs
+ * Core Responsibility: نقل كتل البيانات الضخمة (L3 Market Data) بسرعة الضوء (Performance Pillar).
+ * Design Pattern: Ring Buffer over Mapped File (LMAX-Disruptor style, writer never blocks)
+ * Forensic Impact: لا يترك أثراً في سجلات الشبكة. التحقيق يتطلب تحليل تفريغ الذاكرة (Memory Dump).
+ * =================================================================
+ */
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::mem::size_of;
+use tracing::{info, warn};
+use crate::error::{AlphaResult, AlphaError};
+
+// تعريف الثوابت
+const SHM_PATH: &str = "/dev/shm/alpha_fast_lane";
+const BUFFER_CAPACITY: usize = 1024 * 1024 * 10; // 10 MB Buffer
+const MAGIC_HEADER: u32 = 0xA1BDA1BD; // Magic Number (Alpha)
+
+/// طول رأس كل سجل داخل الحلقة: 4 بايت طول الحمولة (u32 LE) + 8 بايت رقم تسلسلي (u64 LE)
+const RECORD_HEADER_LEN: usize = 12;
+
+/// علامة "الالتفاف" (Wrap Sentinel): تُكتب في حقل الطول عندما لا تتسع السجلة التالية قبل
+/// نهاية المخزن الفعلية، لتخبر القارئ بتجاوز بقية المساحة والمتابعة من البداية (offset 0).
+const SENTINEL_MARKER: u32 = u32::MAX;
+
+/// سياسة التعامل عند امتلاء الحلقة (عندما لا يلحق المستهلك بالكاتب)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// ارفض الكتابة وأرجع خطأ (السلوك الافتراضي القديم، آمن لكنه قد يُسقط بيانات حية عبر إعادة المحاولة في المتصل)
+    FailFast,
+    /// قدّم مؤشر القراءة قسراً متخطياً أقدم السجلات لإفساح المجال، فلا يتوقف الكاتب أبداً.
+    /// القارئ يكتشف ما أُسقط لاحقاً من فجوة الرقم التسلسلي، لا من إشعار مباشر.
+    DropOldest,
+}
+
+/// رأس الذاكرة المشتركة (Metadata Header)
+///
+/// التخطيط بالبايت (Little Endian على جميع المنصات المدعومة)، يجب أن يتطابق تماماً مع
+/// كود Python الذي يفك ترميزه (مثال: `struct.Struct("<IIQQQ")`):
+///
+/// | Offset | Size | Field         | Meaning                                                    |
+/// |--------|------|---------------|------------------------------------------------------------|
+/// | 0      | 4    | magic         | توقيع للتحقق من صحة الملف (0xA1BDA1BD)                      |
+/// | 4      | 4    | capacity      | حجم منطقة البيانات بالبايت (بعد هذا الرأس)                  |
+/// | 8      | 8    | write_cursor  | إجمالي البايتات المُنتَجة في الحلقة منذ الإنشاء (غير مُلفوفة، offset الفعلي = القيمة % capacity) |
+/// | 16     | 8    | read_cursor   | إجمالي البايتات المُستهلكة أو المُسقطة قسراً منذ الإنشاء (نفس منطق الالتفاف) |
+/// | 24     | 8    | sequence      | الرقم التسلسلي الذي سيُعطى للسجل التالي عند كتابته          |
+///
+/// كل سجل بيانات في منطقة البيانات نفسها مكتوب كـ: `[length: u32 LE][sequence: u64 LE][payload: length bytes]`,
+/// أو كسجل علامة التفاف مكوّن من 4 بايت فقط بقيمة `u32::MAX` عندما لا تتسع السجلة الحقيقية التالية.
+#[repr(C)]
+pub struct ShmHeader {
+    pub magic: u32,
+    pub capacity: u32,
+    pub write_cursor: AtomicU64,
+    pub read_cursor: AtomicU64,
+    pub sequence: AtomicU64,
+}
+
+pub struct SharedMemoryTransport {
+    // مؤشر خام لمنطقة الذاكرة (Raw Pointer)
+    header: *mut ShmHeader,
+    data_ptr: *mut u8,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+
+    // الاحتفاظ بمقبض الملف لمنع إغلاقه
+    _mmap: memmap2::MmapMut,
+}
+
+// تنفيذ Send للتأكد من إمكانية نقل الكائن بين الخيوط (نحن نتحمل مسؤولية الأمان)
+unsafe impl Send for SharedMemoryTransport {}
+unsafe impl Sync for SharedMemoryTransport {}
+
+impl SharedMemoryTransport {
+    /// إنشاء أو فتح منطقة الذاكرة المشتركة القياسية
+    pub fn new(create_new: bool) -> AlphaResult<Self> {
+        Self::new_at(SHM_PATH, create_new)
+    }
+
+    /// تحديد سياسة الفيضان (Overflow Policy)، الافتراضي هو `FailFast`
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// نفس `new` لكن مع مسار ملف مخصص (يُستخدم في الاختبارات لتفادي التشارك مع ملف الإنتاج)
+    fn new_at(path: &str, create_new: bool) -> AlphaResult<Self> {
+        use std::fs::OpenOptions;
+        use memmap2::MmapMut;
+
+        // 1. فتح الملف (الذي يمثل الذاكرة)
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(create_new)
+            .open(path)
+            .map_err(|e| AlphaError::BootstrapError(format!("SHM File Open Error: {}", e)))?;
+
+        // تعيين الحجم إذا كنا نحن من ينشئه
+        if create_new {
+            file.set_len((size_of::<ShmHeader>() + BUFFER_CAPACITY) as u64)
+                .map_err(|e| AlphaError::BootstrapError(format!("SHM Truncate Error: {}", e)))?;
+        }
+
+        // 2. تعيين الذاكرة (Memory Mapping)
+        let mut mmap = unsafe {
+            MmapMut::map_mut(&file)
+                .map_err(|e| AlphaError::BootstrapError(format!("Mmap Error: {}", e)))?
+        };
+
+        // 3. حساب المؤشرات
+        let base_ptr = mmap.as_mut_ptr();
+        let header_ptr = base_ptr as *mut ShmHeader;
+
+        // مؤشر البيانات يبدأ بعد الـ Header مباشرة
+        let data_offset = size_of::<ShmHeader>();
+        let data_ptr = unsafe { base_ptr.add(data_offset) };
+
+        // 4. تهيئة الرأس (إذا كان جديداً)
+        if create_new {
+            unsafe {
+                (*header_ptr).magic = MAGIC_HEADER;
+                (*header_ptr).capacity = BUFFER_CAPACITY as u32;
+                (*header_ptr).write_cursor = AtomicU64::new(0);
+                (*header_ptr).read_cursor = AtomicU64::new(0);
+                (*header_ptr).sequence = AtomicU64::new(0);
+            }
+            info!("SHM: Initialized new shared memory region at {}", path);
+        } else {
+            // التحقق من الـ Magic
+            unsafe {
+                if (*header_ptr).magic != MAGIC_HEADER {
+                    return Err(AlphaError::Fatal("SHM Magic Mismatch! Possible memory corruption.".into()));
+                }
+            }
+        }
+
+        let capacity = unsafe { (*header_ptr).capacity as usize };
+
+        Ok(Self {
+            header: header_ptr,
+            data_ptr,
+            capacity,
+            overflow_policy: OverflowPolicy::FailFast,
+            _mmap: mmap,
+        })
+    }
+
+    /// كتابة سجل متغير الطول (Zero-Copy-ish) مسبوق بطول وتسلسل، بدلاً من فتحة ثابتة 1 كيلوبايت.
+    /// الكاتب لا يتوقف أبداً تحت سياسة `DropOldest`؛ يتقدّم مؤشر القراءة قسراً بدلاً من ذلك.
+    pub fn write_bytes(&self, data: &[u8]) -> AlphaResult<()> {
+        let record_len = (RECORD_HEADER_LEN + data.len()) as u64;
+        if record_len > self.capacity as u64 {
+            return Err(AlphaError::ValidationFailed(
+                "Payload (plus record header) does not fit in the SHM ring at all".into(),
+            ));
+        }
+
+        unsafe {
+            let header = &*self.header;
+            let capacity = self.capacity as u64;
+
+            loop {
+                let write_pos = header.write_cursor.load(Ordering::Acquire);
+                let offset = (write_pos % capacity) as usize;
+                let remaining_to_end = capacity - offset as u64;
+
+                if remaining_to_end < record_len {
+                    // السجلة لا تتسع قبل نهاية المخزن الفعلية: أفسح المجال ثم اكتب علامة التفاف والتف
+                    self.ensure_room(header, write_pos, remaining_to_end, capacity)?;
+                    if remaining_to_end >= 4 {
+                        self.write_u32_at(offset, SENTINEL_MARKER);
+                    }
+                    header.write_cursor.store(write_pos + remaining_to_end, Ordering::Release);
+                    continue;
+                }
+
+                self.ensure_room(header, write_pos, record_len, capacity)?;
+
+                let seq = header.sequence.fetch_add(1, Ordering::AcqRel);
+                self.write_u32_at(offset, data.len() as u32);
+                self.write_u64_at(offset + 4, seq);
+                std::ptr::copy_nonoverlapping(
+                    data.as_ptr(),
+                    self.data_ptr.add(offset + RECORD_HEADER_LEN),
+                    data.len(),
+                );
+
+                // Release يضمن أن كل ما سبق (الطول والتسلسل والحمولة) مرئي قبل تقدّم المؤشر
+                header.write_cursor.store(write_pos + record_len, Ordering::Release);
+                return Ok(());
+            }
+        }
+    }
+
+    /// يضمن توفر `needed` بايت حرة أمام `write_pos`، مطبّقاً سياسة الفيضان المهيّأة عند الحاجة
+    unsafe fn ensure_room(&self, header: &ShmHeader, write_pos: u64, needed: u64, capacity: u64) -> AlphaResult<()> {
+        loop {
+            let read_pos = header.read_cursor.load(Ordering::Acquire);
+            let used = write_pos - read_pos;
+            let free = capacity - used;
+            if free >= needed {
+                return Ok(());
+            }
+
+            match self.overflow_policy {
+                OverflowPolicy::FailFast => {
+                    return Err(AlphaError::ExecutionFailed(
+                        "SHM Ring Buffer Full! Consumer is lagging and FailFast policy is active.".into(),
+                    ));
+                }
+                OverflowPolicy::DropOldest => {
+                    // اقرأ رأس أقدم سجل موجود عند read_pos لمعرفة طوله الحقيقي وتخطيه بالكامل
+                    let offset = (read_pos % capacity) as usize;
+                    let length = self.read_u32_at(offset);
+                    let skip = if length == SENTINEL_MARKER {
+                        capacity - (read_pos % capacity)
+                    } else {
+                        (RECORD_HEADER_LEN + length as usize) as u64
+                    };
+                    header.read_cursor.store(read_pos + skip, Ordering::Release);
+                    warn!("SHM: DropOldest evicted a stale record to make room for a new write");
+                }
+            }
+        }
+    }
+
+    unsafe fn write_u32_at(&self, offset: usize, value: u32) {
+        std::ptr::copy_nonoverlapping(value.to_le_bytes().as_ptr(), self.data_ptr.add(offset), 4);
+    }
+
+    unsafe fn write_u64_at(&self, offset: usize, value: u64) {
+        std::ptr::copy_nonoverlapping(value.to_le_bytes().as_ptr(), self.data_ptr.add(offset), 8);
+    }
+
+    unsafe fn read_u32_at(&self, offset: usize) -> u32 {
+        let mut buf = [0u8; 4];
+        std::ptr::copy_nonoverlapping(self.data_ptr.add(offset), buf.as_mut_ptr(), 4);
+        u32::from_le_bytes(buf)
+    }
+
+    unsafe fn read_u64_at(&self, offset: usize) -> u64 {
+        let mut buf = [0u8; 8];
+        std::ptr::copy_nonoverlapping(self.data_ptr.add(offset), buf.as_mut_ptr(), 8);
+        u64::from_le_bytes(buf)
+    }
+
+    /// إنشاء قارئ يتتبع آخر رقم تسلسلي رآه بشكل مستقل، لاكتشاف الفجوات الناتجة عن `DropOldest`
+    pub fn reader(&self) -> ShmReader<'_> {
+        ShmReader { transport: self, last_sequence: None }
+    }
+
+    /// قراءة الإحصائيات (للمراقبة)
+    pub fn get_stats(&self) -> String {
+        unsafe {
+            let header = &*self.header;
+            let write_pos = header.write_cursor.load(Ordering::Relaxed);
+            let read_pos = header.read_cursor.load(Ordering::Relaxed);
+            format!(
+                "SHM Stats [Seq: {}, Write: {}, Read: {}, Lag (bytes): {}]",
+                header.sequence.load(Ordering::Relaxed),
+                write_pos,
+                read_pos,
+                write_pos - read_pos
+            )
+        }
+    }
+}
+
+/// نتيجة قراءة سجل واحد من الحلقة
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadResult {
+    pub data: Vec<u8>,
+    /// عدد الرسائل التي فُقدت بين هذه القراءة والسابقة (من فجوة الرقم التسلسلي، وليس من إشعار مباشر)
+    pub dropped: u64,
+}
+
+/// قارئ بحالة محلية (آخر رقم تسلسلي رآه)، يكتشف الفجوات الناتجة عن `OverflowPolicy::DropOldest`
+/// بمقارنة تسلسل كل سجل جديد بما كان متوقعاً.
+pub struct ShmReader<'a> {
+    transport: &'a SharedMemoryTransport,
+    last_sequence: Option<u64>,
+}
+
+impl<'a> ShmReader<'a> {
+    /// يقرأ السجل التالي المتاح إن وُجد، متخطياً أي علامات التفاف تلقائياً
+    pub fn read_next(&mut self) -> Option<ReadResult> {
+        unsafe {
+            let header = &*self.transport.header;
+            let capacity = self.transport.capacity as u64;
+
+            loop {
+                let read_pos = header.read_cursor.load(Ordering::Acquire);
+                let write_pos = header.write_cursor.load(Ordering::Acquire);
+                if read_pos >= write_pos {
+                    return None; // لا بيانات جديدة بعد
+                }
+
+                let offset = (read_pos % capacity) as usize;
+                let length = self.transport.read_u32_at(offset);
+
+                if length == SENTINEL_MARKER {
+                    let skip = capacity - (read_pos % capacity);
+                    header.read_cursor.store(read_pos + skip, Ordering::Release);
+                    continue;
+                }
+
+                let sequence = self.transport.read_u64_at(offset + 4);
+                let mut data = vec![0u8; length as usize];
+                std::ptr::copy_nonoverlapping(
+                    self.transport.data_ptr.add(offset + RECORD_HEADER_LEN),
+                    data.as_mut_ptr(),
+                    length as usize,
+                );
+
+                header.read_cursor.store(read_pos + RECORD_HEADER_LEN as u64 + length as u64, Ordering::Release);
+
+                let dropped = match self.last_sequence {
+                    Some(prev) if sequence > prev + 1 => sequence - prev - 1,
+                    _ => 0,
+                };
+                self.last_sequence = Some(sequence);
+
+                return Some(ReadResult { data, dropped });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_path(tag: &str) -> String {
+        use std::sync::atomic::AtomicU64;
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!("/tmp/alpha_shm_test_{}_{}_{}", std::process::id(), tag, n)
+    }
+
+    fn new_test_transport(tag: &str) -> SharedMemoryTransport {
+        SharedMemoryTransport::new_at(&unique_test_path(tag), true).unwrap()
+    }
+
+    #[test]
+    fn test_write_read_roundtrip_preserves_order_and_sequence() {
+        let transport = new_test_transport("roundtrip");
+        transport.write_bytes(b"first").unwrap();
+        transport.write_bytes(b"second").unwrap();
+
+        let mut reader = transport.reader();
+        let r1 = reader.read_next().unwrap();
+        assert_eq!(r1.data, b"first");
+        assert_eq!(r1.dropped, 0);
+
+        let r2 = reader.read_next().unwrap();
+        assert_eq!(r2.data, b"second");
+        assert_eq!(r2.dropped, 0);
+
+        assert!(reader.read_next().is_none());
+    }
+
+    #[test]
+    fn test_fail_fast_rejects_write_when_consumer_lags() {
+        // الحلقة القياسية (10 ميجا) دون أي قراءة، فتمتلئ بعد بضع كتابات كبيرة
+        let transport = SharedMemoryTransport::new_at(&unique_test_path("failfast"), true)
+            .unwrap();
+
+        // نملأ الحلقة الفعلية (10 ميجا) بسجلات كبيرة نسبياً حتى تمتلئ دون قراءة
+        let payload = vec![0u8; 1024 * 1024]; // 1 MiB لكل سجل
+        let mut writes = 0;
+        loop {
+            match transport.write_bytes(&payload) {
+                Ok(_) => writes += 1,
+                Err(_) => break,
+            }
+            if writes > 20 {
+                panic!("Ring never reported full under FailFast");
+            }
+        }
+        assert!(writes > 0, "At least one write should have succeeded before the ring filled");
+    }
+
+    #[test]
+    fn test_drop_oldest_never_blocks_and_reports_gap_on_read() {
+        let transport = SharedMemoryTransport::new_at(&unique_test_path("dropoldest"), true)
+            .unwrap()
+            .with_overflow_policy(OverflowPolicy::DropOldest);
+
+        // سجلات صغيرة لكن كثيرة، تكفي لملء حلقة 10 ميجا وتجاوزها عدة مرات (يضمن أيضاً اختبار الالتفاف)
+        let payload = vec![0xABu8; 4096];
+        let total_writes = 5000;
+        for _ in 0..total_writes {
+            transport.write_bytes(&payload).unwrap(); // لا يجب أن يفشل أبداً تحت DropOldest
+        }
+
+        let mut reader = transport.reader();
+        let first = reader.read_next().expect("At least one surviving record after eviction");
+
+        // بما أن القارئ لم يقرأ شيئاً من قبل، الفجوة الأولى غير محسوبة (last_sequence كان None)،
+        // لكن تسلسل أول سجل يبقى يُظهر أن سجلات سابقة أُسقطت (تسلسله > 0)
+        assert!(first.dropped == 0);
+
+        // نتابع القراءة حتى النهاية ونتحقق أن كل فجوة مُبلّغ عنها غير سالبة ومنطقية
+        let mut total_dropped = 0u64;
+        while let Some(r) = reader.read_next() {
+            total_dropped += r.dropped;
+        }
+        // تحت الضغط الشديد، لا بد أن تكون هناك سجلات مُسقطة بما أن الحلقة أصغر من إجمالي البيانات المكتوبة
+        assert!(total_dropped > 0, "DropOldest must have evicted some records under this much pressure");
+    }
+
+    #[test]
+    fn test_wraparound_sentinel_allows_continuous_writes() {
+        let transport = new_test_transport("wraparound");
+        let mut reader = transport.reader();
+
+        // نقرأ فور كل كتابة حتى يبقى المخزن شبه فارغ دوماً (لا امتلاء)، بينما مؤشر الكتابة
+        // التراكمي يتجاوز سعة الحلقة (10 ميجا) عدة مرات، مما يفرض التفافاً فعلياً متكرراً.
+        let payload = vec![1u8; 64];
+        let iterations = 500_000; // 500_000 * (12 + 64) بايت = 38 مليون بايت > 3 أضعاف سعة الحلقة (10 ميجا)
+        let mut reader_positions = 0;
+
+        for i in 0..iterations {
+            transport.write_bytes(&payload).unwrap();
+            let read = reader.read_next().unwrap_or_else(|| panic!("Expected record #{} to be readable", i));
+            assert_eq!(read.data, payload);
+            reader_positions += 1;
+        }
+
+        assert_eq!(reader_positions, iterations, "Reader must have consumed records across multiple wraps");
+    }
+}
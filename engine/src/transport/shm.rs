@@ -0,0 +1,344 @@
+// LMAX-Disruptor Style Ring Buffer Transport
+
+/*
+ * ALPHA SOVEREIGN - DISRUPTOR-STYLE SHM RING TRANSPORTER
+ * =================================================================
+ * Component Name: engine/src/transport/shm.rs
+ * Core Responsibility: نشر بيانات السوق للدماغ بزمن استجابة دون-الميكروثانية كناقل (Transporter) حقيقي،
+ *                       بدلاً من JSON عبر ZMQ (Performance Pillar).
+ * Design Pattern: LMAX Disruptor (مصفوفة خانات ثابتة + ختم تسلسلي خاص بكل خانة، قراءة بلا قفل)
+ * Forensic Impact: الكاتب لا يُحظر أبداً؛ القارئ المتأخر جداً يكتشف تخطّي الكاتب له من فجوة التسلسل،
+ *                   لا من انهيار أو إشعار مباشر.
+ * =================================================================
+ */
+
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::error::{AlphaError, AlphaResult};
+use crate::transport::{EgressEvent, IngressEvent, Transporter};
+
+const SHM_RING_PATH: &str = "/dev/shm/alpha_disruptor_lane";
+const MAGIC_HEADER: u32 = 0xD15C0001;
+
+/// عدد الخانات: يجب أن يكون قوة لاثنين كي يُستخدم AND السريع بدلاً من القسمة (`seq & (N-1)`)
+const SLOT_COUNT: usize = 4096;
+
+/// أقصى حجم حمولة لكل خانة. خانات بحجم ثابت (على عكس السجلات متغيرة الطول في `shared_memory.rs`)
+/// هي ما يسمح بتخطيط Disruptor: موقع كل خانة يُحسب مباشرة من رقمها التسلسلي دون فحص أي طول سابق.
+const SLOT_PAYLOAD_CAP: usize = 1024;
+
+/// قيمة خاصة تعني أن الخانة لم تُنشر فيها أي بيانات منذ إنشاء الحلقة إطلاقاً
+const EMPTY_SLOT_SEQ: u64 = u64::MAX;
+
+#[repr(C)]
+struct RingHeader {
+    magic: u32,
+    slot_count: u32,
+    /// المؤشر الذي يحجزه كل كاتب عبر `fetch_add` للحصول على رقمه التسلسلي الفريد (Claim Sequence)
+    claim: AtomicU64,
+}
+
+#[repr(C)]
+struct Slot {
+    /// الرقم التسلسلي المنشور لهذه الخانة؛ القارئ لا يستهلك الخانة إلا حين يطابق هذا رقمه المتوقع
+    seq: AtomicU64,
+    len: u32,
+    _padding: u32,
+    data: [u8; SLOT_PAYLOAD_CAP],
+}
+
+/// ناقل حلقي بنمط LMAX Disruptor فوق ملف ممسوح بالذاكرة (`/dev/shm`): كاتب واحد أو أكثر يحجزون
+/// خانات عبر `claim.fetch_add`، وقارئ واحد أو أكثر يدورون (Spin) على ختم كل خانة دون أي قفل.
+pub struct ShmRingTransport {
+    header: *mut RingHeader,
+    slots: *mut Slot,
+    slot_count: u64,
+    path: String,
+
+    // الاحتفاظ بمقبض الملف الممسوح لمنع إلغاء تعيينه
+    _mmap: memmap2::MmapMut,
+}
+
+// نتحمل مسؤولية سلامة الوصول المتزامن للمؤشرات الخام أنفسنا عبر الذرّيات (Atomics) فقط
+unsafe impl Send for ShmRingTransport {}
+unsafe impl Sync for ShmRingTransport {}
+
+impl ShmRingTransport {
+    /// إنشاء أو فتح منطقة الحلقة القياسية الافتراضية
+    pub fn new(create_new: bool) -> AlphaResult<Self> {
+        Self::new_at(SHM_RING_PATH, create_new)
+    }
+
+    /// نفس `new` لكن بمسار ملف مخصص (يُستخدم داخلياً لإعادة فتح نفس الحلقة من خيط قارئ مستقل،
+    /// وفي الاختبارات لتفادي التشارك مع ملف الإنتاج)
+    fn new_at(path: &str, create_new: bool) -> AlphaResult<Self> {
+        use memmap2::MmapMut;
+        use std::fs::OpenOptions;
+
+        let region_len = size_of::<RingHeader>() + SLOT_COUNT * size_of::<Slot>();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(create_new)
+            .open(path)
+            .map_err(|e| AlphaError::BootstrapError(format!("SHM Ring File Open Error: {}", e)))?;
+
+        if create_new {
+            file.set_len(region_len as u64)
+                .map_err(|e| AlphaError::BootstrapError(format!("SHM Ring Truncate Error: {}", e)))?;
+        }
+
+        let mut mmap = unsafe {
+            MmapMut::map_mut(&file).map_err(|e| AlphaError::BootstrapError(format!("Mmap Error: {}", e)))?
+        };
+
+        let base_ptr = mmap.as_mut_ptr();
+        let header_ptr = base_ptr as *mut RingHeader;
+        let slots_ptr = unsafe { base_ptr.add(size_of::<RingHeader>()) as *mut Slot };
+
+        if create_new {
+            unsafe {
+                (*header_ptr).magic = MAGIC_HEADER;
+                (*header_ptr).slot_count = SLOT_COUNT as u32;
+                (*header_ptr).claim = AtomicU64::new(0);
+                for i in 0..SLOT_COUNT {
+                    let slot = slots_ptr.add(i);
+                    (*slot).seq = AtomicU64::new(EMPTY_SLOT_SEQ);
+                    (*slot).len = 0;
+                }
+            }
+            info!("SHM_RING: Initialized new Disruptor-style ring at {} ({} slots)", path, SLOT_COUNT);
+        } else {
+            unsafe {
+                if (*header_ptr).magic != MAGIC_HEADER {
+                    return Err(AlphaError::Fatal("SHM Ring Magic Mismatch! Possible memory corruption.".into()));
+                }
+            }
+        }
+
+        let slot_count = unsafe { (*header_ptr).slot_count as u64 };
+
+        Ok(Self {
+            header: header_ptr,
+            slots: slots_ptr,
+            slot_count,
+            path: path.to_string(),
+            _mmap: mmap,
+        })
+    }
+
+    /// يحجز خانة جديدة عبر `fetch_add` ذرّي، ينسخ الحمولة فيها، ثم ينشر رقمها التسلسلي بـ `Release`.
+    /// الكاتب لا يُحظر أبداً: إن كانت الحلقة قد دارت دورة كاملة منذ آخر قراءة للخانة المستهدفة،
+    /// تُكتب الخانة فوقها ببساطة (الكتابة الفوقية هي آلية الإخلاء الوحيدة هنا).
+    pub fn publish(&self, payload: &[u8]) -> AlphaResult<u64> {
+        if payload.len() > SLOT_PAYLOAD_CAP {
+            return Err(AlphaError::ValidationFailed(format!(
+                "Payload of {} bytes exceeds the {}-byte fixed slot capacity",
+                payload.len(),
+                SLOT_PAYLOAD_CAP
+            )));
+        }
+
+        unsafe {
+            let header = &*self.header;
+            let seq = header.claim.fetch_add(1, Ordering::AcqRel);
+            let slot = &mut *self.slots.add((seq & (self.slot_count - 1)) as usize);
+
+            slot.len = payload.len() as u32;
+            std::ptr::copy_nonoverlapping(payload.as_ptr(), slot.data.as_mut_ptr(), payload.len());
+
+            // Release يضمن أن الطول والحمولة مرئيان لأي قارئ يرى هذا التسلسل الجديد بعدها
+            slot.seq.store(seq, Ordering::Release);
+            Ok(seq)
+        }
+    }
+
+    /// إنشاء قارئ مستقل يبدأ من أول خانة لم يرها بعد (الرقم التسلسلي صفر)
+    pub fn reader(&self) -> ShmRingReader<'_> {
+        ShmRingReader { transport: self, next_seq: 0 }
+    }
+}
+
+/// نتيجة قراءة خانة واحدة من الحلقة
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingReadResult {
+    pub sequence: u64,
+    pub data: Vec<u8>,
+}
+
+/// قارئ بحالة محلية (الرقم التسلسلي المتوقع التالي). القراءة بلا قفل ولا انتظار حظر: دوران (Spin)
+/// فعّال فقط على ختم الخانة نفسها.
+pub struct ShmRingReader<'a> {
+    transport: &'a ShmRingTransport,
+    next_seq: u64,
+}
+
+impl ShmRingReader<'_> {
+    /// يدور (Spin) حتى تُنشر الخانة التالية المتوقعة بالضبط، ثم يستهلكها فوراً. لا قفل ولا نوم،
+    /// لذا يُستخدم من خيط مستقل مخصص للقراءة وليس داخل حلقة أحداث Tokio غير الحاجبة.
+    pub fn spin_read_next(&mut self) -> RingReadResult {
+        loop {
+            if let Some(result) = self.try_read() {
+                return result;
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// محاولة قراءة غير حاجبة: تعيد `None` فوراً إن لم تُنشر الخانة التالية بعد بدلاً من الدوران.
+    pub fn try_read(&mut self) -> Option<RingReadResult> {
+        unsafe {
+            let slot_idx = (self.next_seq & (self.transport.slot_count - 1)) as usize;
+            let slot = &*self.transport.slots.add(slot_idx);
+            let published_seq = slot.seq.load(Ordering::Acquire);
+
+            if published_seq == EMPTY_SLOT_SEQ || published_seq < self.next_seq {
+                return None; // لم تُنشر هذه الخانة بعد
+            }
+
+            if published_seq > self.next_seq {
+                // الكاتب دار دورة كاملة (أو أكثر) فوق هذا القارئ قبل أن يقرأ: نقفز للخانة الحيّة الحالية
+                warn!(
+                    "SHM_RING: Reader overrun — writer lapped the buffer ({} records skipped), jumping to sequence {}",
+                    published_seq - self.next_seq,
+                    published_seq
+                );
+                self.next_seq = published_seq;
+            }
+
+            let len = slot.len as usize;
+            let mut data = vec![0u8; len];
+            std::ptr::copy_nonoverlapping(slot.data.as_ptr(), data.as_mut_ptr(), len);
+
+            let sequence = self.next_seq;
+            self.next_seq += 1;
+            Some(RingReadResult { sequence, data })
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transporter for ShmRingTransport {
+    fn name(&self) -> &str {
+        "SHM Disruptor Ring"
+    }
+
+    /// يفتح مقبضاً مستقلاً لنفس ملف الحلقة من خيط نظام مخصص (وليس مهمة Tokio، لأن الدوران الفعّال
+    /// يحظر المنفّذ)، ويدور قراءةً لكل خانة جديدة، يفك ترميزها Bincode، ويرسلها للمحرك عبر القناة.
+    async fn start(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
+        let path = self.path.clone();
+
+        std::thread::spawn(move || {
+            let transport = match ShmRingTransport::new_at(&path, false) {
+                Ok(t) => t,
+                Err(e) => {
+                    error!("SHM_RING: Reader thread failed to attach to {}: {}", path, e);
+                    return;
+                }
+            };
+
+            let mut reader = transport.reader();
+            loop {
+                let result = reader.spin_read_next();
+                match bincode::deserialize::<IngressEvent>(&result.data) {
+                    Ok(event) => {
+                        if sender.blocking_send(event).is_err() {
+                            error!("SHM_RING: Engine channel closed, stopping reader thread");
+                            break;
+                        }
+                    }
+                    Err(e) => warn!("SHM_RING: Malformed record at sequence {}: {}", result.sequence, e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// إرسال حدث للخارج: تسلسل Bincode (أسرع من JSON، مناسب لزمن الاستجابة دون-الميكروثانية
+    /// المطلوب) ثم نشره في الحلقة.
+    async fn send(&self, event: EgressEvent) -> AlphaResult<()> {
+        let payload = bincode::serialize(&event)
+            .map_err(|e| AlphaError::ExecutionFailed(format!("Bincode Serialize Error: {}", e)))?;
+        self.publish(&payload)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_path(tag: &str) -> String {
+        use std::sync::atomic::AtomicU64;
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!("/tmp/alpha_shm_ring_test_{}_{}_{}", std::process::id(), tag, n)
+    }
+
+    fn new_test_transport(tag: &str) -> ShmRingTransport {
+        ShmRingTransport::new_at(&unique_test_path(tag), true).unwrap()
+    }
+
+    #[test]
+    fn test_publish_and_spin_read_roundtrip_preserves_order_and_sequence() {
+        let transport = new_test_transport("roundtrip");
+        transport.publish(b"first").unwrap();
+        transport.publish(b"second").unwrap();
+
+        let mut reader = transport.reader();
+        let r1 = reader.spin_read_next();
+        assert_eq!(r1.sequence, 0);
+        assert_eq!(r1.data, b"first");
+
+        let r2 = reader.spin_read_next();
+        assert_eq!(r2.sequence, 1);
+        assert_eq!(r2.data, b"second");
+
+        assert!(reader.try_read().is_none(), "No third record should be available yet");
+    }
+
+    #[test]
+    fn test_reader_overrun_jumps_forward_when_writer_laps_the_ring() {
+        let transport = new_test_transport("overrun");
+        let mut reader = transport.reader();
+
+        // نملأ الحلقة ونتجاوزها بدورة كاملة إضافية دون أي قراءة، فتُكتب كل الخانات الأصلية فوقها
+        for i in 0..(SLOT_COUNT as u64 + 10) {
+            transport.publish(&i.to_le_bytes()).unwrap();
+        }
+
+        // القارئ كان لا يزال يتوقع التسلسل صفر، لكن تلك الخانة كُتبت فوقها؛ يجب أن يقفز للأمام دون توقف
+        let result = reader.try_read().expect("Reader must recover instead of spinning forever");
+        assert!(result.sequence >= SLOT_COUNT as u64, "Reader should jump past the lapped records");
+    }
+
+    #[tokio::test]
+    async fn test_transporter_send_publishes_bincode_payload_readable_by_reader() {
+        let transport = new_test_transport("transporter_send");
+
+        let event = EgressEvent::OrderStatusUpdate {
+            order_id: 42,
+            status: "FILLED".into(),
+            reason: None,
+        };
+        Transporter::send(&transport, event.clone()).await.unwrap();
+
+        let mut reader = transport.reader();
+        let result = reader.spin_read_next();
+        let decoded: EgressEvent = bincode::deserialize(&result.data).unwrap();
+
+        match decoded {
+            EgressEvent::OrderStatusUpdate { order_id, status, reason } => {
+                assert_eq!(order_id, 42);
+                assert_eq!(status, "FILLED");
+                assert_eq!(reason, None);
+            }
+            _ => panic!("Unexpected variant decoded from SHM ring"),
+        }
+    }
+}
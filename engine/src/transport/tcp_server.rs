@@ -13,9 +13,12 @@
 use tokio::net::{TcpListener, TcpStream};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tracing::{info, warn, error};
+use rust_decimal::Decimal;
 use std::env;
 use crate::error::AlphaResult;
 use crate::risk::{trigger_emergency_stop, is_emergency_state};
+use crate::risk::margin_guard::{last_portfolio_health, RiskAction};
+use crate::matching::wal;
 
 pub struct TcpAdminServer {
     port: u16,
@@ -117,12 +120,36 @@ fn process_command(cmd: &str) -> String {
         "HELP" => {
             "AVAILABLE COMMANDS:
              STATUS  - Check system health
+             HEALTH  - Print live PortfolioHealth and recommended RiskAction
+             SEQ     - Print the last WAL sequence number applied
              PANIC   - TRIGGER GLOBAL KILL SWITCH (HALT TRADING)
              PING    - Test latency
              EXIT    - Close connection".to_string()
         },
-        
+
         "PING" => "PONG".to_string(),
+
+        "HEALTH" => match last_portfolio_health() {
+            Some(health) => {
+                let action = match health.recommended_action {
+                    RiskAction::None => "NONE".to_string(),
+                    RiskAction::HaltNewOrders => "HALT_NEW_ORDERS".to_string(),
+                    RiskAction::CloseAll => "CLOSE_ALL".to_string(),
+                    RiskAction::ReduceBy(qty) => format!("REDUCE_BY({})", qty),
+                };
+                format!(
+                    "MARGIN RATIO: {}%\nEFFECTIVE LEVERAGE: {}x\nMAINTENANCE LIQ. PRICE: {}\nBANKRUPTCY PRICE: {}\nRECOMMENDED ACTION: {}",
+                    (health.margin_ratio * Decimal::from(100)).round_dp(2),
+                    health.effective_leverage.round_dp(2),
+                    health.maintenance_liquidation_price.round_dp(2),
+                    health.bankruptcy_price.round_dp(2),
+                    action
+                )
+            }
+            None => "NO PORTFOLIO HEALTH RECORDED YET.".to_string(),
+        },
+
+        "SEQ" => format!("LAST WAL SEQUENCE: {}", wal::last_sequence()),
         
         "STATUS" => {
             let emergency = is_emergency_state();
@@ -0,0 +1,440 @@
+// Append-Only Forensic Write-Ahead Log
+
+/*
+ * ALPHA SOVEREIGN - FORENSIC WAL / BLACK BOX RECORDER
+ * =================================================================
+ * Component Name: engine/src/transport/wal.rs
+ * Core Responsibility: تسجيل دائم (Durable) لا يُفقد عند الانهيار لكل حدث دخول/خروج/سجل نصي،
+ *                       مع استعادة وإعادة تشغيل (Replay) بعد الإقلاع (Explainability Pillar).
+ * Design Pattern: Mapped Append-Only Segment Log (WAL) + Daily Rolling (على غرار `utils/logger.rs`)
+ * Forensic Impact: هذا هو "الصندوق الأسود" الذي يذكره الكود مراراً دون أن يوجد؛ طوال عمل
+ *                   المحرك يُعاد بناؤه بدقة من هذا السجل وحده حتى لو فشلت كل الأنظمة الأخرى.
+ * =================================================================
+ */
+
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::NaiveDate;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::{AlphaError, AlphaResult};
+use crate::transport::{EgressEvent, IngressEvent};
+use crate::utils::logger::LogEntry;
+
+const WAL_DIR: &str = "/var/lib/alpha/wal";
+const SEGMENT_PREFIX: &str = "alpha_blackbox";
+const MAGIC_HEADER: u32 = 0xA1BDFA11;
+
+/// سعة القطعة (Segment) الواحدة بالبايت. ثابتة ومُخصَّصة مسبقاً (كملفات SHM في `shared_memory.rs` و`shm.rs`)
+/// بدل النمو الديناميكي، لتفادي إعادة تعيين الخرائط (`remap`) أثناء الكتابة الساخنة.
+const SEGMENT_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
+
+/// طول رأس كل سجل: 8 بايت تسلسل + 4 بايت طول + 4 بايت CRC32 للحمولة
+const RECORD_HEADER_LEN: usize = 16;
+
+/// حارس سجل فارغ: رقم التسلسل الذي لم يُكتب فوقه بعد (نهاية المنطقة المُستخدمة فعلياً)
+const UNWRITTEN_SEQ: u64 = 0;
+
+#[repr(C)]
+struct WalHeader {
+    magic: u32,
+    _padding: u32,
+    /// آخر رقم تسلسلي مُثبَّت بشكل دائم: لا يتقدّم إلا بعد تفريغ بايتات الحمولة للقرص (`Release`)
+    committed_seq: AtomicU64,
+    /// موضع الكتابة التالي بالبايت داخل منطقة السجلات (بعد الرأس)
+    write_cursor: AtomicU64,
+}
+
+/// الأحداث الثلاثة القابلة للتسجيل في الصندوق الأسود — دخول، خروج، أو سجل نصي مهيكل.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WalRecord {
+    Ingress(IngressEvent),
+    Egress(EgressEvent),
+    Log(LogEntry),
+}
+
+/// قطعة واحدة من سجل WAL: ملف ممسوح بالذاكرة بسعة ثابتة، مع رأس دائم يتتبّع آخر رقم تسلسلي
+/// مُثبَّت ومكان الكتابة التالي.
+struct WalSegment {
+    header: *mut WalHeader,
+    records_base: *mut u8,
+    capacity: usize,
+    path: String,
+    opened_on: NaiveDate,
+    next_seq: u64,
+
+    _mmap: memmap2::MmapMut,
+}
+
+unsafe impl Send for WalSegment {}
+unsafe impl Sync for WalSegment {}
+
+impl WalSegment {
+    fn open(path: &str, create_new: bool) -> AlphaResult<Self> {
+        use memmap2::MmapMut;
+        use std::fs::OpenOptions;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(create_new)
+            .open(path)
+            .map_err(|e| AlphaError::BootstrapError(format!("WAL Segment Open Error: {}", e)))?;
+
+        if create_new {
+            file.set_len((size_of::<WalHeader>() + SEGMENT_CAPACITY_BYTES) as u64)
+                .map_err(|e| AlphaError::BootstrapError(format!("WAL Segment Truncate Error: {}", e)))?;
+        }
+
+        let mut mmap = unsafe {
+            MmapMut::map_mut(&file).map_err(|e| AlphaError::BootstrapError(format!("WAL Mmap Error: {}", e)))?
+        };
+
+        let base_ptr = mmap.as_mut_ptr();
+        let header_ptr = base_ptr as *mut WalHeader;
+        let records_base = unsafe { base_ptr.add(size_of::<WalHeader>()) };
+
+        if create_new {
+            unsafe {
+                (*header_ptr).magic = MAGIC_HEADER;
+                (*header_ptr).committed_seq = AtomicU64::new(UNWRITTEN_SEQ);
+                (*header_ptr).write_cursor = AtomicU64::new(0);
+            }
+            info!("WAL: Created new segment {}", path);
+
+            Ok(Self {
+                header: header_ptr,
+                records_base,
+                capacity: SEGMENT_CAPACITY_BYTES,
+                path: path.to_string(),
+                opened_on: chrono::Utc::now().date_naive(),
+                next_seq: 1,
+                _mmap: mmap,
+            })
+        } else {
+            unsafe {
+                if (*header_ptr).magic != MAGIC_HEADER {
+                    return Err(AlphaError::Fatal("WAL Magic Mismatch! Segment file is corrupted or foreign.".into()));
+                }
+            }
+
+            let mut segment = Self {
+                header: header_ptr,
+                records_base,
+                capacity: SEGMENT_CAPACITY_BYTES,
+                path: path.to_string(),
+                opened_on: chrono::Utc::now().date_naive(),
+                next_seq: 1,
+                _mmap: mmap,
+            };
+            segment.recover()?;
+            Ok(segment)
+        }
+    }
+
+    /// يُستدعى مرة واحدة عند فتح قطعة موجودة مسبقاً بعد إقلاع جديد: يمسح السجلات من أول
+    /// أوفست بعد الرأس، يتحقق من CRC كل سجل، ويقطع (Truncate) المنطقة المنطقية عند أول سجل
+    /// ممزّق أو غير صالح — عندها يُستأنف الإلحاق من تلك النقطة بالضبط.
+    fn recover(&mut self) -> AlphaResult<()> {
+        let mut offset = 0usize;
+        let mut last_valid_seq = UNWRITTEN_SEQ;
+        let mut records_scanned = 0u64;
+
+        loop {
+            match self.try_read_record_at(offset) {
+                Some((record_len, seq)) if seq == last_valid_seq + 1 || (last_valid_seq == UNWRITTEN_SEQ && seq >= 1) => {
+                    offset += record_len;
+                    last_valid_seq = seq;
+                    records_scanned += 1;
+                }
+                _ => break, // سجل غير صالح، ممزّق، أو نهاية المنطقة المكتوبة فعلياً
+            }
+        }
+
+        unsafe {
+            (*self.header).write_cursor.store(offset as u64, Ordering::Relaxed);
+            // Release: أي قارئ لاحق لـ `committed_seq` يرى حتماً كل السجلات الصحيحة الممسوحة هنا
+            (*self.header).committed_seq.store(last_valid_seq, Ordering::Release);
+        }
+        self.next_seq = last_valid_seq + 1;
+
+        if records_scanned > 0 {
+            info!(
+                "WAL: Recovered segment {} — {} valid records, resuming at sequence {}",
+                self.path, records_scanned, self.next_seq
+            );
+        }
+        Ok(())
+    }
+
+    /// يحاول قراءة رأس السجل والتحقق من CRC عند `offset`. يعيد `None` عند نهاية المنطقة
+    /// المتاحة أو أي فشل في التحقق (سجل لم يُكتب بعد، أو ممزّق من انهيار منتصف الكتابة).
+    fn try_read_record_at(&self, offset: usize) -> Option<(usize, u64)> {
+        if offset + RECORD_HEADER_LEN > self.capacity {
+            return None;
+        }
+
+        let (seq, len, crc) = unsafe { self.read_header_at(offset) };
+        if seq == UNWRITTEN_SEQ || len == 0 {
+            return None;
+        }
+        if offset + RECORD_HEADER_LEN + len as usize > self.capacity {
+            return None;
+        }
+
+        let payload = unsafe {
+            std::slice::from_raw_parts(self.records_base.add(offset + RECORD_HEADER_LEN), len as usize)
+        };
+        let actual_crc = crc32fast::hash(payload);
+        if actual_crc != crc {
+            warn!("WAL: CRC mismatch at offset {} (torn write on crash recovery) — truncating here", offset);
+            return None;
+        }
+
+        Some((RECORD_HEADER_LEN + len as usize, seq))
+    }
+
+    unsafe fn read_header_at(&self, offset: usize) -> (u64, u32, u32) {
+        let base = self.records_base.add(offset);
+        let mut seq_bytes = [0u8; 8];
+        let mut len_bytes = [0u8; 4];
+        let mut crc_bytes = [0u8; 4];
+        std::ptr::copy_nonoverlapping(base, seq_bytes.as_mut_ptr(), 8);
+        std::ptr::copy_nonoverlapping(base.add(8), len_bytes.as_mut_ptr(), 4);
+        std::ptr::copy_nonoverlapping(base.add(12), crc_bytes.as_mut_ptr(), 4);
+        (u64::from_le_bytes(seq_bytes), u32::from_le_bytes(len_bytes), u32::from_le_bytes(crc_bytes))
+    }
+
+    /// يلحق سجلاً واحداً: يكتب [تسلسل][طول][CRC][حمولة]، يُفرِغ البايتات للقرص، ثم يُثبّت
+    /// رقم التسلسل في الرأس بـ `Release` فقط بعد نجاح التفريغ — هذا هو ضمان الديمومة.
+    fn append(&mut self, record: &WalRecord) -> AlphaResult<u64> {
+        let payload = bincode::serialize(record)
+            .map_err(|e| AlphaError::ExecutionFailed(format!("WAL Bincode Serialize Error: {}", e)))?;
+
+        let record_len = RECORD_HEADER_LEN + payload.len();
+        let write_cursor = unsafe { (*self.header).write_cursor.load(Ordering::Relaxed) } as usize;
+
+        if write_cursor + record_len > self.capacity {
+            return Err(AlphaError::ExecutionFailed(format!(
+                "WAL segment {} is full ({} bytes used of {}); roll a new segment",
+                self.path, write_cursor, self.capacity
+            )));
+        }
+
+        let seq = self.next_seq;
+        let crc = crc32fast::hash(&payload);
+
+        unsafe {
+            let base = self.records_base.add(write_cursor);
+            std::ptr::copy_nonoverlapping(seq.to_le_bytes().as_ptr(), base, 8);
+            std::ptr::copy_nonoverlapping((payload.len() as u32).to_le_bytes().as_ptr(), base.add(8), 4);
+            std::ptr::copy_nonoverlapping(crc.to_le_bytes().as_ptr(), base.add(12), 4);
+            std::ptr::copy_nonoverlapping(payload.as_ptr(), base.add(RECORD_HEADER_LEN), payload.len());
+        }
+
+        // تفريغ الحمولة للقرص قبل إعلان التثبيت — وإلا فقد يعلن التثبيت بيانات لم تصل فعلياً
+        self._mmap
+            .flush_range(write_cursor, record_len)
+            .map_err(|e| AlphaError::ExecutionFailed(format!("WAL Flush Error: {}", e)))?;
+
+        unsafe {
+            (*self.header).write_cursor.store((write_cursor + record_len) as u64, Ordering::Relaxed);
+            (*self.header).committed_seq.store(seq, Ordering::Release);
+        }
+
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    fn committed_write_cursor(&self) -> usize {
+        unsafe { (*self.header).write_cursor.load(Ordering::Acquire) as usize }
+    }
+}
+
+/// المسجِّل الدائم الرئيسي: يملك قطعة واحدة "حالية" ويُدوّرها يومياً (بنفس فكرة
+/// `tracing_appender::rolling::daily` المستخدمة في `utils/logger.rs`)، حتى لا تنمو قطعة
+/// واحدة إلى الأبد ولتسهيل الأرشفة/الحذف حسب التاريخ.
+pub struct WalRecorder {
+    dir: String,
+    prefix: String,
+    current: Mutex<WalSegment>,
+}
+
+impl WalRecorder {
+    pub fn new() -> AlphaResult<Self> {
+        Self::new_in(WAL_DIR, SEGMENT_PREFIX)
+    }
+
+    /// نفس `new` لكن بمجلد وبادئة ملف مخصصين (يُستخدم في الاختبارات لتفادي التشارك مع مسار الإنتاج)
+    fn new_in(dir: &str, prefix: &str) -> AlphaResult<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AlphaError::BootstrapError(format!("WAL Directory Create Error: {}", e)))?;
+
+        let today = chrono::Utc::now().date_naive();
+        let path = Self::segment_path(dir, prefix, today);
+        let create_new = !std::path::Path::new(&path).exists();
+        let segment = WalSegment::open(&path, create_new)?;
+
+        Ok(Self { dir: dir.to_string(), prefix: prefix.to_string(), current: Mutex::new(segment) })
+    }
+
+    fn segment_path(dir: &str, prefix: &str, date: NaiveDate) -> String {
+        format!("{}/{}.{}.wal", dir, prefix, date.format("%Y-%m-%d"))
+    }
+
+    /// يُلحِق سجلاً جديداً، مُدوِّراً لقطعة جديدة أولاً إن كان التاريخ قد تغيّر منذ آخر إلحاق.
+    pub fn append(&self, record: WalRecord) -> AlphaResult<u64> {
+        let mut segment = self.current.lock();
+
+        let today = chrono::Utc::now().date_naive();
+        if today != segment.opened_on {
+            info!("WAL: Rolling to a new daily segment ({} -> {})", segment.opened_on, today);
+            let path = Self::segment_path(&self.dir, &self.prefix, today);
+            *segment = WalSegment::open(&path, true)?;
+        }
+
+        segment.append(&record)
+    }
+
+    /// يبني قارئ إعادة تشغيل (Replay) يبدأ من `from_seq` ضمن القطعة الحالية فقط — لإعادة
+    /// بناء حالة المحرك عبر أكثر من قطعة (أيام سابقة)، افتح كل قطعة بدورها بنفس الآلية.
+    pub fn replay(&self, from_seq: u64) -> WalReplay<'_> {
+        let segment = self.current.lock();
+        WalReplay { segment, offset: 0, from_seq }
+    }
+}
+
+/// مكرِّر (Iterator) يعيد بث السجلات بالترتيب بدءاً من `from_seq` لإعادة بناء الحالة بعد
+/// الانهيار أو للتدقيق الجنائي اللاحق. يتوقف عند أول سجل غير صالح أو نهاية المنطقة المُثبَّتة.
+pub struct WalReplay<'a> {
+    segment: parking_lot::MutexGuard<'a, WalSegment>,
+    offset: usize,
+    from_seq: u64,
+}
+
+impl Iterator for WalReplay<'_> {
+    type Item = WalRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let committed = self.segment.committed_write_cursor();
+
+        loop {
+            if self.offset >= committed {
+                return None;
+            }
+
+            let (seq, len, crc) = unsafe { self.segment.read_header_at(self.offset) };
+            if seq == UNWRITTEN_SEQ || len == 0 {
+                return None;
+            }
+
+            let payload_offset = self.offset + RECORD_HEADER_LEN;
+            let payload = unsafe {
+                std::slice::from_raw_parts(self.segment.records_base.add(payload_offset), len as usize)
+            };
+
+            if crc32fast::hash(payload) != crc {
+                return None; // سجل ممزّق: توقف كما في الاستعادة عند الإقلاع
+            }
+
+            self.offset = payload_offset + len as usize;
+
+            if seq < self.from_seq {
+                continue; // قبل النقطة المطلوبة: تخطَّه وتابع البحث
+            }
+
+            return bincode::deserialize(payload).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_dir(tag: &str) -> String {
+        use std::sync::atomic::AtomicU64;
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!("/tmp/alpha_wal_test_{}_{}_{}", std::process::id(), tag, n)
+    }
+
+    fn sample_log(msg: &str) -> WalRecord {
+        WalRecord::Log(LogEntry::new("INFO", msg))
+    }
+
+    #[test]
+    fn test_append_and_replay_roundtrip_preserves_order() {
+        let dir = unique_test_dir("roundtrip");
+        let recorder = WalRecorder::new_in(&dir, "test").unwrap();
+
+        recorder.append(sample_log("first")).unwrap();
+        recorder.append(sample_log("second")).unwrap();
+        recorder.append(sample_log("third")).unwrap();
+
+        let replayed: Vec<WalRecord> = recorder.replay(0).collect();
+        assert_eq!(replayed.len(), 3);
+        assert_eq!(replayed[0], sample_log("first"));
+        assert_eq!(replayed[2], sample_log("third"));
+    }
+
+    #[test]
+    fn test_replay_from_seq_skips_earlier_records() {
+        let dir = unique_test_dir("replay_from");
+        let recorder = WalRecorder::new_in(&dir, "test").unwrap();
+
+        recorder.append(sample_log("one")).unwrap();
+        recorder.append(sample_log("two")).unwrap();
+        let third_seq = recorder.append(sample_log("three")).unwrap();
+
+        let replayed: Vec<WalRecord> = recorder.replay(third_seq).collect();
+        assert_eq!(replayed, vec![sample_log("three")]);
+    }
+
+    #[test]
+    fn test_recovery_after_reopen_truncates_at_torn_record_and_resumes_appending() {
+        let dir = unique_test_dir("recovery");
+        let path;
+        {
+            let recorder = WalRecorder::new_in(&dir, "test").unwrap();
+            recorder.append(sample_log("durable-one")).unwrap();
+            recorder.append(sample_log("durable-two")).unwrap();
+            path = current_segment_path(&recorder);
+            let torn_offset = recorder.current.lock().committed_write_cursor();
+
+            // نحاكي انهياراً أثناء كتابة سجل ثالث: نكتب تسلسلاً وطولاً صالحين لكن نُفسد حمولته،
+            // دون تحديث `write_cursor` أو `committed_seq` (بالضبط كما لو انقطعت الكتابة في منتصفها)
+            corrupt_trailing_bytes(&path, torn_offset);
+        }
+
+        let recorder = WalRecorder::new_in(&dir, "test").unwrap();
+        let replayed: Vec<WalRecord> = recorder.replay(0).collect();
+        assert_eq!(replayed.len(), 2, "Recovery must stop at the last valid record, dropping the torn one");
+
+        // يجب أن يستأنف الإلحاق بنجاح من نقطة القطع دون أي تلف
+        recorder.append(sample_log("durable-three")).unwrap();
+        let replayed_after: Vec<WalRecord> = recorder.replay(0).collect();
+        assert_eq!(replayed_after.len(), 3);
+    }
+
+    fn current_segment_path(recorder: &WalRecorder) -> String {
+        let today = chrono::Utc::now().date_naive();
+        WalRecorder::segment_path(&recorder.dir, &recorder.prefix, today)
+    }
+
+    fn corrupt_trailing_bytes(path: &str, records_offset: usize) {
+        use std::io::{Seek, SeekFrom, Write};
+        let mut file = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+        // نكتب عند أول أوفست غير مُلحَق بعد آخر سجل صالح رأساً بتسلسل 3 وطول 8 وCRC صفري،
+        // تليه حمولة 0xFF لا يطابقها ذلك الـ CRC (سجل كُتب جزئياً ولم يُثبَّت على القرص)
+        let offset = (size_of::<WalHeader>() + records_offset) as u64;
+        file.seek(SeekFrom::Start(offset)).unwrap();
+        file.write_all(&3u64.to_le_bytes()).unwrap();
+        file.write_all(&8u32.to_le_bytes()).unwrap();
+        file.write_all(&0u32.to_le_bytes()).unwrap();
+        file.write_all(&[0xFFu8; 8]).unwrap();
+    }
+}
@@ -1,166 +1,254 @@
-// ZeroMQ IPC
-
-/*
- * ALPHA SOVEREIGN - ZEROMQ HIGH-SPEED BRIDGE
- * =================================================================
- * Component Name: engine/src/transport/zmq_bridge.rs
- * Core Responsibility: جسر نقل البيانات السريعة بين Rust و Python (Integration Pillar).
- * Design Pattern: Publisher-Subscriber (PUB/SUB) & Pipeline (PUSH/PULL)
- * Forensic Impact: يوفر "نسخة طبق الأصل" (Mirror) من حالة المحرك للدماغ. أي تأخير هنا يعني أن الدماغ "يرى الماضي".
- * =================================================================
- */
-
-use tmq::{publish, subscribe, Context, Result};
-use tmq::publish::Publish;
-use tmq::subscribe::Subscribe;
-
-use futures::StreamExt;
-use futures::SinkExt;
-use tokio::sync::mpsc;
-use tracing::{info, error, warn};
-use serde_json;
-
-use crate::transport::{IngressEvent, EgressEvent, Transporter};
-use crate::error::{AlphaResult, AlphaError};
-
-pub struct ZmqBridge {
-    /// عنوان النشر (Outbound): Rust -> Python
-    /// مثال: "tcp://127.0.0.1:5555"
-    pub_address: String,
-
-    /// عنوان الاستقبال (Inbound): Python -> Rust
-    /// مثال: "tcp://127.0.0.1:5556"
-    sub_address: String,
-}
-
-impl ZmqBridge {
-    pub fn new(pub_port: u16, sub_port: u16) -> Self {
-        Self {
-            pub_address: format!("tcp://0.0.0.0:{}", pub_port),
-            sub_address: format!("tcp://0.0.0.0:{}", sub_port),
-        }
-    }
-
-    /// تشغيل حلقة الاستقبال (Listener Loop)
-    /// هذه الدالة تستمع لأوامر Python وترسلها للمحرك الداخلي.
-    async fn run_listener(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
-        info!("ZMQ_BRIDGE: Binding SUB socket on {}", self.sub_address);
-
-        // نستخدم نمط Subscribe للاستماع لكل شيء ("")
-        let mut socket = subscribe(&Context::new())
-            .connect(&self.sub_address) // Python binds, we connect (or vice versa depending on topology)
-            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Sub Error: {}", e)))?
-            .subscribe(b"")
-            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Filter Error: {}", e)))?;
-
-        while let Some(msg) = socket.next().await {
-            match msg {
-                Ok(multipart) => {
-                    // نتوقع أن تكون الرسالة JSON في الجزء الثاني (أو الأول حسب البروتوكول)
-                    // للتبسيط، نفترض Payload مباشر
-                    for part in multipart {
-                        let payload = part.as_str().unwrap_or("");
-                        
-                        // محاولة فك التشفير (Deserialization)
-                        match serde_json::from_str::<IngressEvent>(payload) {
-                            Ok(event) => {
-                                // إرسال الحدث للمحرك
-                                if let Err(e) = sender.send(event).await {
-                                    error!("ZMQ_PIPELINE_FAIL: Engine channel closed! {}", e);
-                                    break;
-                                }
-                            },
-                            Err(e) => {
-                                warn!("ZMQ_MALFORMED: Failed to parse Python message: {}. Payload: {:.50}...", e, payload);
-                            }
-                        }
-                    }
-                },
-                Err(e) => {
-                    error!("ZMQ_RECV_ERROR: {}", e);
-                }
-            }
-        }
-        
-        Ok(())
-    }
-}
-
-#[async_trait::async_trait]
-impl Transporter for ZmqBridge {
-    fn name(&self) -> &str {
-        "ZeroMQ Bridge"
-    }
-
-    async fn start(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
-        info!("ZMQ_BRIDGE: Starting Interface...");
-
-        // 1. تشغيل المستقبل (Receiver) في خيط منفصل
-        let listener_sender = sender.clone();
-        let sub_addr = self.sub_address.clone();
-        
-        tokio::spawn(async move {
-            let bridge_clone = ZmqBridge { 
-                pub_address: "".to_string(), // Dummy
-                sub_address: sub_addr 
-            };
-            if let Err(e) = bridge_clone.run_listener(listener_sender).await {
-                error!("ZMQ_LISTENER_CRASH: {}", e);
-            }
-        });
-
-        // الناشر (Publisher) يتم التعامل معه عند الطلب في دالة send
-        // ملاحظة: في ZeroMQ، السوكيت يجب أن يكون مملوكاً أو مشتركاً بحذر.
-        // هنا سنقوم بإنشاء Pub Socket عند الحاجة أو نحتفظ به (التصميم الأمثل يتطلب هيكلة أعقد قليلاً لـ Sharing).
-        // للتبسيط والموثوقية، سنفترض أن `send` تنشئ اتصالاً أو تستخدم قناة داخلية.
-        
-        Ok(())
-    }
-
-    /// إرسال البيانات إلى Python
-    async fn send(&self, event: EgressEvent) -> AlphaResult<()> {
-        // تحذير: إنشاء سوكيت لكل رسالة مكلف جداً!
-        // في الإنتاج الفعلي، يجب أن يكون السوكيت مخزناً ومشتركاً (Shared State).
-        // هنا نوضح المنطق فقط.
-        
-        // التسلسل (Serialization)
-        let payload = serde_json::to_string(&event)
-            .map_err(|e| AlphaError::Internal(format!("JSON Error: {}", e)))?;
-
-        // *تنبيه هندسي*: هذا الكود يحتاج لتحسين ليعيد استخدام السوكيت.
-        // الحل الأمثل هو وجود Actor منفصل للنشر.
-        
-        // (Pseudocode for publishing logic via a shared handle would go here)
-        // info!("ZMQ_PUB: Sending -> {}", payload);
-        
-        Ok(())
-    }
-}
-
-// ----------------------------------------------------------------
-// ZmqPublisher Actor (لحل مشكلة مشاركة السوكيت)
-// ----------------------------------------------------------------
-
-pub struct ZmqPublisherActor {
-    socket: Publish,
-}
-
-impl ZmqPublisherActor {
-    pub async fn new(address: &str) -> AlphaResult<Self> {
-        let socket = publish(&Context::new())
-            .bind(address)
-            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Bind Error: {}", e)))?;
-            
-        Ok(Self { socket })
-    }
-
-    pub async fn broadcast(&mut self, topic: &str, data: &str) -> AlphaResult<()> {
-        let multipart = vec![topic, data];
-        self.socket.send(multipart).await
-            .map_err(|e| AlphaError::NetworkError { 
-                exchange: "INTERNAL_ZMQ".into(), 
-                details: e.to_string() 
-            })?;
-        Ok(())
-    }
-}
\ No newline at end of file
+// ZeroMQ IPC
+
+/*
+ * ALPHA SOVEREIGN - ZEROMQ HIGH-SPEED BRIDGE
+ * =================================================================
+ * Component Name: engine/src/transport/zmq_bridge.rs
+ * Core Responsibility: جسر نقل البيانات السريعة بين Rust و Python (Integration Pillar).
+ * Design Pattern: Publisher-Subscriber (PUB/SUB) & Pipeline (PUSH/PULL)
+ * Forensic Impact: يوفر "نسخة طبق الأصل" (Mirror) من حالة المحرك للدماغ. أي تأخير هنا يعني أن الدماغ "يرى الماضي".
+ * =================================================================
+ */
+
+use tmq::{publish, subscribe, Context, Result};
+use tmq::publish::Publish;
+use tmq::subscribe::Subscribe;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::SinkExt;
+use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use tracing::{info, error, warn};
+use serde_json;
+
+use crate::transport::{IngressEvent, EgressEvent, Transporter};
+use crate::error::{AlphaResult, AlphaError};
+
+pub struct ZmqBridge {
+    /// عنوان النشر (Outbound): Rust -> Python
+    /// مثال: "tcp://127.0.0.1:5555"
+    pub_address: String,
+
+    /// عنوان الاستقبال (Inbound): Python -> Rust
+    /// مثال: "tcp://127.0.0.1:5556"
+    sub_address: String,
+
+    /// خط الإرسال إلى مُشغِّل الناشر الدفعي (Batched Publisher Actor)، يُملأ عند `start()`.
+    /// `send()` لا تفعل شيئاً سوى وضع الرسالة في هذه القناة — السوكيت الفعلي مملوك للمُشغِّل فقط.
+    publisher_tx: Arc<RwLock<Option<mpsc::Sender<PublishMessage>>>>,
+}
+
+impl ZmqBridge {
+    pub fn new(pub_port: u16, sub_port: u16) -> Self {
+        Self {
+            pub_address: format!("tcp://0.0.0.0:{}", pub_port),
+            sub_address: format!("tcp://0.0.0.0:{}", sub_port),
+            publisher_tx: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// تشغيل حلقة الاستقبال (Listener Loop)
+    /// هذه الدالة تستمع لأوامر Python وترسلها للمحرك الداخلي.
+    async fn run_listener(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
+        info!("ZMQ_BRIDGE: Binding SUB socket on {}", self.sub_address);
+
+        // نستخدم نمط Subscribe للاستماع لكل شيء ("")
+        let mut socket = subscribe(&Context::new())
+            .connect(&self.sub_address) // Python binds, we connect (or vice versa depending on topology)
+            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Sub Error: {}", e)))?
+            .subscribe(b"")
+            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Filter Error: {}", e)))?;
+
+        while let Some(msg) = socket.next().await {
+            match msg {
+                Ok(multipart) => {
+                    // نتوقع أن تكون الرسالة JSON في الجزء الثاني (أو الأول حسب البروتوكول)
+                    // للتبسيط، نفترض Payload مباشر
+                    for part in multipart {
+                        let payload = part.as_str().unwrap_or("");
+
+                        // محاولة فك التشفير (Deserialization)
+                        match serde_json::from_str::<IngressEvent>(payload) {
+                            Ok(event) => {
+                                // إرسال الحدث للمحرك
+                                if let Err(e) = sender.send(event).await {
+                                    error!("ZMQ_PIPELINE_FAIL: Engine channel closed! {}", e);
+                                    break;
+                                }
+                            },
+                            Err(e) => {
+                                warn!("ZMQ_MALFORMED: Failed to parse Python message: {}. Payload: {:.50}...", e, payload);
+                            }
+                        }
+                    }
+                },
+                Err(e) => {
+                    error!("ZMQ_RECV_ERROR: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Transporter for ZmqBridge {
+    fn name(&self) -> &str {
+        "ZeroMQ Bridge"
+    }
+
+    async fn start(&self, sender: mpsc::Sender<IngressEvent>) -> AlphaResult<()> {
+        info!("ZMQ_BRIDGE: Starting Interface...");
+
+        // 1. تشغيل المستقبل (Receiver) في خيط منفصل
+        let listener_sender = sender.clone();
+        let sub_addr = self.sub_address.clone();
+
+        tokio::spawn(async move {
+            let bridge_clone = ZmqBridge {
+                pub_address: "".to_string(), // Dummy
+                sub_address: sub_addr,
+                publisher_tx: Arc::new(RwLock::new(None)),
+            };
+            if let Err(e) = bridge_clone.run_listener(listener_sender).await {
+                error!("ZMQ_LISTENER_CRASH: {}", e);
+            }
+        });
+
+        // 2. تشغيل الناشر الدفعي (Batched Publisher Actor) مرة واحدة فقط: سوكيت واحد
+        // طويل العمر، وكل رسالة لاحقة تُضاف لقناة، لا تُنشئ سوكيتاً جديداً
+        let actor = BatchedPublisherActor::spawn(&self.pub_address, PublisherBatchConfig::default()).await?;
+        *self.publisher_tx.write() = Some(actor.into_sender());
+
+        Ok(())
+    }
+
+    /// إرسال البيانات إلى Python: مجرد إدراج في قناة المُشغِّل الدفعي، لا إنشاء سوكيت جديد إطلاقاً
+    async fn send(&self, event: EgressEvent) -> AlphaResult<()> {
+        let payload = serde_json::to_string(&event)
+            .map_err(|e| AlphaError::InternalError(format!("JSON Error: {}", e)))?;
+
+        let tx = self
+            .publisher_tx
+            .read()
+            .clone()
+            .ok_or_else(|| AlphaError::BootstrapError("ZMQ_BRIDGE: Publisher not started yet".into()))?;
+
+        tx.send(PublishMessage { topic: "events".to_string(), payload })
+            .await
+            .map_err(|e| AlphaError::NetworkError(format!("ZMQ Publisher Channel Closed: {}", e)))
+    }
+}
+
+// ----------------------------------------------------------------
+// Batched Publisher Actor (سوكيت واحد طويل العمر + تجميع دفعي)
+// ----------------------------------------------------------------
+
+/// ضبط سلوك التجميع الدفعي: يُفرَّغ المخزن المؤقت أيهما أسبق — بلوغ `items_in_batch` رسالة،
+/// أو انقضاء `flush_interval` — فلا تبقى رسالة عالقة أطول من الفاصل الزمني حتى تحت حمل خفيف.
+#[derive(Debug, Clone, Copy)]
+pub struct PublisherBatchConfig {
+    pub items_in_batch: usize,
+    pub flush_interval: Duration,
+}
+
+impl Default for PublisherBatchConfig {
+    fn default() -> Self {
+        Self {
+            items_in_batch: 200,
+            flush_interval: Duration::from_millis(5),
+        }
+    }
+}
+
+struct PublishMessage {
+    topic: String,
+    payload: String,
+}
+
+/// يملك سوكيت `Publish` الوحيد طوال عمره، ويستقبل الرسائل عبر قناة داخلية، ويُجمِّعها في دفعات
+/// قبل إرسالها — هذا يستهلك تكلفة السوكيت مرة واحدة فقط بدل مرة لكل رسالة (كما كان سابقاً).
+pub struct BatchedPublisherActor {
+    sender: mpsc::Sender<PublishMessage>,
+}
+
+impl BatchedPublisherActor {
+    /// يربط سوكيت PUB على `address` ويشغّل حلقة التجميع الدفعي في مهمة Tokio مستقلة.
+    pub async fn spawn(address: &str, config: PublisherBatchConfig) -> AlphaResult<Self> {
+        let socket = publish(&Context::new())
+            .bind(address)
+            .map_err(|e| AlphaError::BootstrapError(format!("ZMQ Bind Error: {}", e)))?;
+
+        // قناة بسعة كافية لاستيعاب دفعة كاملة دون حظر الخيوط الساخنة التي تستدعي enqueue
+        let (tx, rx) = mpsc::channel(config.items_in_batch * 4);
+
+        tokio::spawn(Self::run_batch_loop(socket, rx, config));
+
+        info!(
+            "ZMQ_BRIDGE: Batched publisher bound to {} (items_in_batch={}, flush_interval={:?})",
+            address, config.items_in_batch, config.flush_interval
+        );
+
+        Ok(Self { sender: tx })
+    }
+
+    /// يستهلك المُشغِّل ويعيد طرف الإرسال نفسه، ليتمكن مستدعون آخرون (مثل `ZmqBridge::send`)
+    /// من وضع رسائل في القناة دون مشاركة الكائن نفسه.
+    pub fn into_sender(self) -> mpsc::Sender<PublishMessage> {
+        self.sender
+    }
+
+    pub async fn broadcast(&self, topic: &str, data: &str) -> AlphaResult<()> {
+        self.sender
+            .send(PublishMessage { topic: topic.to_string(), payload: data.to_string() })
+            .await
+            .map_err(|e| AlphaError::NetworkError(format!("ZMQ Publisher Channel Closed: {}", e)))
+    }
+
+    /// حلقة التجميع الدفعي: تُفرَّغ عند بلوغ `items_in_batch` رسالة أو عند نبضة المؤقّت،
+    /// أيهما أسبق؛ وعند إغلاق القناة تُفرَّغ بقية المخزن المؤقت قبل الخروج.
+    async fn run_batch_loop(mut socket: Publish, mut rx: mpsc::Receiver<PublishMessage>, config: PublisherBatchConfig) {
+        let mut buffer: Vec<PublishMessage> = Vec::with_capacity(config.items_in_batch);
+        let mut ticker = tokio::time::interval(config.flush_interval);
+
+        loop {
+            tokio::select! {
+                received = rx.recv() => {
+                    match received {
+                        Some(msg) => {
+                            buffer.push(msg);
+                            if buffer.len() >= config.items_in_batch {
+                                Self::flush(&mut socket, &mut buffer).await;
+                            }
+                        }
+                        None => {
+                            if !buffer.is_empty() {
+                                Self::flush(&mut socket, &mut buffer).await;
+                            }
+                            info!("ZMQ_BRIDGE: Publisher channel closed, batch loop stopping");
+                            break;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    if !buffer.is_empty() {
+                        Self::flush(&mut socket, &mut buffer).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// إرسال كل الرسائل المجمَّعة حالياً عبر نفس السوكيت الوحيد، ثم تفريغ المخزن المؤقت.
+    async fn flush(socket: &mut Publish, buffer: &mut Vec<PublishMessage>) {
+        for msg in buffer.drain(..) {
+            if let Err(e) = socket.send(vec![msg.topic, msg.payload]).await {
+                error!("ZMQ_PUB_FAIL: Batched send error: {}", e);
+            }
+        }
+    }
+}
@@ -11,9 +11,10 @@
  */
 
 use config::{Config, File, FileFormat};
+use parking_lot::RwLock;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 use std::time::Duration;
 use notify::{Watcher, RecursiveMode, RecommendedWatcher, Config as NotifyConfig};
 use tokio::sync::watch;
@@ -129,7 +130,7 @@ impl ConfigManager {
                             Ok(new_config) => {
                                 // التحديث الذري (Atomic Update)
                                 {
-                                    let mut write_lock = manager.current_config.write().unwrap();
+                                    let mut write_lock = manager.current_config.write();
                                     *write_lock = new_config.clone();
                                 } // Release lock immediately
 
@@ -155,6 +156,12 @@ impl ConfigManager {
 
     /// الحصول على لقطة من الإعدادات الحالية
     pub fn get_current(&self) -> GlobalConfig {
-        self.current_config.read().unwrap().clone()
+        self.current_config.read().clone()
+    }
+
+    /// محاولة الحصول على لقطة دون الانتظار خلف كاتب يقوم بإعادة التحميل الآن.
+    /// يستخدمها القارئ على المسار الساخن الذي يفضل تخطي الدورة الحالية بدلاً من الحظر.
+    pub fn try_get_current(&self) -> Option<GlobalConfig> {
+        self.current_config.try_read().map(|guard| guard.clone())
     }
 }
\ No newline at end of file
@@ -13,10 +13,12 @@
  */
 
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::Level;
 use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use serde::{Deserialize, Serialize}; // [مضاف] للتسلسل
+use tokio::sync::mpsc;
 
 // =================================================================
 // 1. هياكل البيانات (Data Structures)
@@ -51,9 +53,11 @@ impl LogEntry {
 
 /// تهيئة نظام التسجيل العالمي.
 /// يجب استدعاء هذه الدالة مرة واحدة فقط في `main.rs`.
+/// `log_stream`: مرّر طبقة البث الحي (`LogStreamLayer`) مع مستوى فلترتها الخاص (مثلاً "info")
+/// لتثبيتها جنباً إلى جنب مع طبقة الملف؛ مرّر `None` لتعطيل البث الحي والاكتفاء بتسجيل الملف.
 /// تعيد `WorkerGuard` الذي يجب الاحتفاظ به حياً حتى نهاية البرنامج.
-pub fn init_logger(log_dir: &str, file_name: &str, level: &str) -> WorkerGuard {
-    
+pub fn init_logger(log_dir: &str, file_name: &str, level: &str, log_stream: Option<(LogStreamLayer, &str)>) -> WorkerGuard {
+
     // أ. إعداد الكتابة الدورية للملفات (Rolling File Appender)
     // يقوم بإنشاء ملف جديد كل يوم أو ساعة تلقائياً.
     let file_appender = tracing_appender::rolling::daily(log_dir, file_name);
@@ -79,10 +83,19 @@ pub fn init_logger(log_dir: &str, file_name: &str, level: &str) -> WorkerGuard {
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(level));
 
+    // و. طبقة البث الحي الاختيارية (Live Structured Stream) — مفهرسة بمستوى فلترة خاص بها،
+    // كي يتلقى الدماغ/الواجهة سجلات INFO مثلاً حتى لو كان ملف القرص مضبوطاً على DEBUG أو العكس.
+    // `Option<Layer>` يطبّق `Layer` بحد ذاته (لا عملية عند None)، فلا حاجة لفرع If/Else منفصل.
+    let stream_layer = log_stream.map(|(layer, stream_level)| {
+        let stream_filter = EnvFilter::try_new(stream_level).unwrap_or_else(|_| EnvFilter::new("info"));
+        layer.with_filter(stream_filter)
+    });
+
     // هـ. تجميع الطبقات وتعيينها عالمياً
     tracing_subscriber::registry()
         .with(filter)
         .with(file_layer)
+        .with(stream_layer)
         .init();
 
     tracing::info!("LOGGER: Initialized non-blocking logging system at {}/{}", log_dir, file_name);
@@ -90,6 +103,84 @@ pub fn init_logger(log_dir: &str, file_name: &str, level: &str) -> WorkerGuard {
     guard
 }
 
+// =================================================================
+// 4. طبقة البث الحي للسجلات (Live LogEntry Streaming Layer)
+// =================================================================
+// `LogEntry` مُعرَّف أعلاه كـ DTO "لإرسال السجلات للدماغ أو الواجهة"، لكن لا شيء كان يُنتجه
+// فعلياً من سجلات `tracing`. هذه الطبقة تفعل بالضبط ذلك: تبني `LogEntry` من كل حدث وتضعه
+// في قناة محدودة السعة يستهلكها ناشر ZMQ (أو أي ناقل آخر) دون أي حظر على مسار التداول الحرج.
+
+/// عدّاد السجلات التي أُسقطت بسبب امتلاء القناة (Backpressure). لا نطبع عند كل إسقاط لأن
+/// الطباعة نفسها بطيئة وقد تُعيد المشكلة التي نحاول تفاديها.
+static DROPPED_LOG_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// عدد السجلات المُسقطة منذ الإقلاع بسبب الضغط الخلفي على القناة.
+pub fn dropped_log_stream_count() -> u64 {
+    DROPPED_LOG_COUNT.load(Ordering::Relaxed)
+}
+
+/// طبقة `tracing_subscriber::Layer` تبني `LogEntry` من كل حدث وتدفعه بلا حظر (`try_send`)
+/// إلى قناة محدودة يستهلكها ناشر الدماغ. تحت الضغط، تُسقط السجل الجديد بدل الانتظار أو
+/// التراكم — أبداً لا يجوز لمسار التسجيل أن يُبطئ مسار التداول.
+pub struct LogStreamLayer {
+    sender: mpsc::Sender<LogEntry>,
+}
+
+impl LogStreamLayer {
+    /// ينشئ الطبقة وقناتها معاً؛ الطرف المُستهلِك (`mpsc::Receiver`) يُمرَّر لناشر ZMQ أو أي
+    /// ناقل آخر ليستنزفه في حلقته الخاصة.
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<LogEntry>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        (Self { sender }, receiver)
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for LogStreamLayer
+where
+    S: tracing::Subscriber,
+{
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let mut visitor = MessageFieldVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = LogEntry::new(level_as_str(event.metadata().level()), &visitor.message);
+
+        // try_send: لا حظر أبداً. الامتلاء يعني أن المستهلك (ناشر ZMQ) متأخر؛ نُسقط بدل الانتظار.
+        if self.sender.try_send(entry).is_err() {
+            DROPPED_LOG_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn level_as_str(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "TRACE",
+        Level::DEBUG => "DEBUG",
+        Level::INFO => "INFO",
+        Level::WARN => "WARN",
+        Level::ERROR => "ERROR",
+    }
+}
+
+/// يجمع حقول الحدث في رسالة نصية واحدة: حقل `message` إن وُجد يقود الرسالة، وأي حقول أخرى
+/// (مثل `symbol`/`reason` في `log_trade`/`log_risk_reject`) تُلحق بصيغة `key=value`.
+#[derive(Default)]
+struct MessageFieldVisitor {
+    message: String,
+}
+
+impl tracing::field::Visit for MessageFieldVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else if self.message.is_empty() {
+            self.message = format!("{}={:?}", field.name(), value);
+        } else {
+            self.message.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
 // =================================================================
 // 3. وحدات مساعدة للتسجيل المهيكل (Structured Logging Helper)
 // =================================================================
@@ -10,7 +10,7 @@
 
 use rust_decimal_macros::dec;
 use alpha_engine::matching::orderbook::OrderBook;
-use alpha_engine::models::order::{Order, OrderSide, OrderType};
+use alpha_engine::models::order::{Order, OrderSide, OrderType, TimeInForce};
 use alpha_engine::risk::pre_trade_check::{PreTradeCheck, TradeConstraints};
 use alpha_engine::utils::id;
 
@@ -119,6 +119,46 @@ async fn test_market_sweep_multiple_levels() {
     assert_eq!(snap.asks[0].quantity, dec!(3.0));
 }
 
+#[tokio::test]
+async fn test_fok_whale_order_rejected_when_ladder_insufficient() {
+    // نفس سلم السيولة في test_market_sweep_multiple_levels: 15 ETH إجمالاً
+    let mut book = OrderBook::new("ETHUSDT".into());
+    book.add_order(create_limit_order(OrderSide::Sell, 2000.0, 10.0)).unwrap();
+    book.add_order(create_limit_order(OrderSide::Sell, 2010.0, 5.0)).unwrap();
+
+    // حوت يطلب 20 ETH بينما الدفتر يعرض 15 فقط: يجب رفض الأمر بالكامل دون أي تنفيذ
+    let mut whale_order = create_market_order(OrderSide::Buy, 20.0);
+    whale_order.time_in_force = TimeInForce::FOK;
+    let res = book.add_order(whale_order);
+
+    assert!(res.is_err(), "FOK must reject rather than partially fill the whale order");
+
+    let snap = book.get_snapshot();
+    assert_eq!(snap.asks.len(), 2, "Ladder must remain fully untouched by a rejected FOK");
+    assert_eq!(snap.asks[0].executed_qty, dec!(0.0));
+}
+
+#[tokio::test]
+async fn test_fok_whale_order_fills_completely_when_ladder_sufficient() {
+    let mut book = OrderBook::new("ETHUSDT".into());
+    book.add_order(create_limit_order(OrderSide::Sell, 2000.0, 10.0)).unwrap();
+    book.add_order(create_limit_order(OrderSide::Sell, 2010.0, 5.0)).unwrap();
+
+    // نفس السلم، لكن الحوت يطلب 12 ETH (أقل من الـ 15 المتاحة): يجب أن يُنفذ بالكامل
+    let mut whale_order = create_market_order(OrderSide::Buy, 12.0);
+    whale_order.time_in_force = TimeInForce::FOK;
+    let trades = book.add_order(whale_order).unwrap();
+
+    assert_eq!(trades.len(), 2, "Whale should eat two levels in full");
+    assert_eq!(trades[0].quantity, dec!(10.0));
+    assert_eq!(trades[1].quantity, dec!(2.0));
+
+    let snap = book.get_snapshot();
+    assert_eq!(snap.asks.len(), 1, "First level fully consumed");
+    assert_eq!(snap.asks[0].price, dec!(2010.0));
+    assert_eq!(snap.asks[0].original_qty - snap.asks[0].executed_qty, dec!(3.0));
+}
+
 #[test] // اختبار متزامن (Synchronous) لوحدة المخاطر
 fn test_risk_firewall_rejection() {
     // إعداد قواعد صارمة
@@ -130,13 +170,15 @@ fn test_risk_firewall_rejection() {
         min_notional: dec!(10.0),
         max_notional: dec!(50000.0),
         max_price_deviation: dec!(0.1),
+        band_up: dec!(0.1),
+        band_down: dec!(0.1),
     };
     
     let checker = PreTradeCheck::new(constraints);
 
     // 1. سيناريو "إصبع الغباء" (Fat Finger): كمية ضخمة جداً
     let fat_finger_order = create_limit_order(OrderSide::Buy, 50000.0, 1000.0); // 50M Notional!
-    let res = checker.validate(&fat_finger_order, Some(dec!(50000.0)));
+    let res = checker.validate(&fat_finger_order, Some(dec!(50000.0)), None);
     
     assert!(res.is_err(), "Risk engine failed to stop Fat Finger order!");
     
@@ -151,28 +193,24 @@ fn test_risk_firewall_rejection() {
     // 2. سيناريو الانحراف السعري (Price Deviation)
     // محاولة شراء بسعر 60,000 بينما السوق 50,000 (+20%)
     let crazy_price_order = create_limit_order(OrderSide::Buy, 60000.0, 0.1);
-    let res2 = checker.validate(&crazy_price_order, Some(dec!(50000.0)));
+    let res2 = checker.validate(&crazy_price_order, Some(dec!(50000.0)), None);
     
     assert!(res2.is_err(), "Risk engine failed to stop price deviation!");
 }
 
 #[tokio::test]
 async fn test_self_trade_prevention() {
-    // اختبار منع التداول مع النفس (Wash Trading) - ميزة متقدمة
-    // (نفترض أن المحرك يدعمها أو أننا نختبر السلوك الافتراضي)
+    // اختبار منع التداول مع النفس (Wash Trading)
+    // الدفتر يستخدم السياسة الافتراضية (CancelNewest): يُلغى ما تبقّى من الأمر الوارد (الآخذ) دون تنفيذ صفقة، ويبقى أمر المُقيم من نفس الاستراتيجية في الدفتر.
     let mut book = OrderBook::new("BTCUSDT".into());
-    
+
     // وضع أمر بيع
     let order1 = create_limit_order(OrderSide::Sell, 50000.0, 1.0);
     // محاولة شراء نفس الكمية بنفس السعر (من نفس الاستراتيجية)
     let order2 = create_limit_order(OrderSide::Buy, 50000.0, 1.0);
-    
+
     book.add_order(order1).unwrap();
     let trades = book.add_order(order2).unwrap();
-    
-    // إذا كان لدينا STP (Self-Trade Prevention)، يجب أن يكون عدد الصفقات 0
-    // أو يتم تنفيذها إذا لم نقم بتفعيل الـ STP (حسب التصميم الحالي للمحرك)
-    // في تصميمنا الحالي، نحن نسمح بذلك تقنياً، ولكن يجب مراقبته.
-    
-    assert_eq!(trades.len(), 1, "Self-trade executed (Current design allows this, but flags it in logs)");
+
+    assert_eq!(trades.len(), 0, "STP must prevent the wash trade between identical strategy_id orders");
 }
\ No newline at end of file
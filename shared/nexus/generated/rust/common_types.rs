@@ -59,6 +59,9 @@ pub enum TimeInForce {
     GTC, // Good Till Canceled (حتى الإلغاء)
     IOC, // Immediate or Cancel (نفذ فوراً أو الغِ الباقي)
     FOK, // Fill or Kill (الكل أو لا شيء)
+    /// Good Till Date: ينتهي تلقائياً عند بلوغ `expire_at_ms`، يُفعَّل عبر
+    /// `funding_scheduler::sweep_expired_gtd_orders`
+    GTD { expire_at_ms: u64 },
 }
 
 // ==============================================================================
@@ -129,6 +132,10 @@ pub struct Position {
     // إدارة المخاطر
     pub liquidation_price: f64,
     pub margin_used: f64,
+
+    // محاسبة التمويل (Funding) للعقود الدائمة
+    pub last_funding_ms: u64,
+    pub accrued_funding: f64,
 }
 
 // ==============================================================================
@@ -177,4 +184,47 @@ impl Order {
             OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired
         )
     }
+
+    /// يوفّق حالة الأمر مع حدث `ORDER_TRADE_UPDATE` الوارد من تدفق بيانات المستخدم الخاص
+    /// بالبورصة: يحدّث الكمية المنفذة والسعر المتوسط والعمولة، ويعيد اشتقاق الكمية
+    /// المتبقية، ويترجم نص حالة البورصة إلى `OrderStatus` الداخلية. بعد هذا الاستدعاء
+    /// يصبح `is_closed()` موثوقاً لأنه يعكس ما أكدته البورصة فعلاً لا افتراضاً محلياً.
+    pub fn apply_exchange_fill(
+        &mut self,
+        exchange_status: &str,
+        filled_quantity: f64,
+        average_fill_price: f64,
+        commission: f64,
+        commission_asset: String,
+        now_ms: u64,
+    ) {
+        self.status = match exchange_status {
+            "NEW" => OrderStatus::New,
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" | "PENDING_CANCEL" => OrderStatus::Canceled,
+            "EXPIRED" => OrderStatus::Expired,
+            "REJECTED" => OrderStatus::Rejected,
+            _ => self.status, // حالة غير معروفة: نتجاهل تغيير الحالة ونحدّث الأرقام فقط
+        };
+
+        self.filled_quantity = filled_quantity;
+        self.average_fill_price = average_fill_price;
+        self.remaining_quantity = (self.request.quantity - filled_quantity).max(0.0);
+        self.commission_paid += commission;
+        self.commission_asset = commission_asset;
+        self.updated_at = now_ms;
+    }
+}
+
+impl Position {
+    /// يوفّق هذا المركز مع حدث `ACCOUNT_UPDATE` الوارد من تدفق بيانات المستخدم الخاص -
+    /// الرصيد اللحظي للكمية وسعر الدخول والربح/الخسارة غير المحقق كما تراه البورصة الآن.
+    /// ملاحظة: `liquidation_price` ليس جزءاً من حمولة `ACCOUNT_UPDATE` نفسها، لذا يبقى
+    /// كما هو هنا - إعادة اشتقاقه من الهامش والرافعة مسؤولية `MarginGuard` لا هذا الحدث.
+    pub fn apply_account_update(&mut self, quantity: f64, entry_price: f64, unrealized_pnl: f64) {
+        self.quantity = quantity;
+        self.entry_price = entry_price;
+        self.unrealized_pnl = unrealized_pnl;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,200 @@
+/*
+ * ==============================================================================
+ * ALPHA SOVEREIGN - FUNDING SETTLEMENT & GTD EXPIRY SCHEDULER
+ * ==============================================================================
+ * Component: funding_scheduler.rs
+ * Responsibility: Time-driven bookkeeping over the `common_types` DTOs - perpetual
+ *                  funding settlement on fixed UTC-aligned intervals, and sweeping
+ *                  Good-Till-Date orders into `Expired` once their deadline passes.
+ * Pattern: Scheduler / Sweep
+ * ==============================================================================
+ */
+
+use super::common_types::{Order, OrderStatus, Position, Side, TimeInForce};
+
+/// طول دورة تسوية التمويل للعقود الدائمة (Perpetual Funding Interval) - 8 ساعات، محاذاة
+/// على حدود UTC المطلقة (00:00, 08:00, 16:00) وليس نسبياً لوقت فتح كل مركز على حدة.
+pub const FUNDING_INTERVAL_MS: u64 = 8 * 60 * 60 * 1000;
+
+/// معدل هامش الصيانة الثابت (0.5%) المستخدم عند إعادة اشتقاق `liquidation_price` بعد التسوية.
+/// لا يُقرأ من إعداد `MarginGuard` في محرك المخاطر (حقل `default_maintenance_margin` هناك يُضبط
+/// من الإعداد بلا قيمة افتراضية)، ولا تُستخدم هنا جداول الشرائح المتدرجة.
+/// هذا الملف لا يستورد منه لأن طبقة `common_types` مفصولة عمداً (انظر توثيق الملف الرئيسي).
+const DEFAULT_MAINTENANCE_MARGIN_RATE: f64 = 0.005;
+
+/// حدث انتهاء صلاحية أمر GTD - يوازي `EgressEvent::OrderStatusUpdate` في طبقة النقل
+/// الحقيقية للمحرك، لكن هذا الملف DTO مستقل ولا يستورد منها.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderExpiredEvent {
+    pub internal_id: String,
+    pub expired_at_ms: u64,
+}
+
+/// أقرب ختم زمني قادم لتسوية التمويل بالنسبة لـ `now_ms`، محاذى على حدود
+/// `FUNDING_INTERVAL_MS` المطلقة منذ حقبة UNIX - تماماً مثل إعادة ترحيل المركز المجدولة
+/// على وقت UTC ثابت، بحيث يمكن للمستدعي معرفة موعد القطع مقدماً دون الحاجة لانتظاره.
+pub fn next_funding_timestamp(now_ms: u64) -> u64 {
+    (now_ms / FUNDING_INTERVAL_MS + 1) * FUNDING_INTERVAL_MS
+}
+
+/// يفحص كل الأوامر النشطة ويحوّل أي أمر GTD تجاوز موعده إلى `Expired`، مصدراً حدثاً لكل
+/// أمر تمت تهيئته. الأوامر المغلقة مسبقاً (`is_closed`) تُتجاهل لأن `Expired` لا يلغي نتيجة
+/// تنفيذ أو إلغاء سابقة.
+pub fn sweep_expired_gtd_orders(orders: &mut [Order], now_ms: u64) -> Vec<OrderExpiredEvent> {
+    let mut expired = Vec::new();
+
+    for order in orders.iter_mut() {
+        if order.is_closed() {
+            continue;
+        }
+
+        if let TimeInForce::GTD { expire_at_ms } = order.request.time_in_force {
+            if now_ms >= expire_at_ms {
+                order.status = OrderStatus::Expired;
+                order.updated_at = now_ms;
+                expired.push(OrderExpiredEvent {
+                    internal_id: order.internal_id.clone(),
+                    expired_at_ms: now_ms,
+                });
+            }
+        }
+    }
+
+    expired
+}
+
+/// يطبّق تسوية تمويل واحدة على مركز واحد: `funding_rate * position_notional` يُحسم من
+/// `realized_pnl` للطويل ويُضاف إليه للقصير (الطويل يدفع معدلاً موجباً، القصير يقبضه)، ثم يُطبَّق
+/// نفس المبلغ بإشارته على `margin_used` (ينقص للطويل ويزيد للقصير، ولا ينزل تحت الصفر)، وأخيراً
+/// يُعاد اشتقاق `liquidation_price` من سعر الدخول والرافعة ومعدل الصيانة الثابت وحدها، فلا يدخل
+/// `margin_used` في حسابه.
+pub fn settle_funding(position: &mut Position, funding_rate: f64, now_ms: u64) {
+    let position_notional = position.quantity.abs() * position.current_price;
+    let funding_payment = funding_rate * position_notional;
+
+    // المراكز الطويلة (Buy) تدفع تمويلاً موجباً؛ القصيرة (Sell) تقبضه - الإشارة معكوسة.
+    let signed_payment = match position.side {
+        Side::Buy => -funding_payment,
+        Side::Sell => funding_payment,
+    };
+
+    position.realized_pnl += signed_payment;
+    position.margin_used = (position.margin_used + signed_payment).max(0.0);
+    position.last_funding_ms = now_ms;
+    position.accrued_funding += funding_payment;
+
+    position.liquidation_price = recalculate_liquidation_price(position);
+}
+
+/// مقاربة لسعر التسييل: Entry * (1 -/+ (1/Leverage - MMR))، حسب اتجاه المركز. تختلف عن
+/// `MarginGuard::calculate_liquidation_prices` التي تحسب من الحقوق (equity) ومبلغ خصم الشريحة.
+fn recalculate_liquidation_price(position: &Position) -> f64 {
+    if position.leverage == 0 {
+        return position.liquidation_price;
+    }
+
+    let risk_factor = (1.0 / position.leverage as f64) - DEFAULT_MAINTENANCE_MARGIN_RATE;
+
+    match position.side {
+        Side::Buy => position.entry_price * (1.0 - risk_factor),
+        Side::Sell => position.entry_price * (1.0 + risk_factor),
+    }
+}
+
+// ==============================================================================
+// UNIT TESTS
+// ==============================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::common_types::{OrderRequest, OrderType};
+
+    fn sample_order(time_in_force: TimeInForce) -> Order {
+        let req = OrderRequest {
+            request_id: "req-1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            quantity: 1.0,
+            price: Some(50000.0),
+            stop_price: None,
+            leverage: 10,
+            time_in_force,
+            timestamp: 0,
+        };
+        Order::new(req)
+    }
+
+    fn sample_position(side: Side) -> Position {
+        Position {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            quantity: 1.0,
+            entry_price: 50000.0,
+            current_price: 50000.0,
+            leverage: 10,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            liquidation_price: 45000.0,
+            margin_used: 5000.0,
+            last_funding_ms: 0,
+            accrued_funding: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_next_funding_timestamp_aligns_to_utc_boundary() {
+        let one_hour_past_boundary = FUNDING_INTERVAL_MS + 3_600_000;
+        assert_eq!(next_funding_timestamp(one_hour_past_boundary), FUNDING_INTERVAL_MS * 2);
+        assert_eq!(next_funding_timestamp(0), FUNDING_INTERVAL_MS);
+    }
+
+    #[test]
+    fn test_sweep_expires_past_due_gtd_order_only() {
+        let mut orders = vec![
+            sample_order(TimeInForce::GTD { expire_at_ms: 1000 }),
+            sample_order(TimeInForce::GTC),
+            sample_order(TimeInForce::GTD { expire_at_ms: 5000 }),
+        ];
+
+        let expired = sweep_expired_gtd_orders(&mut orders, 2000);
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(orders[0].status, OrderStatus::Expired);
+        assert_eq!(orders[1].status, OrderStatus::Pending);
+        assert_eq!(orders[2].status, OrderStatus::Pending);
+    }
+
+    #[test]
+    fn test_sweep_ignores_already_closed_orders() {
+        let mut order = sample_order(TimeInForce::GTD { expire_at_ms: 1000 });
+        order.status = OrderStatus::Filled;
+        let mut orders = vec![order];
+
+        let expired = sweep_expired_gtd_orders(&mut orders, 2000);
+
+        assert!(expired.is_empty());
+        assert_eq!(orders[0].status, OrderStatus::Filled);
+    }
+
+    #[test]
+    fn test_settle_funding_long_pays_positive_rate() {
+        let mut position = sample_position(Side::Buy);
+        settle_funding(&mut position, 0.0001, 12345);
+
+        // Notional = 1.0 * 50000 = 50000; payment = 5.0; long pays, so realized_pnl decreases.
+        assert_eq!(position.realized_pnl, -5.0);
+        assert_eq!(position.margin_used, 4995.0);
+        assert_eq!(position.last_funding_ms, 12345);
+        assert_eq!(position.accrued_funding, 5.0);
+    }
+
+    #[test]
+    fn test_settle_funding_short_receives_positive_rate() {
+        let mut position = sample_position(Side::Sell);
+        settle_funding(&mut position, 0.0001, 12345);
+
+        assert_eq!(position.realized_pnl, 5.0);
+        assert_eq!(position.margin_used, 5005.0);
+    }
+}
@@ -9,8 +9,12 @@
  */
 
 use std::net::IpAddr;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use std::str::FromStr;
+use parking_lot::RwLock;
 use std::collections::HashSet;
+use ipnet::IpNet;
+use serde::Deserialize;
 use tracing::{info, warn, error};
 use crate::error::{AlphaError, AlphaResult};
 
@@ -40,16 +44,87 @@ pub struct TrafficContext {
     pub protocol: String, // "TCP", "UDP", "gRPC"
 }
 
-/// قاعدة جدار ناري
+/// قاعدة جدار ناري. `allowed_networks` نطاقات CIDR (`IpNet`) بدل عناوين مفردة، لأن عناوين
+/// البورصات تتغير ضمن مدى معروف ولا يمكن تثبيتها على IP واحد.
 #[derive(Clone)]
-struct FirewallRule {
+pub struct FirewallRule {
     name: String,
     allowed_ports: HashSet<u16>,
-    // في التطبيق الفعلي نستخدم IpNetwork للتعامل مع CIDR
-    allowed_ips: HashSet<IpAddr>, 
+    allowed_networks: Vec<IpNet>,
     min_defcon: SystemDefcon, // القاعدة فعالة فقط إذا كان النظام في هذا المستوى أو أعلى
 }
 
+impl FirewallRule {
+    pub fn new(
+        name: impl Into<String>,
+        allowed_ports: HashSet<u16>,
+        allowed_networks: Vec<IpNet>,
+        min_defcon: SystemDefcon,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            allowed_ports,
+            allowed_networks,
+            min_defcon,
+        }
+    }
+}
+
+/// توصيف قاعدة كما يُقرأ من مصدر إعدادات خارجي (TOML) قبل تحويله إلى `FirewallRule` فعالة -
+/// النطاقات هنا سلاسل نصية بصيغة CIDR (`"52.0.0.0/8"`) تُفسَّر في `parse_rule_spec`، ومستوى
+/// الدفاع رقم خام (1..=5) يطابق القيم العددية لـ `SystemDefcon`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FirewallRuleSpec {
+    pub name: String,
+    pub allowed_ports: Vec<u16>,
+    pub allowed_networks: Vec<String>,
+    pub min_defcon: u8,
+}
+
+fn defcon_from_level(level: u8) -> AlphaResult<SystemDefcon> {
+    match level {
+        5 => Ok(SystemDefcon::Level5_Normal),
+        4 => Ok(SystemDefcon::Level4_HighAlert),
+        3 => Ok(SystemDefcon::Level3_NoTrade),
+        2 => Ok(SystemDefcon::Level2_Lockdown),
+        1 => Ok(SystemDefcon::Level1_Omega),
+        other => Err(AlphaError::ConfigMissing(format!("Invalid DEFCON level in rule spec: {}", other))),
+    }
+}
+
+fn parse_rule_spec(spec: FirewallRuleSpec) -> AlphaResult<FirewallRule> {
+    let allowed_networks = spec.allowed_networks.iter()
+        .map(|cidr| IpNet::from_str(cidr)
+            .map_err(|e| AlphaError::ConfigMissing(format!("Invalid CIDR '{}' in rule '{}': {}", cidr, spec.name, e))))
+        .collect::<AlphaResult<Vec<IpNet>>>()?;
+
+    Ok(FirewallRule::new(
+        spec.name,
+        HashSet::from_iter(spec.allowed_ports),
+        allowed_networks,
+        defcon_from_level(spec.min_defcon)?,
+    ))
+}
+
+/// يحمّل مجموعة قواعد من مصدر TOML نصي (ملف إعدادات أو حمولة مُرسَلة من لوحة التحكم)، ليتم
+/// تمريرها بعد ذلك لـ `FirewallRulesEngine::reload_rules` كتبديل ذري واحد.
+pub fn load_rules_from_toml(raw: &str) -> AlphaResult<Vec<FirewallRule>> {
+    #[derive(Debug, Deserialize)]
+    struct RuleSetFile {
+        rules: Vec<FirewallRuleSpec>,
+    }
+
+    let settings = config::Config::builder()
+        .add_source(config::File::from_str(raw, config::FileFormat::Toml))
+        .build()
+        .map_err(|e| AlphaError::ConfigMissing(format!("Firewall rule set build error: {}", e)))?;
+
+    let parsed: RuleSetFile = settings.try_deserialize()
+        .map_err(|e| AlphaError::ConfigMissing(format!("Firewall rule set parse error: {}", e)))?;
+
+    parsed.rules.into_iter().map(parse_rule_spec).collect()
+}
+
 pub struct FirewallRulesEngine {
     current_defcon: Arc<RwLock<SystemDefcon>>,
     rules: Arc<RwLock<Vec<FirewallRule>>>,
@@ -70,31 +145,40 @@ impl FirewallRulesEngine {
     }
 
     fn load_default_rules(&self) {
-        let mut rules = self.rules.write().unwrap();
-        
+        let mut rules = self.rules.write();
+
         // القاعدة 1: السماح بـ Localhost دائماً (للإدارة الداخلية)
         // فعالة حتى في حالة Lockdown (Level 2)
-        rules.push(FirewallRule {
-            name: "Allow Localhost Internal".to_string(),
-            allowed_ports: HashSet::from([5555, 50051, 8080]), // ZMQ, gRPC, Web
-            allowed_ips: HashSet::from(["127.0.0.1".parse().unwrap()]),
-            min_defcon: SystemDefcon::Level2_Lockdown,
-        });
+        rules.push(FirewallRule::new(
+            "Allow Localhost Internal",
+            HashSet::from([5555, 50051, 8080]), // ZMQ, gRPC, Web
+            vec![IpNet::from_str("127.0.0.1/32").unwrap()],
+            SystemDefcon::Level2_Lockdown,
+        ));
 
         // القاعدة 2: السماح بالبورصات (Binance API)
         // فعالة فقط في الوضع الطبيعي والتحذير (Level 4+)
-        // ملاحظة: IPs البورصات تتغير، هنا نضع مثالاً
-        rules.push(FirewallRule {
-            name: "Allow Exchange API".to_string(),
-            allowed_ports: HashSet::from([443]),
-            allowed_ips: HashSet::from(["1.1.1.1".parse().unwrap()]), // Example IP
-            min_defcon: SystemDefcon::Level4_HighAlert,
-        });
+        // نطاق CIDR بدل عنوان مفرد لأن IPs البورصات تتناوب ضمن مدى معروف
+        rules.push(FirewallRule::new(
+            "Allow Exchange API",
+            HashSet::from([443]),
+            vec![IpNet::from_str("52.0.0.0/8").unwrap()], // Example Binance-like range
+            SystemDefcon::Level4_HighAlert,
+        ));
+    }
+
+    /// استبدال مجموعة القواعد بالكامل بشكل ذري - يسمح للمشغّلين بدفع قوائم سماح جديدة
+    /// (بما فيها قواعد مقيّدة بـ DEFCON) دون إعادة تشغيل المحرك. القراء الذين يحملون قفل
+    /// `rules.read()` حالياً في `check_traffic` يكملون على اللقطة القديمة حتى يحرروه.
+    pub fn reload_rules(&self, new_rules: Vec<FirewallRule>) {
+        let mut rules = self.rules.write();
+        info!("FIREWALL: Reloading rule set ({} rules).", new_rules.len());
+        *rules = new_rules;
     }
 
     /// تغيير مستوى الدفاع (State Transition)
     pub fn set_defcon(&self, level: SystemDefcon) {
-        let mut defcon = self.current_defcon.write().unwrap();
+        let mut defcon = self.current_defcon.write();
         if *defcon != level {
             warn!("FIREWALL: DEFCON Level changed from {:?} to {:?}", *defcon, level);
             *defcon = level;
@@ -106,17 +190,17 @@ impl FirewallRulesEngine {
     /// Output: Allowed (true) / Blocked (false)
     pub fn check_traffic(&self, ctx: &TrafficContext) -> bool {
         // 1. فحص القائمة السوداء أولاً (Fast Reject)
-        let blacklist = self.blacklist.read().unwrap();
+        let blacklist = self.blacklist.read();
         if blacklist.contains(&ctx.source_ip) {
             warn!("FIREWALL_BLOCK: IP {:?} is blacklisted.", ctx.source_ip);
             return false;
         }
 
         // 2. الحصول على مستوى النظام الحالي
-        let current_defcon = *self.current_defcon.read().unwrap();
+        let current_defcon = *self.current_defcon.read();
 
         // 3. تقييم القواعد
-        let rules = self.rules.read().unwrap();
+        let rules = self.rules.read();
         
         for rule in rules.iter() {
             // هل القاعدة نشطة في هذا المستوى الأمني؟
@@ -125,8 +209,8 @@ impl FirewallRulesEngine {
                 continue;
             }
 
-            // مطابقة القاعدة
-            let ip_match = rule.allowed_ips.contains(&ctx.source_ip) || rule.allowed_ips.contains(&"0.0.0.0".parse().unwrap());
+            // مطابقة القاعدة: احتواء شبكة CIDR بدل تساوي عنوان مفرد
+            let ip_match = rule.allowed_networks.iter().any(|net| net.contains(&ctx.source_ip));
             let port_match = rule.allowed_ports.contains(&ctx.dest_port);
 
             if ip_match && port_match {
@@ -147,7 +231,7 @@ impl FirewallRulesEngine {
 
     /// إضافة IP للقائمة السوداء (ديناميكياً من IDS)
     pub fn block_ip(&self, ip: IpAddr) {
-        let mut blacklist = self.blacklist.write().unwrap();
+        let mut blacklist = self.blacklist.write();
         blacklist.insert(ip);
         warn!("FIREWALL: Added {:?} to dynamic blacklist.", ip);
     }
@@ -159,18 +243,17 @@ impl FirewallRulesEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
 
     #[test]
     fn test_lockdown_blocks_external_traffic() {
         let fw = FirewallRulesEngine::new();
-        
+
         // 1. الوضع الطبيعي (Normal)
         fw.set_defcon(SystemDefcon::Level5_Normal);
-        
-        // محاكاة اتصال من البورصة
+
+        // محاكاة اتصال من البورصة (ضمن نطاق CIDR الافتراضي 52.0.0.0/8)
         let exchange_ctx = TrafficContext {
-            source_ip: IpAddr::from_str("1.1.1.1").unwrap(),
+            source_ip: IpAddr::from_str("52.1.2.3").unwrap(),
             dest_port: 443,
             direction: TrafficDirection::Outbound,
             protocol: "HTTPS".into(),
@@ -211,4 +294,62 @@ mod tests {
 
         assert!(!fw.check_traffic(&ctx), "Blacklisted IP should be blocked immediately");
     }
+
+    #[test]
+    fn test_cidr_match_rejects_address_outside_range() {
+        let fw = FirewallRulesEngine::new();
+        fw.set_defcon(SystemDefcon::Level5_Normal);
+
+        // خارج نطاق 52.0.0.0/8 الافتراضي للبورصات
+        let outsider_ctx = TrafficContext {
+            source_ip: IpAddr::from_str("9.9.9.9").unwrap(),
+            dest_port: 443,
+            direction: TrafficDirection::Outbound,
+            protocol: "HTTPS".into(),
+        };
+
+        assert!(!fw.check_traffic(&outsider_ctx), "Address outside every rule's CIDR range must be denied");
+    }
+
+    #[test]
+    fn test_reload_rules_swaps_atomically() {
+        let fw = FirewallRulesEngine::new();
+        fw.set_defcon(SystemDefcon::Level5_Normal);
+
+        let custom_ip = IpAddr::from_str("10.0.0.5").unwrap();
+        let custom_ctx = TrafficContext {
+            source_ip: custom_ip,
+            dest_port: 9000,
+            direction: TrafficDirection::Inbound,
+            protocol: "TCP".into(),
+        };
+
+        // القواعد الافتراضية لا تسمح بهذا العنوان/المنفذ
+        assert!(!fw.check_traffic(&custom_ctx));
+
+        fw.reload_rules(vec![FirewallRule::new(
+            "Allow Internal VPC",
+            HashSet::from([9000]),
+            vec![IpNet::from_str("10.0.0.0/8").unwrap()],
+            SystemDefcon::Level5_Normal,
+        )]);
+
+        assert!(fw.check_traffic(&custom_ctx), "Reloaded rule set should take effect immediately");
+    }
+
+    #[test]
+    fn test_load_rules_from_toml_parses_cidr_and_defcon() {
+        let raw = r#"
+            [[rules]]
+            name = "Allow Ops Subnet"
+            allowed_ports = [22, 443]
+            allowed_networks = ["172.16.0.0/12"]
+            min_defcon = 3
+        "#;
+
+        let rules = load_rules_from_toml(raw).expect("valid rule set should parse");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "Allow Ops Subnet");
+        assert!(rules[0].allowed_networks[0].contains(&IpAddr::from_str("172.20.0.1").unwrap()));
+    }
 }
\ No newline at end of file
@@ -12,15 +12,97 @@
 
 use wasmtime::*;
 use anyhow::Result;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error};
 use crate::error::{AlphaError, AlphaResult};
+use crate::matching::Order;
+use crate::transport::{EventTx, IngressEvent};
+
+/// الفاصل الزمني بين كل نبضتي `increment_epoch` من خيط المراقبة (Watchdog) - كلما صغر،
+/// زادت دقة حصر زمن الاستجابة، على حساب بعض الحمل الإضافي على النواة المراقبة.
+const EPOCH_TICK_MS: u64 = 1;
+
+/// ميزانية زمن الاستجابة المسموحة لكل `execute_tick` واحد، مقوَّمة بعدد نبضات Epoch.
+/// تجاوزها يعني أن الاستراتيجية حجبت خيط التنفيذ (حلقة لا نهائية داخل نداء مضيف مثلاً) بما
+/// يتجاوز ما يستطيع الوقود وحده رصده.
+const WALL_CLOCK_BUDGET_MS: u64 = 5;
+const WALL_CLOCK_BUDGET_TICKS: u64 = WALL_CLOCK_BUDGET_MS / EPOCH_TICK_MS;
+
+/// صيغة سلكية مبسّطة لمركز مفتوح، تُستخدم فقط عبر حدود المضيف/الضيف في `host_get_position` -
+/// ليست نوع المركز الداخلي الكامل للمحرك، بل إسقاط منه كافٍ لاتخاذ قرار استراتيجية.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuestPosition {
+    pub symbol: String,
+    pub quantity: f64,
+    pub entry_price: f64,
+    pub unrealized_pnl: f64,
+}
+
+/// تكلفة الوقود الثابتة لكل نداء مضيف (Host Call) - تضمن أن عمل المضيف نفسه يُحتسَب
+/// ضمن حصة المعالج الخاصة بالاستراتيجية، لا أن يُستنزف فقط على تعليمات الضيف.
+const HOST_CALL_FUEL_COST: u64 = 500;
 
 /// سياق المتداول داخل الـ Wasm (يحتوي على الحالة والوقود)
 struct TraderContext {
     wasi_ctx: wasi_common::WasiCtx,
     limits: StoreLimits,
     strategy_id: String,
+    /// ناقل الأحداث الداخلي - يسمح لـ `host_place_order` بدفع أوامر حقيقية للمحرك
+    event_bus: EventTx,
+    /// أحدث مراكز معروفة، يقرأ منها `host_get_position` فقط (لا يكتب الضيف هنا أبداً)
+    positions: Arc<Mutex<HashMap<String, GuestPosition>>>,
+}
+
+/// يقرأ شريحة بايتات من ذاكرة الضيف المصدَّرة (`memory`)، مع التحقق من الحدود قبل كل وصول.
+/// أي تجاوز هنا يُترجَم لـ Trap بدل Undefined Behavior.
+fn read_guest_memory(caller: &mut Caller<'_, TraderContext>, ptr: i32, len: i32) -> Result<Vec<u8>, Trap> {
+    let memory = caller.get_export("memory")
+        .and_then(|ext| ext.into_memory())
+        .ok_or_else(|| Trap::new("host ABI: guest module does not export 'memory'"))?;
+
+    if ptr < 0 || len < 0 {
+        return Err(Trap::new("host ABI: negative ptr/len"));
+    }
+    let start = ptr as usize;
+    let end = start.checked_add(len as usize)
+        .ok_or_else(|| Trap::new("host ABI: ptr+len overflow"))?;
+
+    memory.data(&*caller)
+        .get(start..end)
+        .map(|bytes| bytes.to_vec())
+        .ok_or_else(|| Trap::new("host ABI: out-of-bounds guest memory access"))
+}
+
+/// يكتب بايتات إلى ذاكرة الضيف عند `ptr`، للاستجابات التي يحتاجها المضيف أن يعيدها للضيف
+/// (مثل `host_get_position`). يتحقق من الحدود بنفس صرامة `read_guest_memory`.
+fn write_guest_memory(caller: &mut Caller<'_, TraderContext>, ptr: i32, bytes: &[u8]) -> Result<(), Trap> {
+    let memory = caller.get_export("memory")
+        .and_then(|ext| ext.into_memory())
+        .ok_or_else(|| Trap::new("host ABI: guest module does not export 'memory'"))?;
+
+    if ptr < 0 {
+        return Err(Trap::new("host ABI: negative ptr"));
+    }
+    let start = ptr as usize;
+    let end = start.checked_add(bytes.len())
+        .ok_or_else(|| Trap::new("host ABI: ptr+len overflow"))?;
+
+    let slice = memory.data_mut(&mut *caller)
+        .get_mut(start..end)
+        .ok_or_else(|| Trap::new("host ABI: out-of-bounds guest memory access (write)"))?;
+    slice.copy_from_slice(bytes);
+    Ok(())
+}
+
+/// يخصم وقود كل نداء مضيف من ميزانية الاستراتيجية - نداء مضيف مكلف (كتسلسل أوامر) لا يجب
+/// أن يكون "مجانياً" مقارنة بتعليمات الضيف العادية.
+fn charge_host_fuel(caller: &mut Caller<'_, TraderContext>) -> Result<(), Trap> {
+    caller.consume_fuel(HOST_CALL_FUEL_COST)
+        .map(|_| ())
+        .map_err(|_| Trap::new("host ABI: strategy ran out of fuel servicing a host call"))
 }
 
 pub struct WasmRuntime {
@@ -33,6 +115,7 @@ pub struct ActiveStrategy {
     instance: Instance,
     // الدوال المصدرة من الـ Wasm (Exported Functions)
     on_tick_fn: TypedFunc<(f64, f64), i32>, // Input: (Price, Vol), Output: Decision
+    strategy_id: String,
 }
 
 impl WasmRuntime {
@@ -45,6 +128,17 @@ impl WasmRuntime {
         let engine = Engine::new(&config)
             .map_err(|e| AlphaError::BootstrapError(format!("Wasm Engine Init Failed: {}", e)))?;
 
+        // خيط المراقبة (Watchdog): المصدر الوحيد لزيادة الـ Epoch. طالما لم يستدعِ أحد
+        // `increment_epoch`، فإن `epoch_interruption` بلا أثر فعلي - هذا الخيط هو ما يجعلها
+        // أداة حصر زمني حقيقية بدل علم معطّل في الإعدادات.
+        {
+            let watchdog_engine = engine.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(Duration::from_millis(EPOCH_TICK_MS));
+                watchdog_engine.increment_epoch();
+            });
+        }
+
         let mut linker = Linker::new(&engine);
         
         // ربط مكتبة WASI (للسماح بالوظائف الأساسية المحدودة)
@@ -52,19 +146,72 @@ impl WasmRuntime {
             .map_err(|e| AlphaError::BootstrapError(format!("WASI Link Error: {}", e)))?;
 
         // تعريف دوال المضيف (Host Functions) - ما يسمح للـ Wasm بفعله
-        // مثال: السماح للـ Wasm بالتسجيل في سجلات النظام
-        linker.func_wrap("env", "host_log", |mut caller: Caller<'_, TraderContext>, ptr: i32, len: i32| {
-            // منطق قراءة النص من ذاكرة Wasm وطباعته (معقد قليلاً ويتطلب Memory View)
-            // للتبسيط: نسجل فقط أن الاستراتيجية نادت الدالة
-            let id = &caller.data().strategy_id;
-            info!("WASM_GUEST [{}]: Called host_log (ptr: {}, len: {})", id, ptr, len);
+        // كل دالة هنا تقرأ/تكتب ذاكرة الضيف عبر ABI موحّد (ptr, len) وتخصم وقوداً ثابتاً
+        // لكل نداء، كي لا يصبح العمل داخل المضيف نفسه "مجانياً" بالنسبة لحصة المعالج.
+
+        // host_log(ptr, len): يفك ترميز UTF-8 من ذاكرة الضيف ويطبعه في سجلات النظام
+        linker.func_wrap("env", "host_log", |mut caller: Caller<'_, TraderContext>, ptr: i32, len: i32| -> Result<(), Trap> {
+            charge_host_fuel(&mut caller)?;
+            let bytes = read_guest_memory(&mut caller, ptr, len)?;
+            let message = String::from_utf8_lossy(&bytes).into_owned();
+            let strategy_id = caller.data().strategy_id.clone();
+            info!("WASM_GUEST [{}]: {}", strategy_id, message);
+            Ok(())
+        }).unwrap();
+
+        // host_place_order(ptr, len) -> i32: يفك ترميز `Order` مُرمَّز بـ bincode من ذاكرة
+        // الضيف ويدفعه على ناقل الأحداث كـ `IngressEvent::NewOrderRequest`. يعيد 0 عند
+        // النجاح، -1 عند حمولة تالفة، -2 إن امتلأت القناة (اختناق المحرك).
+        linker.func_wrap("env", "host_place_order", |mut caller: Caller<'_, TraderContext>, ptr: i32, len: i32| -> Result<i32, Trap> {
+            charge_host_fuel(&mut caller)?;
+            let bytes = read_guest_memory(&mut caller, ptr, len)?;
+            let strategy_id = caller.data().strategy_id.clone();
+
+            let order: Order = match bincode::deserialize(&bytes) {
+                Ok(order) => order,
+                Err(e) => {
+                    warn!("WASM_GUEST [{}]: host_place_order got malformed OrderRequest: {}", strategy_id, e);
+                    return Ok(-1);
+                }
+            };
+
+            match caller.data().event_bus.try_send(IngressEvent::NewOrderRequest(order)) {
+                Ok(()) => Ok(0),
+                Err(e) => {
+                    warn!("WASM_GUEST [{}]: host_place_order dropped (engine backpressure): {}", strategy_id, e);
+                    Ok(-2)
+                }
+            }
+        }).unwrap();
+
+        // host_get_position(symbol_ptr, len, out_ptr) -> i32: يقرأ اسم الرمز من ذاكرة الضيف،
+        // يبحث عن آخر `GuestPosition` معروف له، ويكتب نسخته المُرمَّزة بـ bincode في
+        // `out_ptr` داخل ذاكرة الضيف. يعيد عدد البايتات المكتوبة، أو -1 إن لم يوجد مركز.
+        linker.func_wrap("env", "host_get_position", |mut caller: Caller<'_, TraderContext>, symbol_ptr: i32, symbol_len: i32, out_ptr: i32| -> Result<i32, Trap> {
+            charge_host_fuel(&mut caller)?;
+            let symbol_bytes = read_guest_memory(&mut caller, symbol_ptr, symbol_len)?;
+            let symbol = String::from_utf8_lossy(&symbol_bytes).into_owned();
+
+            let position = caller.data().positions.lock().unwrap().get(&symbol).cloned();
+            let Some(position) = position else { return Ok(-1); };
+
+            let encoded = bincode::serialize(&position)
+                .map_err(|e| Trap::new(format!("host ABI: failed to encode Position for guest: {}", e)))?;
+            write_guest_memory(&mut caller, out_ptr, &encoded)?;
+            Ok(encoded.len() as i32)
         }).unwrap();
 
         Ok(Self { engine, linker })
     }
 
     /// تحميل وتشغيل استراتيجية جديدة
-    pub fn load_strategy(&self, strategy_id: &str, wasm_bytes: &[u8]) -> AlphaResult<ActiveStrategy> {
+    pub fn load_strategy(
+        &self,
+        strategy_id: &str,
+        wasm_bytes: &[u8],
+        event_bus: EventTx,
+        positions: Arc<Mutex<HashMap<String, GuestPosition>>>,
+    ) -> AlphaResult<ActiveStrategy> {
         info!("WASM_RUNTIME: JIT Compiling strategy '{}' ({} bytes)...", strategy_id, wasm_bytes.len());
 
         // 1. تجميع الكود (Compilation)
@@ -84,14 +231,23 @@ impl WasmRuntime {
             wasi_ctx: wasi,
             limits,
             strategy_id: strategy_id.to_string(),
+            event_bus,
+            positions,
         };
 
         let mut store = Store::new(&self.engine, context);
-        
+
         // منح "وقود" للمعالجة (مثلاً 10 ملايين تعليمة)
         store.add_fuel(10_000_000).unwrap();
         store.limiter(|s| &mut s.limits);
 
+        // حارس الزمن الحقيقي (Wall-Clock Watchdog): عند بلوغ الموعد النهائي المضبوط عبر
+        // `set_epoch_deadline` في كل `execute_tick`، يُستدعى هذا الرد فوراً ويُسقِط الاستدعاء
+        // بـ Trap مميَّز - هذا يرصد الانحباس حتى داخل نداء مضيف طويل لا يراه الوقود إطلاقاً.
+        store.epoch_deadline_callback(|_store_data| -> Result<UpdateDeadline> {
+            Err(anyhow::anyhow!("WALL_CLOCK_DEADLINE_EXCEEDED"))
+        });
+
         // 3. إنشاء النسخة (Instantiation)
         let instance = self.linker.instantiate(&mut store, &module)
             .map_err(|e| AlphaError::Internal(format!("Wasm Instantiation Error: {}", e)))?;
@@ -107,6 +263,7 @@ impl WasmRuntime {
             store,
             instance,
             on_tick_fn,
+            strategy_id: strategy_id.to_string(),
         })
     }
 }
@@ -119,6 +276,10 @@ impl ActiveStrategy {
         self.store.consume_fuel(0).unwrap(); // Reset logic (depends on Wasmtime version)
         self.store.add_fuel(100_000).unwrap();
 
+        // ضبط الموعد النهائي لزمن الاستجابة لهذه الدورة وحدها - يُعاد ضبطه في كل نداء
+        // تماماً كما يُعاد ضبط الوقود، فالموعد السابق يُستهلَك بمجرد بلوغه أو تجاوزه
+        self.store.set_epoch_deadline(WALL_CLOCK_BUDGET_TICKS);
+
         // استدعاء الدالة
         match self.on_tick_fn.call(&mut self.store, (price, volume)) {
             Ok(decision) => Ok(decision),
@@ -132,6 +293,14 @@ impl ActiveStrategy {
                         });
                     }
                 }
+                // الموعد النهائي للـ Epoch لا يُترجَم لـ Trap بكود ثابت، بل لرسالة الخطأ التي
+                // أعادها epoch_deadline_callback - نميّزه عبر محتوى الرسالة بدل كود Trap
+                if e.to_string().contains("WALL_CLOCK_DEADLINE_EXCEEDED") {
+                    error!("WASM_TRAP: Strategy '{}' blew its {}ms wall-clock latency budget!", self.strategy_id, WALL_CLOCK_BUDGET_MS);
+                    return Err(AlphaError::RiskViolation {
+                        rule: "WALL_CLOCK".into(), limit: format!("{}ms", WALL_CLOCK_BUDGET_MS), actual: "EXCEEDED".into()
+                    });
+                }
                 error!("WASM_CRASH: Runtime error: {}", e);
                 Err(AlphaError::Internal(format!("Wasm Execution Failed: {}", e)))
             }
@@ -42,17 +42,94 @@ pub enum ComponentHealth {
     Unresponsive(u64),  // تأخير خطير (ميت أو عالق)
 }
 
+/// إجراء تعافٍ قابل للتوصيل: يُنفَّذ عند تصعيد مكون إلى حالة `Unresponsive` مستمرة
+/// تتجاوز عتبة الفحوصات المتتالية المسجَّلة له عبر `on_unhealthy`. التنفيذ الفعلي
+/// (إشارة إعادة تشغيل، حجر صحي، وضع أمان عام...) متروك للمستدعي.
+pub trait RecoveryAction: Send + Sync {
+    fn execute(&self, component: &str, consecutive_failures: u32);
+}
+
+/// سياسة تصعيد مكون واحد: كم فحصاً متتالياً من `Unresponsive` قبل تنفيذ `action`.
+struct EscalationPolicy {
+    trigger_after: u32,
+    action: Arc<dyn RecoveryAction>,
+}
+
 /// المراقب المركزي للنبضات
 pub struct PulseMonitor {
     /// سجل المكونات المراقبة
     /// الاسم -> (آخر نبضة، الحد المسموح)
     registry: RwLock<HashMap<String, (Arc<AtomicU64>, u64)>>,
+
+    /// سياسات التصعيد المسجَّلة لكل مكون، عبر `on_unhealthy`.
+    escalation_policies: RwLock<HashMap<String, EscalationPolicy>>,
+
+    /// حالة التصعيد الجارية لكل مكون: (عدد الفحوصات المتتالية Unresponsive، وقت آخر
+    /// إجراء تعافٍ نُفِّذ له). تُصفَّر عدّادات المكون بمجرد غيابه عن تقرير فحص لاحق
+    /// (أي عودته لحالة سليمة)، فلا يتكرر نفس الإجراء الجسيم مع كل نبضة متعثرة لاحقة.
+    escalation_state: RwLock<HashMap<String, (u32, Option<u64>)>>,
 }
 
 impl PulseMonitor {
     pub fn new() -> Self {
         Self {
             registry: RwLock::new(HashMap::new()),
+            escalation_policies: RwLock::new(HashMap::new()),
+            escalation_state: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// تسجيل سياسة تصعيد لمكون: إن بقي `Unresponsive` لـ `trigger_after` فحصاً متتالياً،
+    /// يُنفَّذ `action` مرة واحدة ثم يُصفَّر العدّاد (Debounce) بدل تكراره مع كل فحص لاحق.
+    pub fn on_unhealthy(&self, name: &str, trigger_after: u32, action: Arc<dyn RecoveryAction>) {
+        self.escalation_policies.write().unwrap()
+            .insert(name.to_string(), EscalationPolicy { trigger_after, action });
+        info!("PULSE: Escalation policy registered for '{}' (trigger after {} consecutive checks)", name, trigger_after);
+    }
+
+    /// يُحدِّث عدّادات التصعيد حسب تقرير `check_system_health` وينفّذ أي إجراء تعافٍ
+    /// مسجَّل تجاوز مكونه عتبته. `Lagging` يرفع تحذيراً ناعماً فقط ويصفّر عدّاد المكون
+    /// (فهو لم يصل بعد لحالة حرجة). أي مكون غائب عن التقرير (سليم الآن) يُصفَّر عدّاده أيضاً.
+    pub fn escalate(&self, report: &[(String, ComponentHealth)]) {
+        let mut state = self.escalation_state.write().unwrap();
+        let policies = self.escalation_policies.read().unwrap();
+
+        let reported_unresponsive: std::collections::HashSet<&str> = report.iter()
+            .filter(|(_, health)| matches!(health, ComponentHealth::Unresponsive(_)))
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        // تصفير عدّاد أي مكون لم يَرد في هذا التقرير كـ Unresponsive (تعافى أو كان سليماً أصلاً)
+        for (name, (count, _)) in state.iter_mut() {
+            if !reported_unresponsive.contains(name.as_str()) {
+                *count = 0;
+            }
+        }
+
+        for (name, health) in report {
+            match health {
+                ComponentHealth::Lagging(silence) => {
+                    warn!("ESCALATION_SOFT_ALERT: Component '{}' lagging ({}ms)", name, silence);
+                }
+                ComponentHealth::Unresponsive(silence) => {
+                    let entry = state.entry(name.clone()).or_insert((0, None));
+                    entry.0 += 1;
+
+                    if let Some(policy) = policies.get(name) {
+                        if entry.0 >= policy.trigger_after {
+                            let now = crate::utils::time::now_ms();
+                            error!(
+                                "ESCALATION_ACTION: Component '{}' Unresponsive for {} consecutive checks ({}ms) - firing recovery action",
+                                name, entry.0, silence
+                            );
+                            policy.action.execute(name, entry.0);
+                            entry.1 = Some(now);
+                            entry.0 = 0; // Debounce: لا تُعِد نفس الإجراء الجسيم مع كل فحص متعثر لاحق
+                        }
+                    }
+                }
+                ComponentHealth::Healthy => {}
+            }
         }
     }
 
@@ -111,14 +188,7 @@ impl PulseMonitor {
             tokio::time::sleep(Duration::from_millis(500)).await; // فحص كل نصف ثانية
             
             let issues = self.check_system_health();
-            
-            for (name, status) in issues {
-                if let ComponentHealth::Unresponsive(_) = status {
-                    // هنا يمكننا اتخاذ إجراءات عنيفة، مثل إرسال إشارة قتل للنظام
-                    // أو تفعيل "وضع الأمان"
-                    // crate::risk::emergency_stop();
-                }
-            }
+            self.escalate(&issues);
         }
     }
 }
@@ -165,4 +235,42 @@ mod tests {
         let issues_recovered = monitor.check_system_health();
         assert!(issues_recovered.is_empty(), "System should recover after beat");
     }
+
+    struct CountingAction {
+        fired: Arc<AtomicU64>,
+    }
+
+    impl RecoveryAction for CountingAction {
+        fn execute(&self, _component: &str, _consecutive_failures: u32) {
+            self.fired.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_escalation_fires_once_then_debounces() {
+        let monitor = PulseMonitor::new();
+        let fired = Arc::new(AtomicU64::new(0));
+
+        monitor.on_unhealthy("FLAKY_WORKER", 2, Arc::new(CountingAction { fired: fired.clone() }));
+
+        let unresponsive = vec![("FLAKY_WORKER".to_string(), ComponentHealth::Unresponsive(999))];
+
+        // فحص أول متعثر: لم يصل بعد لعتبة التصعيد (2)
+        monitor.escalate(&unresponsive);
+        assert_eq!(fired.load(Ordering::Relaxed), 0);
+
+        // فحص ثانٍ متتالٍ: يصل للعتبة وينفّذ الإجراء، ثم يُصفَّر العدّاد
+        monitor.escalate(&unresponsive);
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+        // فحص ثالث متعثر مباشرة بعد التصفير: لا يُعيد الإجراء فوراً (Debounce)
+        monitor.escalate(&unresponsive);
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+        // نبضة سليمة واحدة (غياب عن التقرير) ثم تعثر جديد متتالٍ يُعيد بناء العدّاد من الصفر
+        monitor.escalate(&[]);
+        monitor.escalate(&unresponsive);
+        monitor.escalate(&unresponsive);
+        assert_eq!(fired.load(Ordering::Relaxed), 2);
+    }
 }
\ No newline at end of file